@@ -0,0 +1,125 @@
+//! Elo ratings for learners and questions
+//!
+//! Each response is treated as a match between a learner and a question: a
+//! correct answer is a learner win. Question ratings that come out of this
+//! act as a data-driven difficulty measure.
+
+use crate::quiz::{Question, QuizSession};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub const INITIAL_RATING: f32 = 1500.0;
+pub const DEFAULT_K: f32 = 32.0;
+
+/// Probability that a learner rated `learner` answers a question rated
+/// `question` correctly
+pub fn expected_score(learner: f32, question: f32) -> f32 {
+    1.0 / (1.0 + 10f32.powf((question - learner) / 400.0))
+}
+
+/// New `(learner, question)` ratings after a single response
+pub fn update(learner: f32, question: f32, correct: bool, k: f32) -> (f32, f32) {
+    let actual = if correct { 1.0 } else { 0.0 };
+    let delta = k * (actual - expected_score(learner, question));
+    (learner + delta, question - delta)
+}
+
+/// Replay every response across `sessions` in submission order, returning
+/// `(learner_ratings, question_ratings)`.
+///
+/// Learners are keyed by `user_id`, falling back to the session id for
+/// anonymous sessions. Responses to questions not in `questions` are ignored.
+pub fn rate_from_sessions(
+    sessions: &[QuizSession],
+    questions: &[Question],
+) -> (HashMap<Uuid, f32>, HashMap<Uuid, f32>) {
+    let mut learner_ratings = HashMap::new();
+    let mut question_ratings: HashMap<Uuid, f32> =
+        questions.iter().map(|q| (q.id, INITIAL_RATING)).collect();
+
+    let mut responses: Vec<_> = sessions
+        .iter()
+        .flat_map(|session| {
+            let learner_id = session.user_id.unwrap_or(session.id);
+            session.responses.iter().map(move |r| (learner_id, r))
+        })
+        .filter(|(_, r)| question_ratings.contains_key(&r.question_id))
+        .collect();
+    responses.sort_by_key(|(_, r)| r.submitted_at);
+
+    for (learner_id, response) in responses {
+        let learner = *learner_ratings.entry(learner_id).or_insert(INITIAL_RATING);
+        let question = question_ratings[&response.question_id];
+
+        let (new_learner, new_question) = update(learner, question, response.is_correct, DEFAULT_K);
+        learner_ratings.insert(learner_id, new_learner);
+        question_ratings.insert(response.question_id, new_question);
+    }
+
+    (learner_ratings, question_ratings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quiz::{Answer, QuestionType};
+
+    fn question() -> Question {
+        Question::new(
+            QuestionType::TrueFalse {
+                statement: "Test".to_string(),
+                correct_answer: true,
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+        )
+    }
+
+    #[test]
+    fn test_equal_ratings_move_symmetrically() {
+        let (learner, question) = update(1500.0, 1500.0, true, 32.0);
+        assert!((learner - 1516.0).abs() < 1e-3);
+        assert!((question - 1484.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_strong_learner_beating_easy_question_barely_moves() {
+        let (learner, question) = update(2000.0, 1000.0, true, 32.0);
+        assert!(learner - 2000.0 < 0.5);
+        assert!(1000.0 - question < 0.5);
+        assert!(learner > 2000.0);
+    }
+
+    #[test]
+    fn test_upset_moves_ratings_a_lot() {
+        let (learner, question) = update(2000.0, 1000.0, false, 32.0);
+        assert!(2000.0 - learner > 30.0);
+        assert!(question - 1000.0 > 30.0);
+    }
+
+    #[test]
+    fn test_rate_from_sessions() {
+        let easy = question();
+        let hard = question();
+        let questions = vec![easy.clone(), hard.clone()];
+
+        let mut sessions = Vec::new();
+        for _ in 0..5 {
+            let mut session = QuizSession::new(Uuid::new_v4(), Some(Uuid::new_v4()));
+            session.start().unwrap();
+            session
+                .submit_answer(&easy, Answer::TrueFalse(true), 10)
+                .unwrap();
+            session
+                .submit_answer(&hard, Answer::TrueFalse(false), 10)
+                .unwrap();
+            sessions.push(session);
+        }
+
+        let (learners, question_ratings) = rate_from_sessions(&sessions, &questions);
+        assert_eq!(learners.len(), 5);
+        assert!(question_ratings[&hard.id] > question_ratings[&easy.id]);
+        assert!(question_ratings[&easy.id] < INITIAL_RATING);
+    }
+}
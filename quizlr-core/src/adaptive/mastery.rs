@@ -0,0 +1,164 @@
+//! Per-topic mastery tracking and review recommendations
+
+use crate::quiz::Question;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// How much a single response moves a topic's mastery estimate
+const LEARNING_RATE: f32 = 0.3;
+
+/// How far above current mastery a question should be to offer productive
+/// challenge without being discouraging
+const CHALLENGE_OFFSET: f32 = 0.1;
+
+/// Tracks an estimate of the learner's mastery (0.0 to 1.0) for each topic
+/// they have answered questions on, as an exponential moving average of
+/// correctness.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MasteryTracker {
+    topics: HashMap<Uuid, f32>,
+}
+
+impl MasteryTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, topic_id: Uuid, is_correct: bool) {
+        let outcome = if is_correct { 1.0 } else { 0.0 };
+        let mastery = self.topics.entry(topic_id).or_insert(outcome);
+        *mastery += LEARNING_RATE * (outcome - *mastery);
+    }
+
+    pub fn set_mastery(&mut self, topic_id: Uuid, mastery: f32) {
+        self.topics.insert(topic_id, mastery.clamp(0.0, 1.0));
+    }
+
+    pub fn mastery(&self, topic_id: Uuid) -> Option<f32> {
+        self.topics.get(&topic_id).copied()
+    }
+
+    pub fn topics(&self) -> impl Iterator<Item = (Uuid, f32)> + '_ {
+        self.topics.iter().map(|(id, mastery)| (*id, *mastery))
+    }
+}
+
+/// Pick up to `n` questions from `bank` for review, drawing from the
+/// learner's weakest topics first. Within a topic, questions whose difficulty
+/// sits just above current mastery are preferred. Topics with no questions in
+/// the bank are skipped.
+pub fn recommend_review<'a>(
+    tracker: &MasteryTracker,
+    bank: &'a [Question],
+    n: usize,
+) -> Vec<&'a Question> {
+    let mut by_topic: HashMap<Uuid, Vec<&Question>> = HashMap::new();
+    for question in bank {
+        if tracker.mastery(question.topic_id).is_some() {
+            by_topic
+                .entry(question.topic_id)
+                .or_default()
+                .push(question);
+        }
+    }
+
+    let mut topics: Vec<(Uuid, f32)> = tracker
+        .topics()
+        .filter(|(id, _)| by_topic.contains_key(id))
+        .collect();
+    topics.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+
+    let mut recommended = Vec::with_capacity(n);
+    for (topic_id, mastery) in topics {
+        let target = (mastery + CHALLENGE_OFFSET).min(1.0);
+        let mut questions = by_topic.remove(&topic_id).unwrap_or_default();
+        questions.sort_by(|a, b| {
+            (a.difficulty - target)
+                .abs()
+                .total_cmp(&(b.difficulty - target).abs())
+        });
+
+        for question in questions {
+            if recommended.len() == n {
+                return recommended;
+            }
+            recommended.push(question);
+        }
+    }
+
+    recommended
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quiz::QuestionType;
+
+    fn question(topic_id: Uuid, difficulty: f32) -> Question {
+        Question::new(
+            QuestionType::TrueFalse {
+                statement: "Test".to_string(),
+                correct_answer: true,
+                explanation: None,
+            },
+            topic_id,
+            difficulty,
+        )
+    }
+
+    #[test]
+    fn test_record_moves_mastery() {
+        let topic = Uuid::new_v4();
+        let mut tracker = MasteryTracker::new();
+
+        tracker.record(topic, true);
+        assert_eq!(tracker.mastery(topic), Some(1.0));
+
+        tracker.record(topic, false);
+        assert!((tracker.mastery(topic).unwrap() - 0.7).abs() < 1e-6);
+        assert!(tracker.mastery(Uuid::new_v4()).is_none());
+    }
+
+    #[test]
+    fn test_weak_topic_questions_dominate() {
+        let weak = Uuid::new_v4();
+        let strong = Uuid::new_v4();
+        let mut tracker = MasteryTracker::new();
+        tracker.set_mastery(weak, 0.2);
+        tracker.set_mastery(strong, 0.9);
+
+        let bank = vec![
+            question(strong, 0.9),
+            question(weak, 0.9),
+            question(weak, 0.3),
+            question(strong, 0.95),
+            question(weak, 0.5),
+        ];
+
+        let picks = recommend_review(&tracker, &bank, 3);
+        assert_eq!(picks.len(), 3);
+        assert!(picks.iter().all(|q| q.topic_id == weak));
+        // Slightly above mastery 0.2 comes first
+        assert_eq!(picks[0].difficulty, 0.3);
+
+        let picks = recommend_review(&tracker, &bank, 4);
+        assert_eq!(picks.iter().filter(|q| q.topic_id == weak).count(), 3);
+        assert_eq!(picks[3].topic_id, strong);
+    }
+
+    #[test]
+    fn test_topics_without_questions_are_skipped() {
+        let empty_topic = Uuid::new_v4();
+        let topic = Uuid::new_v4();
+        let mut tracker = MasteryTracker::new();
+        tracker.set_mastery(empty_topic, 0.0);
+        tracker.set_mastery(topic, 0.5);
+
+        let bank = vec![question(topic, 0.6), question(Uuid::new_v4(), 0.1)];
+        let picks = recommend_review(&tracker, &bank, 5);
+
+        assert_eq!(picks.len(), 1);
+        assert_eq!(picks[0].topic_id, topic);
+    }
+}
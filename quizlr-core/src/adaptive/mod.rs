@@ -1,5 +1,14 @@
 use serde::{Deserialize, Serialize};
 
+pub mod elo;
+mod mastery;
+mod practice;
+mod session;
+
+pub use mastery::{recommend_review, MasteryTracker};
+pub use practice::PracticeLoop;
+pub use session::{select_next_question, AdaptiveSession};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AdaptiveEngine {
     // Placeholder for adaptive learning algorithm
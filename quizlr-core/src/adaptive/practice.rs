@@ -0,0 +1,234 @@
+//! Practice loops that keep serving questions until topics are mastered
+//!
+//! Each question carries an SM-2 style review schedule. Intervals are
+//! counted in questions served rather than days, so items answered wrongly
+//! come back soon within the same sitting while well-known items drift
+//! further out. Topic mastery comes from a [`MasteryTracker`].
+
+use super::MasteryTracker;
+use crate::error::{QuizlrError, Result};
+use crate::quiz::{Answer, Question};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const DEFAULT_MASTERY_THRESHOLD: f32 = 0.8;
+const DEFAULT_SUSTAIN: usize = 3;
+
+const INITIAL_EASE: f32 = 2.5;
+const MIN_EASE: f32 = 1.3;
+const QUALITY_CORRECT: f32 = 4.0;
+const QUALITY_INCORRECT: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy)]
+struct Schedule {
+    repetitions: u32,
+    interval: u64,
+    ease: f32,
+    due: u64,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            repetitions: 0,
+            interval: 0,
+            ease: INITIAL_EASE,
+            due: 0,
+        }
+    }
+}
+
+impl Schedule {
+    /// SM-2 update after a response at turn `now`
+    fn review(&mut self, is_correct: bool, now: u64) {
+        let quality = if is_correct {
+            QUALITY_CORRECT
+        } else {
+            QUALITY_INCORRECT
+        };
+        let miss = 5.0 - quality;
+        self.ease = (self.ease + 0.1 - miss * (0.08 + miss * 0.02)).max(MIN_EASE);
+
+        if is_correct {
+            self.repetitions += 1;
+            self.interval = match self.repetitions {
+                1 => 1,
+                2 => 6,
+                _ => (self.interval as f32 * self.ease).round() as u64,
+            };
+        } else {
+            self.repetitions = 0;
+            self.interval = 1;
+        }
+        self.due = now + self.interval;
+    }
+}
+
+pub struct PracticeLoop {
+    bank: Vec<Question>,
+    tracker: MasteryTracker,
+    schedules: Vec<Schedule>,
+    mastery_threshold: f32,
+    sustain: usize,
+    streaks: HashMap<Uuid, usize>,
+    turn: u64,
+    current: Option<usize>,
+}
+
+impl PracticeLoop {
+    pub fn new(bank: Vec<Question>, tracker: MasteryTracker) -> Self {
+        Self {
+            schedules: vec![Schedule::default(); bank.len()],
+            bank,
+            tracker,
+            mastery_threshold: DEFAULT_MASTERY_THRESHOLD,
+            sustain: DEFAULT_SUSTAIN,
+            streaks: HashMap::new(),
+            turn: 0,
+            current: None,
+        }
+    }
+
+    /// A topic counts as mastered once its mastery has stayed at or above
+    /// `threshold` for `sustain` responses in a row
+    pub fn with_mastery_threshold(mut self, threshold: f32, sustain: usize) -> Self {
+        self.mastery_threshold = threshold.clamp(0.0, 1.0);
+        self.sustain = sustain.max(1);
+        self
+    }
+
+    pub fn tracker(&self) -> &MasteryTracker {
+        &self.tracker
+    }
+
+    pub fn into_tracker(self) -> MasteryTracker {
+        self.tracker
+    }
+
+    pub fn is_mastered(&self, topic_id: Uuid) -> bool {
+        self.streaks.get(&topic_id).copied().unwrap_or(0) >= self.sustain
+    }
+
+    /// Whether every topic in the bank is mastered
+    pub fn is_finished(&self) -> bool {
+        self.bank.iter().all(|q| self.is_mastered(q.topic_id))
+    }
+
+    /// The question to practice next: due items before ones scheduled
+    /// later, weaker topics first, skipping mastered topics. `None` once
+    /// everything is mastered. Calling this again before submitting
+    /// returns the same question.
+    pub fn next_question(&mut self) -> Option<&Question> {
+        if self.current.is_none() {
+            self.current = (0..self.bank.len())
+                .filter(|&i| !self.is_mastered(self.bank[i].topic_id))
+                .min_by(|&a, &b| {
+                    let due_a = self.schedules[a].due.max(self.turn);
+                    let due_b = self.schedules[b].due.max(self.turn);
+                    due_a
+                        .cmp(&due_b)
+                        .then(self.topic_mastery(a).total_cmp(&self.topic_mastery(b)))
+                        .then(self.schedules[a].due.cmp(&self.schedules[b].due))
+                });
+        }
+        self.current.map(|index| &self.bank[index])
+    }
+
+    /// Grade an answer to the current question, updating its schedule and
+    /// the topic's mastery
+    pub fn submit(&mut self, answer: &Answer) -> Result<bool> {
+        let index = self
+            .current
+            .ok_or_else(|| QuizlrError::QuizEngine("No question is pending".to_string()))?;
+        let question = &self.bank[index];
+        let is_correct = question
+            .validate_answer(answer)
+            .map_err(QuizlrError::InvalidInput)?;
+        let topic_id = question.topic_id;
+
+        self.schedules[index].review(is_correct, self.turn);
+        self.tracker.record(topic_id, is_correct);
+        let mastery = self.tracker.mastery(topic_id).unwrap_or(0.0);
+        let streak = self.streaks.entry(topic_id).or_insert(0);
+        if mastery >= self.mastery_threshold {
+            *streak += 1;
+        } else {
+            *streak = 0;
+        }
+
+        self.turn += 1;
+        self.current = None;
+        Ok(is_correct)
+    }
+
+    /// Mastery of a question's topic, treating unseen topics as unlearned
+    fn topic_mastery(&self, index: usize) -> f32 {
+        self.tracker
+            .mastery(self.bank[index].topic_id)
+            .unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quiz::QuestionType;
+
+    fn question(topic_id: Uuid, statement: &str) -> Question {
+        Question::new(
+            QuestionType::TrueFalse {
+                statement: statement.to_string(),
+                correct_answer: true,
+                explanation: None,
+            },
+            topic_id,
+            0.5,
+        )
+    }
+
+    #[test]
+    fn test_repeated_correct_answers_reach_mastery() {
+        let topic = Uuid::new_v4();
+        let bank: Vec<Question> = (0..3)
+            .map(|i| question(topic, &format!("Fact {}", i)))
+            .collect();
+        let mut practice = PracticeLoop::new(bank, MasteryTracker::new());
+
+        let mut served = 0;
+        while practice.next_question().is_some() {
+            assert!(practice.submit(&Answer::TrueFalse(true)).unwrap());
+            served += 1;
+            assert!(served <= 10, "never reached mastery");
+        }
+
+        assert!(practice.is_mastered(topic));
+        assert!(practice.is_finished());
+        assert_eq!(served, DEFAULT_SUSTAIN);
+        assert!(practice.submit(&Answer::TrueFalse(true)).is_err());
+    }
+
+    #[test]
+    fn test_missed_items_and_weak_topics_come_first() {
+        let weak = Uuid::new_v4();
+        let strong = Uuid::new_v4();
+        let mut tracker = MasteryTracker::new();
+        tracker.set_mastery(weak, 0.2);
+        tracker.set_mastery(strong, 0.7);
+        let bank = vec![question(strong, "Strong"), question(weak, "Weak")];
+        let mut practice = PracticeLoop::new(bank, tracker);
+
+        assert_eq!(practice.next_question().unwrap().topic_id, weak);
+        // A miss keeps mastery low and schedules the item again next turn
+        assert!(!practice.submit(&Answer::TrueFalse(false)).unwrap());
+        assert!(!practice.is_mastered(weak));
+
+        // Still due and still the weakest after one correct answer
+        for _ in 0..2 {
+            assert_eq!(practice.next_question().unwrap().topic_id, weak);
+            practice.submit(&Answer::TrueFalse(true)).unwrap();
+        }
+
+        // Two in a row push the weak item out, so the strong one is due first
+        assert_eq!(practice.next_question().unwrap().topic_id, strong);
+    }
+}
@@ -0,0 +1,233 @@
+//! Adaptive quiz sessions driven from a question bank
+//!
+//! Rather than working through a fixed quiz, an [`AdaptiveSession`] keeps a
+//! running estimate of the learner's ability (on the same 0.0 to 1.0 scale as
+//! question difficulty) and always serves the unasked question closest to
+//! it. The estimate moves up on correct answers and down on incorrect ones.
+//! The step only shrinks when the direction reverses, so a learner still far
+//! from the estimate keeps moving it quickly, while one answering around
+//! their level brings it to rest.
+
+use crate::error::{QuizlrError, Result};
+use crate::quiz::{Answer, Question};
+use std::collections::{HashSet, VecDeque};
+use uuid::Uuid;
+
+const INITIAL_ABILITY: f32 = 0.5;
+const INITIAL_STEP: f32 = 0.2;
+const DEFAULT_TOLERANCE: f32 = 0.05;
+
+/// Number of answers the ability estimate must stay within the tolerance
+/// over before the session counts as stabilized
+const STABILITY_WINDOW: usize = 2;
+
+/// The unasked question in `bank` whose difficulty is closest to `ability`
+pub fn select_next_question<'a>(
+    bank: &'a [Question],
+    ability: f32,
+    asked: &HashSet<Uuid>,
+) -> Option<&'a Question> {
+    bank.iter()
+        .filter(|q| !asked.contains(&q.id))
+        .min_by(|a, b| {
+            (a.difficulty - ability)
+                .abs()
+                .total_cmp(&(b.difficulty - ability).abs())
+        })
+}
+
+pub struct AdaptiveSession {
+    bank: Vec<Question>,
+    target_length: usize,
+    tolerance: f32,
+    ability: f32,
+    asked: HashSet<Uuid>,
+    answered: usize,
+    reversals: usize,
+    last_correct: Option<bool>,
+    recent_abilities: VecDeque<f32>,
+    current: Option<usize>,
+    stabilized: bool,
+}
+
+impl AdaptiveSession {
+    pub fn new(bank: Vec<Question>, target_length: usize) -> Self {
+        Self {
+            bank,
+            target_length,
+            tolerance: DEFAULT_TOLERANCE,
+            ability: INITIAL_ABILITY,
+            asked: HashSet::new(),
+            answered: 0,
+            reversals: 0,
+            last_correct: None,
+            recent_abilities: VecDeque::with_capacity(STABILITY_WINDOW + 1),
+            current: None,
+            stabilized: false,
+        }
+    }
+
+    /// Stop early once the ability estimate has stayed within `tolerance`
+    /// over the last few answers
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn with_initial_ability(mut self, ability: f32) -> Self {
+        self.ability = ability.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn ability(&self) -> f32 {
+        self.ability
+    }
+
+    pub fn answered(&self) -> usize {
+        self.answered
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.answered >= self.target_length
+            || self.stabilized
+            || (self.current.is_none() && self.asked.len() >= self.bank.len())
+    }
+
+    /// The question to answer next, or `None` once the session is finished.
+    /// Calling this again before answering returns the same question.
+    pub fn next_question(&mut self) -> Option<&Question> {
+        if self.current.is_none() {
+            if self.is_finished() {
+                return None;
+            }
+            let next_id = select_next_question(&self.bank, self.ability, &self.asked)?.id;
+            self.current = self.bank.iter().position(|q| q.id == next_id);
+        }
+        self.current.map(|index| &self.bank[index])
+    }
+
+    /// Grade an answer to the current question and update the ability
+    /// estimate
+    pub fn submit_answer(&mut self, answer: &Answer) -> Result<bool> {
+        let index = self
+            .current
+            .ok_or_else(|| QuizlrError::QuizEngine("No question is pending".to_string()))?;
+        let question = &self.bank[index];
+        let is_correct = question
+            .validate_answer(answer)
+            .map_err(QuizlrError::InvalidInput)?;
+
+        if self.recent_abilities.is_empty() {
+            self.recent_abilities.push_back(self.ability);
+        }
+        if self.last_correct.is_some_and(|last| last != is_correct) {
+            self.reversals += 1;
+        }
+        let step = INITIAL_STEP / (self.reversals as f32 + 1.0);
+        let delta = if is_correct { step } else { -step };
+        self.ability = (self.ability + delta).clamp(0.0, 1.0);
+
+        self.asked.insert(question.id);
+        self.answered += 1;
+        self.last_correct = Some(is_correct);
+        self.current = None;
+
+        self.recent_abilities.push_back(self.ability);
+        if self.recent_abilities.len() > STABILITY_WINDOW + 1 {
+            self.recent_abilities.pop_front();
+        }
+        self.stabilized = self.recent_abilities.len() > STABILITY_WINDOW && {
+            let (low, high) = self
+                .recent_abilities
+                .iter()
+                .fold((f32::MAX, f32::MIN), |(low, high), &a| {
+                    (low.min(a), high.max(a))
+                });
+            high - low < self.tolerance
+        };
+
+        Ok(is_correct)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quiz::QuestionType;
+
+    fn bank() -> Vec<Question> {
+        (0..=50)
+            .map(|i| {
+                Question::new(
+                    QuestionType::TrueFalse {
+                        statement: format!("Question {}", i),
+                        correct_answer: true,
+                        explanation: None,
+                    },
+                    Uuid::new_v4(),
+                    i as f32 * 0.02,
+                )
+            })
+            .collect()
+    }
+
+    /// Answers correctly exactly when the question is at or below its level
+    fn run_consistent_responder(session: &mut AdaptiveSession, level: f32) -> Vec<f32> {
+        let mut served = Vec::new();
+        while let Some(question) = session.next_question() {
+            let difficulty = question.difficulty;
+            served.push(difficulty);
+            session
+                .submit_answer(&Answer::TrueFalse(difficulty <= level))
+                .unwrap();
+        }
+        served
+    }
+
+    #[test]
+    fn test_consistent_responder_converges() {
+        let mut session = AdaptiveSession::new(bank(), 30);
+        let served = run_consistent_responder(&mut session, 0.6);
+
+        // Stabilizes well before the target length
+        assert!(served.len() < 30);
+        assert!((session.ability() - 0.6).abs() < 0.05);
+        for difficulty in served.iter().rev().take(5) {
+            assert!((difficulty - 0.6).abs() <= 0.1, "served {}", difficulty);
+        }
+    }
+
+    #[test]
+    fn test_stops_at_target_length() {
+        let mut session = AdaptiveSession::new(bank(), 5).with_tolerance(0.0);
+        let served = run_consistent_responder(&mut session, 0.6);
+        assert_eq!(served.len(), 5);
+        assert!(session.next_question().is_none());
+    }
+
+    #[test]
+    fn test_next_is_stable_until_answered() {
+        let mut session = AdaptiveSession::new(bank(), 5);
+        let first = session.next_question().unwrap().id;
+        assert_eq!(session.next_question().unwrap().id, first);
+
+        assert!(AdaptiveSession::new(bank(), 5)
+            .submit_answer(&Answer::TrueFalse(true))
+            .is_err());
+    }
+
+    #[test]
+    fn test_contradictory_answers_keep_session_running() {
+        // Runs of four right then four wrong, whatever the difficulty, keep
+        // dragging the estimate back and forth
+        let mut session = AdaptiveSession::new(bank(), 20);
+        let mut served: usize = 0;
+        while session.next_question().is_some() {
+            let answer = Answer::TrueFalse((served / 4).is_multiple_of(2));
+            session.submit_answer(&answer).unwrap();
+            served += 1;
+        }
+        assert_eq!(served, 20);
+        assert_eq!(session.answered(), 20);
+    }
+}
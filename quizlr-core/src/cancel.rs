@@ -0,0 +1,46 @@
+//! Explicit cancellation of long-running async calls
+//!
+//! Every async method in this crate is safe to drop mid-flight: state the
+//! caller can see is only changed once the work it depends on has
+//! finished, and storage writes land whole or not at all. Dropping the
+//! future is therefore always a valid way to cancel. A
+//! [`CancellationToken`] is for when the code that wants to cancel doesn't
+//! own the future, such as a UI handler for navigating away.
+
+use crate::error::{QuizlrError, Result};
+use std::future::Future;
+pub use tokio_util::sync::CancellationToken;
+
+/// Run `future` until it finishes or `token` is cancelled, whichever comes
+/// first. On cancellation the future is dropped and
+/// [`QuizlrError::Cancelled`] returned; a token cancelled beforehand stops
+/// the future before it starts.
+pub async fn cancellable<T>(
+    token: &CancellationToken,
+    future: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::select! {
+        biased;
+        _ = token.cancelled() => Err(QuizlrError::Cancelled),
+        result = future => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancelled_token_stops_future() {
+        let token = CancellationToken::new();
+        assert_eq!(cancellable(&token, async { Ok(3) }).await.unwrap(), 3);
+
+        let waiting = token.clone();
+        let result: Result<()> = cancellable(&waiting, async {
+            token.cancel();
+            std::future::pending().await
+        })
+        .await;
+        assert!(matches!(result, Err(QuizlrError::Cancelled)));
+    }
+}
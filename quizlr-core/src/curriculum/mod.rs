@@ -1,5 +1,10 @@
+use crate::clock::{Clock, SystemClock};
+use crate::error::{QuizlrError, Result};
+use crate::graph::KnowledgeGraph;
+use crate::quiz::{Quiz, SessionSummary};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,7 +18,13 @@ pub struct Curriculum {
 
 impl Curriculum {
     pub fn new(title: String, description: String) -> Self {
-        let now = Utc::now();
+        Self::with_clock(title, description, &SystemClock)
+    }
+
+    /// Create a curriculum stamped with the time from `clock` instead of the
+    /// system clock
+    pub fn with_clock(title: String, description: String, clock: &dyn Clock) -> Self {
+        let now = clock.now();
         Self {
             id: Uuid::new_v4(),
             title,
@@ -23,3 +34,199 @@ impl Curriculum {
         }
     }
 }
+
+/// Roll up several session scores into a single weighted grade.
+///
+/// Each entry pairs a session summary with its weight. Zero-weight entries
+/// are ignored, and an empty (or all-zero) input yields 0.0. Negative weights
+/// are rejected.
+pub fn weighted_grade(entries: &[(SessionSummary, f32)]) -> Result<f32> {
+    if let Some((_, weight)) = entries.iter().find(|(_, w)| *w < 0.0 || w.is_nan()) {
+        return Err(QuizlrError::InvalidInput(format!(
+            "Weights must be non-negative, got {}",
+            weight
+        )));
+    }
+
+    let (weighted_sum, total_weight) = entries
+        .iter()
+        .filter(|(_, weight)| *weight > 0.0)
+        .fold((0.0, 0.0), |(sum, total), (summary, weight)| {
+            (sum + summary.score * weight, total + weight)
+        });
+
+    if total_weight > 0.0 {
+        Ok(weighted_sum / total_weight)
+    } else {
+        Ok(0.0)
+    }
+}
+
+/// A prerequisite topic the learner hasn't mastered yet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MissingPrerequisite {
+    pub topic_id: Uuid,
+    /// Current mastery, 0.0 if the topic has never been measured
+    pub mastery: f32,
+}
+
+/// Outcome of checking a learner against a quiz's prerequisites
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReadinessReport {
+    /// Prerequisites below the threshold, in the order they were found
+    pub missing: Vec<MissingPrerequisite>,
+}
+
+impl ReadinessReport {
+    pub fn is_ready(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Check whether a learner has mastered the direct prerequisites of every
+/// topic the quiz covers.
+///
+/// Prerequisites that are themselves topics of the quiz are not required,
+/// since the quiz tests them anyway. Topics missing from `mastery` count as
+/// unlearned.
+pub fn is_ready(
+    graph: &KnowledgeGraph,
+    mastery: &HashMap<Uuid, f32>,
+    quiz: &Quiz,
+    threshold: f32,
+) -> ReadinessReport {
+    let mut seen = HashSet::new();
+    let missing = quiz
+        .topic_ids
+        .iter()
+        .flat_map(|&topic| graph.prerequisites(topic))
+        .map(|(prerequisite, _)| prerequisite)
+        .filter(|prerequisite| !quiz.topic_ids.contains(prerequisite))
+        .filter(|&prerequisite| seen.insert(prerequisite))
+        .filter_map(|topic_id| {
+            let level = mastery.get(&topic_id).copied().unwrap_or(0.0);
+            (level < threshold).then_some(MissingPrerequisite {
+                topic_id,
+                mastery: level,
+            })
+        })
+        .collect();
+
+    ReadinessReport { missing }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quiz::{Question, QuestionType, QuizBuilder};
+    use crate::test_utils::builders::factories::add_topic;
+    use chrono::Duration;
+    use uuid::Uuid;
+
+    fn summary(score: f32) -> SessionSummary {
+        SessionSummary {
+            session_id: Uuid::new_v4(),
+            quiz_id: Uuid::new_v4(),
+            score,
+            correct_answers: 0,
+            total_questions: 0,
+            skipped_questions: 0,
+            total_time_seconds: 0,
+            duration: Duration::zero(),
+            average_time_per_question: 0,
+            completion_rate: 0.0,
+            answered_question_ids: Vec::new(),
+            abandon_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_weighted_grade_equal_weights() {
+        let entries = vec![
+            (summary(0.6), 1.0),
+            (summary(0.8), 1.0),
+            (summary(1.0), 1.0),
+        ];
+        let grade = weighted_grade(&entries).unwrap();
+        assert!((grade - 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_weighted_grade_skewed_weights() {
+        // Final exam counts three times as much as the midterm
+        let entries = vec![
+            (summary(0.5), 1.0),
+            (summary(0.9), 3.0),
+            (summary(0.0), 0.0),
+        ];
+        let grade = weighted_grade(&entries).unwrap();
+        assert!((grade - 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_weighted_grade_all_zero_or_empty() {
+        let entries = vec![(summary(0.9), 0.0), (summary(0.4), 0.0)];
+        assert_eq!(weighted_grade(&entries).unwrap(), 0.0);
+        assert_eq!(weighted_grade(&[]).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_weighted_grade_rejects_negative_weight() {
+        let entries = vec![(summary(0.9), 1.0), (summary(0.4), -1.0)];
+        assert!(matches!(
+            weighted_grade(&entries),
+            Err(QuizlrError::InvalidInput(_))
+        ));
+    }
+
+    fn quiz_on(topic_id: Uuid) -> Quiz {
+        let question = Question::new(
+            QuestionType::TrueFalse {
+                statement: "Closures capture their environment".to_string(),
+                correct_answer: true,
+                explanation: None,
+            },
+            topic_id,
+            0.5,
+        );
+        QuizBuilder::new("Closures".to_string())
+            .add_question(question)
+            .build()
+    }
+
+    #[test]
+    fn test_is_ready_missing_one_prerequisite() {
+        let mut graph = KnowledgeGraph::new();
+        let variables = add_topic(&mut graph, "Variables");
+        let functions = add_topic(&mut graph, "Functions");
+        let closures = add_topic(&mut graph, "Closures");
+        graph.add_prerequisite(variables, closures, 1.0).unwrap();
+        graph.add_prerequisite(functions, closures, 1.0).unwrap();
+
+        let mastery = HashMap::from([(variables, 0.9), (functions, 0.4)]);
+        let report = is_ready(&graph, &mastery, &quiz_on(closures), 0.7);
+        assert!(!report.is_ready());
+        assert_eq!(
+            report.missing,
+            vec![MissingPrerequisite {
+                topic_id: functions,
+                mastery: 0.4,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_is_ready_fully_ready() {
+        let mut graph = KnowledgeGraph::new();
+        let variables = add_topic(&mut graph, "Variables");
+        let functions = add_topic(&mut graph, "Functions");
+        let closures = add_topic(&mut graph, "Closures");
+        graph.add_prerequisite(variables, closures, 1.0).unwrap();
+        graph.add_prerequisite(functions, closures, 1.0).unwrap();
+
+        let mastery = HashMap::from([(variables, 0.9), (functions, 0.7)]);
+        let report = is_ready(&graph, &mastery, &quiz_on(closures), 0.7);
+        assert!(report.is_ready());
+        assert!(report.missing.is_empty());
+    }
+}
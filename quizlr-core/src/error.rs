@@ -35,6 +35,9 @@ pub enum QuizlrError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("Operation cancelled")]
+    Cancelled,
+
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
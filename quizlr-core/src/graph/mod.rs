@@ -1,7 +1,14 @@
-use petgraph::graph::DiGraph;
+use crate::error::{QuizlrError, Result};
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+/// Share of a topic's rolled-up mastery that comes from its prerequisites
+const PREREQUISITE_BLEND: f32 = 0.5;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TopicNode {
     pub id: Uuid,
@@ -9,6 +16,17 @@ pub struct TopicNode {
     pub description: String,
 }
 
+impl TopicNode {
+    /// A topic with a fresh id and no description
+    pub fn new(name: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name,
+            description: String::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TopicEdge {
     pub relationship: RelationshipType,
@@ -22,17 +40,83 @@ pub enum RelationshipType {
     Subtopic,
 }
 
+/// Topics and how they relate. Edges point from a topic to the topic that
+/// builds on it, so a prerequisite edge runs prerequisite -> dependent.
 pub struct KnowledgeGraph {
-    #[allow(dead_code)] // Will be used in future implementations
     graph: DiGraph<TopicNode, TopicEdge>,
+    nodes: HashMap<Uuid, NodeIndex>,
 }
 
 impl KnowledgeGraph {
     pub fn new() -> Self {
         Self {
             graph: DiGraph::new(),
+            nodes: HashMap::new(),
         }
     }
+
+    /// Add a topic, replacing any existing topic with the same id
+    pub fn add_topic(&mut self, topic: TopicNode) {
+        match self.nodes.get(&topic.id) {
+            Some(&index) => self.graph[index] = topic,
+            None => {
+                let id = topic.id;
+                let index = self.graph.add_node(topic);
+                self.nodes.insert(id, index);
+            }
+        }
+    }
+
+    pub fn topic(&self, id: Uuid) -> Option<&TopicNode> {
+        self.nodes.get(&id).map(|&index| &self.graph[index])
+    }
+
+    pub fn len(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.graph.node_count() == 0
+    }
+
+    /// Relate two topics already in the graph, `from` leading to `to`
+    pub fn add_edge(&mut self, from: Uuid, to: Uuid, edge: TopicEdge) -> Result<()> {
+        let from = self.index_of(from)?;
+        let to = self.index_of(to)?;
+        self.graph.add_edge(from, to, edge);
+        Ok(())
+    }
+
+    /// Record that `prerequisite` should be learned before `topic`
+    pub fn add_prerequisite(&mut self, prerequisite: Uuid, topic: Uuid, weight: f32) -> Result<()> {
+        self.add_edge(
+            prerequisite,
+            topic,
+            TopicEdge {
+                relationship: RelationshipType::Prerequisite,
+                weight,
+            },
+        )
+    }
+
+    /// Direct prerequisites of `topic` with their edge weights
+    pub fn prerequisites(&self, topic: Uuid) -> Vec<(Uuid, f32)> {
+        let Some(&index) = self.nodes.get(&topic) else {
+            return Vec::new();
+        };
+        self.graph
+            .edges_directed(index, Direction::Incoming)
+            .filter(|edge| matches!(edge.weight().relationship, RelationshipType::Prerequisite))
+            .map(|edge| (self.graph[edge.source()].id, edge.weight().weight))
+            .collect()
+    }
+
+    fn index_of(&self, id: Uuid) -> Result<NodeIndex> {
+        self.nodes
+            .get(&id)
+            .copied()
+            .ok_or_else(|| QuizlrError::Graph(format!("Unknown topic {}", id)))
+    }
 }
 
 impl Default for KnowledgeGraph {
@@ -40,3 +124,111 @@ impl Default for KnowledgeGraph {
         Self::new()
     }
 }
+
+/// Effective mastery of each topic once its prerequisites are accounted for.
+///
+/// A topic's rolled-up mastery blends its own mastery with the
+/// edge-weighted mean of its prerequisites' rolled-up mastery, so weakness
+/// anywhere down a prerequisite chain carries through. Prerequisites can
+/// only pull a topic down, never lift it above its own mastery. Topics
+/// missing from `raw` count as 0.0; entries for topics outside the graph
+/// are passed through unchanged. Prerequisite cycles are cut where the
+/// walk first revisits a topic.
+pub fn rollup_mastery(graph: &KnowledgeGraph, raw: &HashMap<Uuid, f32>) -> HashMap<Uuid, f32> {
+    let mut rolled = raw.clone();
+    let mut done = HashSet::new();
+    for &id in graph.nodes.keys() {
+        rollup_topic(graph, raw, id, &mut rolled, &mut done, &mut HashSet::new());
+    }
+    rolled
+}
+
+fn rollup_topic(
+    graph: &KnowledgeGraph,
+    raw: &HashMap<Uuid, f32>,
+    id: Uuid,
+    rolled: &mut HashMap<Uuid, f32>,
+    done: &mut HashSet<Uuid>,
+    visiting: &mut HashSet<Uuid>,
+) -> f32 {
+    let own = raw.get(&id).copied().unwrap_or(0.0);
+    if done.contains(&id) {
+        return rolled[&id];
+    }
+    if !visiting.insert(id) {
+        return own;
+    }
+
+    let mut weighted = 0.0;
+    let mut total_weight = 0.0;
+    for (prerequisite, weight) in graph.prerequisites(id) {
+        let weight = weight.max(0.0);
+        weighted += weight * rollup_topic(graph, raw, prerequisite, rolled, done, visiting);
+        total_weight += weight;
+    }
+
+    let effective = if total_weight > 0.0 {
+        let prerequisites = weighted / total_weight;
+        own.min((1.0 - PREREQUISITE_BLEND) * own + PREREQUISITE_BLEND * prerequisites)
+    } else {
+        own
+    };
+
+    visiting.remove(&id);
+    done.insert(id);
+    rolled.insert(id, effective);
+    effective
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::builders::factories::add_topic;
+
+    #[test]
+    fn test_rollup_two_level_prerequisite_chain() {
+        let mut graph = KnowledgeGraph::new();
+        let variables = add_topic(&mut graph, "Variables");
+        let functions = add_topic(&mut graph, "Functions");
+        let closures = add_topic(&mut graph, "Closures");
+        graph.add_prerequisite(variables, functions, 1.0).unwrap();
+        graph.add_prerequisite(functions, closures, 1.0).unwrap();
+
+        let raw = HashMap::from([(variables, 0.2), (functions, 1.0), (closures, 1.0)]);
+        let rolled = rollup_mastery(&graph, &raw);
+        assert!((rolled[&variables] - 0.2).abs() < 1e-6);
+        assert!((rolled[&functions] - 0.6).abs() < 1e-6);
+        assert!((rolled[&closures] - 0.8).abs() < 1e-6);
+
+        // An unmeasured prerequisite counts as unlearned
+        let raw = HashMap::from([(functions, 1.0), (closures, 1.0)]);
+        let rolled = rollup_mastery(&graph, &raw);
+        assert_eq!(rolled[&variables], 0.0);
+        assert!((rolled[&functions] - 0.5).abs() < 1e-6);
+        assert!((rolled[&closures] - 0.75).abs() < 1e-6);
+
+        // Strong prerequisites don't lift a weak topic
+        let raw = HashMap::from([(variables, 1.0), (functions, 1.0), (closures, 0.3)]);
+        assert!((rollup_mastery(&graph, &raw)[&closures] - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_unknown_topics_and_cycles() {
+        let mut graph = KnowledgeGraph::new();
+        let a = add_topic(&mut graph, "A");
+        let b = add_topic(&mut graph, "B");
+        assert!(matches!(
+            graph.add_prerequisite(a, Uuid::new_v4(), 1.0),
+            Err(QuizlrError::Graph(_))
+        ));
+
+        graph.add_prerequisite(a, b, 1.0).unwrap();
+        graph.add_prerequisite(b, a, 1.0).unwrap();
+        let outside = Uuid::new_v4();
+        let raw = HashMap::from([(a, 1.0), (b, 1.0), (outside, 0.4)]);
+        let rolled = rollup_mastery(&graph, &raw);
+        assert_eq!(rolled[&a], 1.0);
+        assert_eq!(rolled[&b], 1.0);
+        assert_eq!(rolled[&outside], 0.4);
+    }
+}
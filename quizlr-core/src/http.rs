@@ -0,0 +1,179 @@
+//! Transport-agnostic HTTP, so API clients can run over reqwest natively,
+//! over fetch in the browser, or against canned responses in tests.
+
+use crate::error::QuizlrError;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Put,
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: HttpMethod,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+}
+
+impl HttpRequest {
+    pub fn new(method: HttpMethod, url: impl Into<String>) -> Self {
+        Self {
+            method,
+            url: url.into(),
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set a JSON body and the matching content type
+    pub fn json<T: Serialize + ?Sized>(self, body: &T) -> crate::Result<Self> {
+        let mut request = self.header("Content-Type", "application/json");
+        request.body = Some(serde_json::to_vec(body)?);
+        Ok(request)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> crate::Result<T> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+// Browser fetch futures are not `Send`, so the bound is only required on
+// native targets.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait HttpClient: Send + Sync {
+    /// Send a request. Non-2xx statuses are returned as responses, not
+    /// errors; only transport failures are `Err`.
+    async fn request(&self, req: HttpRequest) -> crate::Result<HttpResponse>;
+}
+
+#[cfg(feature = "native")]
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestHttpClient {
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "native")]
+impl ReqwestHttpClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "native")]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpClient for ReqwestHttpClient {
+    async fn request(&self, req: HttpRequest) -> crate::Result<HttpResponse> {
+        let method = match req.method {
+            HttpMethod::Get => reqwest::Method::GET,
+            HttpMethod::Post => reqwest::Method::POST,
+            HttpMethod::Put => reqwest::Method::PUT,
+            HttpMethod::Delete => reqwest::Method::DELETE,
+        };
+
+        let mut builder = self.client.request(method, &req.url);
+        for (name, value) in &req.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = req.body {
+            builder = builder.body(body);
+        }
+
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| QuizlrError::Network(e.to_string()))?;
+        let status = response.status().as_u16();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| QuizlrError::Network(e.to_string()))?;
+
+        Ok(HttpResponse {
+            status,
+            body: body.to_vec(),
+        })
+    }
+}
+
+/// Replays queued responses in order and records every request it gets
+#[derive(Debug, Default)]
+pub struct MockHttpClient {
+    responses: Mutex<VecDeque<HttpResponse>>,
+    requests: Mutex<Vec<HttpRequest>>,
+}
+
+impl MockHttpClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_response(&self, response: HttpResponse) {
+        self.responses
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push_back(response);
+    }
+
+    pub fn push_json<T: Serialize>(&self, status: u16, body: &T) -> crate::Result<()> {
+        self.push_response(HttpResponse {
+            status,
+            body: serde_json::to_vec(body)?,
+        });
+        Ok(())
+    }
+
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpClient for MockHttpClient {
+    async fn request(&self, req: HttpRequest) -> crate::Result<HttpResponse> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(req);
+        self.responses
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop_front()
+            .ok_or_else(|| QuizlrError::Network("No mock response queued".to_string()))
+    }
+}
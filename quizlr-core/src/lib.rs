@@ -1,18 +1,20 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 pub mod adaptive;
+pub mod cancel;
+pub mod clock;
 pub mod curriculum;
 pub mod error;
 pub mod graph;
+pub mod http;
 pub mod quiz;
+pub mod storage;
 
 // Features that require networking (not available in WASM)
 #[cfg(feature = "native")]
 pub mod auth;
 #[cfg(feature = "native")]
 pub mod llm;
-#[cfg(feature = "native")]
-pub mod storage;
 
 // FFI module for future iOS/Android support
 // #[cfg(not(target_arch = "wasm32"))]
@@ -34,7 +36,10 @@ pub fn init_panic_hook() {
 
 #[wasm_bindgen]
 pub struct QuizlrCore {
-    // Core application state will be managed here
+    quiz: Option<quiz::Quiz>,
+    session: Option<quiz::QuizSession>,
+    #[cfg(target_arch = "wasm32")]
+    storage: Option<storage::LocalStorageBackend>,
 }
 
 #[wasm_bindgen]
@@ -42,12 +47,190 @@ impl QuizlrCore {
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
         init_panic_hook();
-        Self {}
+        Self {
+            quiz: None,
+            session: None,
+            #[cfg(target_arch = "wasm32")]
+            storage: None,
+        }
+    }
+
+    /// Load the quiz that subsequent calls operate on, from its JSON form.
+    pub fn load_quiz(&mut self, quiz_json: &str) -> std::result::Result<(), JsValue> {
+        self.quiz = Some(serde_json::from_str(quiz_json).map_err(to_js_error)?);
+        Ok(())
+    }
+
+    /// Load the current session, from its JSON form. Sessions saved by
+    /// older versions are migrated first.
+    pub fn load_session(&mut self, session_json: &str) -> std::result::Result<(), JsValue> {
+        self.session = Some(
+            storage::migrate::from_versioned_json(session_json.as_bytes()).map_err(to_js_error)?,
+        );
+        Ok(())
+    }
+
+    /// Score the current session against the loaded quiz using a
+    /// JSON-encoded `ScoringStrategy`, returning the `Score` as a JS object.
+    pub fn score_session(&self, strategy_json: &str) -> std::result::Result<JsValue, JsValue> {
+        let mut strategy: serde_json::Value =
+            serde_json::from_str(strategy_json).map_err(to_js_error)?;
+        storage::migrate::upgrade_scoring_strategy(&mut strategy);
+        let strategy: quiz::ScoringStrategy =
+            serde_json::from_value(strategy).map_err(to_js_error)?;
+        let score = self.score(&strategy).map_err(to_js_error)?;
+        serde_wasm_bindgen::to_value(&score).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+/// Persisted-quiz bindings, backed by the browser's localStorage
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+impl QuizlrCore {
+    /// Persist quizzes in localStorage under keys starting with `prefix`
+    pub fn use_local_storage(&mut self, prefix: &str) {
+        self.storage = Some(storage::LocalStorageBackend::new(prefix));
+    }
+
+    /// Every saved quiz as an array of `{id, title}`, ordered by id.
+    /// Entries that can't be read or migrated are skipped with a console
+    /// warning, so one bad blob doesn't hide the rest.
+    pub fn list_quizzes(&self) -> std::result::Result<JsValue, JsValue> {
+        let storage = self.local_storage().map_err(to_js_error)?;
+        let listings: Vec<QuizListing> = storage
+            .keys(&storage::StorageKey::quiz_prefix())
+            .map_err(to_js_error)?
+            .iter()
+            .filter_map(|key| {
+                let quiz: Result<quiz::Quiz> = storage
+                    .get(key)
+                    .and_then(|bytes| storage::migrate::from_versioned_json(&bytes));
+                match quiz {
+                    Ok(quiz) => Some(QuizListing {
+                        id: quiz.id,
+                        title: quiz.title,
+                    }),
+                    Err(error) => {
+                        web_sys::console::warn_1(
+                            &format!("Skipping unreadable quiz {}: {}", key, error).into(),
+                        );
+                        None
+                    }
+                }
+            })
+            .collect();
+        serde_wasm_bindgen::to_value(&listings).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Load a saved quiz by id so subsequent calls operate on it
+    pub fn open_quiz(&mut self, id: &str) -> std::result::Result<(), JsValue> {
+        let id = uuid::Uuid::parse_str(id).map_err(to_js_error)?;
+        let storage = self.local_storage().map_err(to_js_error)?;
+        let bytes = storage
+            .get(storage::StorageKey::quiz(id).as_str())
+            .map_err(to_js_error)?;
+        self.quiz = Some(storage::migrate::from_versioned_json(&bytes).map_err(to_js_error)?);
+        Ok(())
     }
 }
 
+#[cfg(target_arch = "wasm32")]
+impl QuizlrCore {
+    fn local_storage(&self) -> Result<&storage::LocalStorageBackend> {
+        self.storage
+            .as_ref()
+            .ok_or_else(|| QuizlrError::Config("No storage configured".to_string()))
+    }
+}
+
+/// A saved quiz as shown in the browser's quiz picker
+#[cfg(target_arch = "wasm32")]
+#[derive(serde::Serialize)]
+struct QuizListing {
+    id: uuid::Uuid,
+    title: String,
+}
+
+impl QuizlrCore {
+    pub fn quiz(&self) -> Option<&quiz::Quiz> {
+        self.quiz.as_ref()
+    }
+
+    pub fn session(&self) -> Option<&quiz::QuizSession> {
+        self.session.as_ref()
+    }
+
+    pub fn score(&self, strategy: &quiz::ScoringStrategy) -> Result<quiz::Score> {
+        let quiz = self
+            .quiz
+            .as_ref()
+            .ok_or_else(|| QuizlrError::NotFound("No quiz loaded".to_string()))?;
+        let session = self
+            .session
+            .as_ref()
+            .ok_or_else(|| QuizlrError::NotFound("No session loaded".to_string()))?;
+        Ok(strategy.calculate_score(session, &quiz.questions))
+    }
+}
+
+fn to_js_error(error: impl std::fmt::Display) -> JsValue {
+    JsValue::from_str(&error.to_string())
+}
+
 impl Default for QuizlrCore {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quiz::{Answer, Question, QuestionType, QuizBuilder, QuizSession, ScoringStrategy};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_score_loaded_session() {
+        let question = Question::new(
+            QuestionType::TrueFalse {
+                statement: "Rust is memory safe".to_string(),
+                correct_answer: true,
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+        );
+        let quiz = QuizBuilder::new("Core".to_string())
+            .add_question(question.clone())
+            .add_question(Question::new(
+                question.question_type.clone(),
+                Uuid::new_v4(),
+                0.5,
+            ))
+            .build();
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+        session
+            .submit_answer(&question, Answer::TrueFalse(true), 10)
+            .unwrap();
+
+        let mut core = QuizlrCore::new();
+        assert!(core
+            .score(&ScoringStrategy::Simple {
+                hint_penalty_per_use: 0.0,
+            })
+            .is_err());
+
+        core.load_quiz(&serde_json::to_string(&quiz).unwrap())
+            .unwrap();
+        core.load_session(&serde_json::to_string(&session).unwrap())
+            .unwrap();
+
+        let score = core
+            .score(&ScoringStrategy::Simple {
+                hint_penalty_per_use: 0.0,
+            })
+            .unwrap();
+        assert_eq!(score.raw_score, 0.5);
+    }
+}
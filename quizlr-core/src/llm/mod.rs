@@ -1,5 +1,52 @@
+use crate::cancel::{cancellable, CancellationToken};
+use crate::error::QuizlrError;
+use crate::quiz::{Question, QuestionType, Quiz};
 use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+mod openai;
+mod template;
+
+pub use openai::OpenAiClient;
+pub use template::TemplateLlmClient;
+
+/// Quiz metadata key mapping topic names to the ids used for them
+const TOPIC_IDS_META_KEY: &str = "llm_topic_ids";
+
+/// Difficulty given to generated questions
+const DEFAULT_DIFFICULTY: f32 = 0.5;
+
+/// Most questions requested from the model in one call
+const GENERATION_BATCH_SIZE: usize = 10;
+
+/// Generation requests `LlmManager` keeps in flight at once by default
+const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Called with (generated so far, total requested) after each batch
+pub type ProgressCallback<'a> = &'a mut (dyn FnMut(usize, usize) + Send);
+
+/// A question as the model wrote it, with its own difficulty estimate if
+/// it gave one
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedQuestion {
+    pub question_type: QuestionType,
+    pub difficulty: Option<f32>,
+}
+
+impl GeneratedQuestion {
+    /// Build the question, using the model's difficulty clamped into
+    /// 0.0..=1.0 or `fallback_difficulty` when it didn't give one
+    pub fn into_question(self, topic_id: Uuid, fallback_difficulty: f32) -> Question {
+        let difficulty = self
+            .difficulty
+            .unwrap_or(fallback_difficulty)
+            .clamp(0.0, 1.0);
+        Question::new(self.question_type, topic_id, difficulty)
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum LlmProvider {
@@ -14,12 +61,178 @@ pub trait LlmClient: Send + Sync {
 }
 
 pub struct LlmManager {
-    // Placeholder for LLM integration
+    max_concurrency: usize,
 }
 
 impl LlmManager {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+        }
+    }
+
+    /// Cap on concurrent model calls in batch generation, at least 1
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Generate questions for several `(topic, count, difficulty)` requests
+    /// at once, with at most `max_concurrency` in flight. Results are in
+    /// request order, and a failed request doesn't affect the others.
+    /// Requests for the same topic name share a topic id.
+    pub async fn generate_questions_batch(
+        &self,
+        client: &dyn LlmClient,
+        requests: &[(String, usize, f32)],
+    ) -> Vec<crate::Result<Vec<Question>>> {
+        let mut topic_ids: HashMap<&str, Uuid> = HashMap::new();
+        for (topic, _, _) in requests {
+            topic_ids.entry(topic.as_str()).or_insert_with(Uuid::new_v4);
+        }
+
+        stream::iter(requests)
+            .map(|(topic, count, difficulty)| {
+                let topic_id = topic_ids[topic.as_str()];
+                async move {
+                    let generated = self.generate_questions(client, topic, *count, None).await?;
+                    Ok(generated
+                        .into_iter()
+                        .map(|question| question.into_question(topic_id, *difficulty))
+                        .collect())
+                }
+            })
+            .buffered(self.max_concurrency)
+            .collect()
+            .await
+    }
+
+    /// Generate `count` questions on `topic`, asking the model for at most
+    /// `GENERATION_BATCH_SIZE` at a time and reporting progress after
+    /// each batch. Questions with an invalid answer key are dropped. Stops
+    /// early if the model stops producing valid questions.
+    ///
+    /// Dropping the future mid-flight discards the questions generated so
+    /// far; nothing outside the call is touched.
+    pub async fn generate_questions(
+        &self,
+        client: &dyn LlmClient,
+        topic: &str,
+        count: usize,
+        on_progress: Option<ProgressCallback<'_>>,
+    ) -> crate::Result<Vec<GeneratedQuestion>> {
+        self.generate_questions_with_warnings(client, topic, count, on_progress)
+            .await
+            .map(|(questions, _)| questions)
+    }
+
+    /// [`Self::generate_questions`], also returning one warning per
+    /// generated question that was dropped for an invalid answer key
+    pub async fn generate_questions_with_warnings(
+        &self,
+        client: &dyn LlmClient,
+        topic: &str,
+        count: usize,
+        mut on_progress: Option<ProgressCallback<'_>>,
+    ) -> crate::Result<(Vec<GeneratedQuestion>, Vec<String>)> {
+        let mut questions = Vec::with_capacity(count);
+        let mut warnings = Vec::new();
+
+        while questions.len() < count {
+            let batch = (count - questions.len()).min(GENERATION_BATCH_SIZE);
+            let response = client.generate(&generation_prompt(topic, batch)).await?;
+            let (generated, rejected) = parse_generated_questions(&response)?;
+            warnings.extend(rejected);
+            if generated.is_empty() {
+                break;
+            }
+
+            questions.extend(generated.into_iter().take(batch));
+            if let Some(callback) = on_progress.as_mut() {
+                callback(questions.len(), count);
+            }
+        }
+
+        Ok((questions, warnings))
+    }
+
+    /// Generate `count` questions on `topic` and append the valid ones
+    /// whose content the quiz doesn't already have. Returns how many were
+    /// added.
+    ///
+    /// Every question generated for the same topic name gets the same topic
+    /// id; the mapping is kept in the quiz's metadata.
+    ///
+    /// The quiz is only modified after generation finishes, so dropping the
+    /// future mid-flight leaves it exactly as it was.
+    pub async fn extend_quiz(
+        &self,
+        quiz: &mut Quiz,
+        client: &dyn LlmClient,
+        topic: &str,
+        count: usize,
+    ) -> crate::Result<usize> {
+        self.extend_quiz_with_warnings(quiz, client, topic, count)
+            .await
+            .map(|(added, _)| added)
+    }
+
+    /// [`Self::extend_quiz`], also returning one warning per generated
+    /// question that was dropped for an invalid answer key
+    pub async fn extend_quiz_with_warnings(
+        &self,
+        quiz: &mut Quiz,
+        client: &dyn LlmClient,
+        topic: &str,
+        count: usize,
+    ) -> crate::Result<(usize, Vec<String>)> {
+        let (generated, warnings) = self
+            .generate_questions_with_warnings(client, topic, count, None)
+            .await?;
+        let topic_id = resolve_topic_id(quiz, topic)?;
+
+        let mut seen: HashSet<u64> = quiz.questions.iter().map(Question::content_hash).collect();
+        let mut added = 0;
+        for generated_question in generated {
+            let question = generated_question.into_question(topic_id, DEFAULT_DIFFICULTY);
+            if seen.insert(question.content_hash()) {
+                quiz.add_question(question);
+                added += 1;
+            }
+        }
+
+        Ok((added, warnings))
+    }
+
+    /// [`Self::generate_questions`], abandoned with
+    /// `QuizlrError::Cancelled` if `token` is cancelled first
+    pub async fn generate_questions_cancellable(
+        &self,
+        client: &dyn LlmClient,
+        topic: &str,
+        count: usize,
+        on_progress: Option<ProgressCallback<'_>>,
+        token: &CancellationToken,
+    ) -> crate::Result<Vec<GeneratedQuestion>> {
+        cancellable(
+            token,
+            self.generate_questions(client, topic, count, on_progress),
+        )
+        .await
+    }
+
+    /// [`Self::extend_quiz`], abandoned with `QuizlrError::Cancelled` if
+    /// `token` is cancelled first. A cancelled call leaves the quiz
+    /// unchanged.
+    pub async fn extend_quiz_cancellable(
+        &self,
+        quiz: &mut Quiz,
+        client: &dyn LlmClient,
+        topic: &str,
+        count: usize,
+        token: &CancellationToken,
+    ) -> crate::Result<usize> {
+        cancellable(token, self.extend_quiz(quiz, client, topic, count)).await
     }
 }
 
@@ -28,3 +241,397 @@ impl Default for LlmManager {
         Self::new()
     }
 }
+
+fn generation_prompt(topic: &str, count: usize) -> String {
+    format!(
+        "Write {} quiz questions about \"{}\". Respond with only a JSON array. \
+         Each element is an object {{\"type\": ..., \"data\": ...}} where type is one of \
+         TrueFalse, MultipleChoice, MultiSelect or FillInTheBlank and data holds that \
+         question's fields. Optionally add \"difficulty\", your estimate from 0.0 (easy) \
+         to 1.0 (hard), and \"explanation\", why the answer is correct.",
+        count, topic
+    )
+}
+
+/// Number of questions asked for by a prompt from [`generation_prompt`]
+fn requested_count(prompt: &str) -> Option<usize> {
+    prompt
+        .strip_prefix("Write ")?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Parse the model's reply, tolerating a surrounding markdown code fence.
+/// A top-level `explanation` fills the question's explanation unless its
+/// data already has one. Questions whose answer key fails
+/// [`Question::validate`] are left out, with one warning each.
+fn parse_generated_questions(
+    response: &str,
+) -> crate::Result<(Vec<GeneratedQuestion>, Vec<String>)> {
+    let body = response.trim();
+    let body = body
+        .strip_prefix("```json")
+        .or_else(|| body.strip_prefix("```"))
+        .and_then(|b| b.strip_suffix("```"))
+        .unwrap_or(body);
+
+    let unparseable =
+        |e: serde_json::Error| QuizlrError::LlmApi(format!("Unparseable question list: {}", e));
+    let items: Vec<serde_json::Value> = serde_json::from_str(body.trim()).map_err(unparseable)?;
+
+    let generated = items
+        .into_iter()
+        .map(|mut item| {
+            let (difficulty, explanation) = match item.as_object_mut() {
+                Some(fields) => (fields.remove("difficulty"), fields.remove("explanation")),
+                None => (None, None),
+            };
+            let mut question_type: QuestionType =
+                serde_json::from_value(item).map_err(unparseable)?;
+
+            if let (Some(slot), Some(serde_json::Value::String(text))) =
+                (explanation_mut(&mut question_type), explanation)
+            {
+                slot.get_or_insert(text);
+            }
+            let difficulty = difficulty
+                .and_then(|d| d.as_f64())
+                .map(|d| d as f32)
+                .filter(|d| d.is_finite());
+
+            Ok(GeneratedQuestion {
+                question_type,
+                difficulty,
+            })
+        })
+        .collect::<crate::Result<Vec<_>>>()?;
+
+    let mut warnings = Vec::new();
+    let valid = generated
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, generated)| {
+            let check = Question::new(generated.question_type.clone(), Uuid::nil(), 0.0);
+            match check.validate() {
+                Ok(()) => Some(generated),
+                Err(problem) => {
+                    warnings.push(format!("Generated question {}: {}", index, problem));
+                    None
+                }
+            }
+        })
+        .collect();
+    Ok((valid, warnings))
+}
+
+fn explanation_mut(question_type: &mut QuestionType) -> Option<&mut Option<String>> {
+    match question_type {
+        QuestionType::TrueFalse { explanation, .. }
+        | QuestionType::MultipleChoice { explanation, .. }
+        | QuestionType::MultiSelect { explanation, .. }
+        | QuestionType::FillInTheBlank { explanation, .. }
+        | QuestionType::MatchPairs { explanation, .. } => Some(explanation),
+        _ => None,
+    }
+}
+
+/// Id for `topic` in this quiz, creating and recording one on first use
+fn resolve_topic_id(quiz: &mut Quiz, topic: &str) -> crate::Result<Uuid> {
+    let mut topics: HashMap<String, Uuid> = quiz.get_meta(TOPIC_IDS_META_KEY)?.unwrap_or_default();
+    if let Some(id) = topics.get(topic) {
+        return Ok(*id);
+    }
+
+    let id = Uuid::new_v4();
+    topics.insert(topic.to_string(), id);
+    quiz.set_meta(TOPIC_IDS_META_KEY, &topics)?;
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    struct MockClient {
+        response: String,
+    }
+
+    #[async_trait]
+    impl LlmClient for MockClient {
+        async fn generate(&self, _prompt: &str) -> Result<String, QuizlrError> {
+            Ok(self.response.clone())
+        }
+    }
+
+    fn true_false(statement: &str) -> QuestionType {
+        QuestionType::TrueFalse {
+            statement: statement.to_string(),
+            correct_answer: true,
+            explanation: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extend_quiz_filters_duplicates() {
+        let mut quiz = Quiz::new("Rust".to_string());
+        quiz.add_question(Question::new(
+            true_false("Rust has ownership"),
+            Uuid::new_v4(),
+            0.5,
+        ));
+
+        let generated = vec![
+            true_false("Rust has ownership"),
+            true_false("Rust has lifetimes"),
+        ];
+        let client = MockClient {
+            response: format!(
+                "```json\n{}\n```",
+                serde_json::to_string(&generated).unwrap()
+            ),
+        };
+
+        let manager = LlmManager::new();
+        let added = manager
+            .extend_quiz(&mut quiz, &client, "ownership", 2)
+            .await
+            .unwrap();
+        assert_eq!(added, 1);
+        assert_eq!(quiz.questions.len(), 2);
+
+        // Running again adds nothing new and reuses the topic id
+        let again = manager
+            .extend_quiz(&mut quiz, &client, "ownership", 2)
+            .await
+            .unwrap();
+        assert_eq!(again, 0);
+        let topic_id = quiz.questions[1].topic_id;
+        assert_eq!(resolve_topic_id(&mut quiz, "ownership").unwrap(), topic_id);
+    }
+
+    #[tokio::test]
+    async fn test_extend_quiz_drops_invalid_answer_keys() {
+        let mut quiz = Quiz::new("Rust".to_string());
+        let client = MockClient {
+            response: r#"[
+                {"type": "MultipleChoice",
+                 "data": {"question": "Which is a smart pointer?",
+                          "options": ["Box", "i32"], "correct_index": 7}},
+                {"type": "MultiSelect",
+                 "data": {"question": "Which are integers?",
+                          "options": ["i32", "u8"], "correct_indices": [0, 2]}},
+                {"type": "TrueFalse",
+                 "data": {"statement": "Rust has lifetimes", "correct_answer": true}}
+            ]"#
+            .to_string(),
+        };
+
+        let (added, warnings) = LlmManager::new()
+            .extend_quiz_with_warnings(&mut quiz, &client, "ownership", 1)
+            .await
+            .unwrap();
+        assert_eq!(added, 1);
+        assert_eq!(quiz.questions.len(), 1);
+        assert!(quiz.validate().is_ok());
+        assert_eq!(
+            warnings,
+            vec![
+                "Generated question 0: correct index out of range",
+                "Generated question 1: correct index out of range",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extend_quiz_rejects_unparseable_reply() {
+        let mut quiz = Quiz::new("Rust".to_string());
+        let client = MockClient {
+            response: "Sorry, I can't help with that".to_string(),
+        };
+
+        let result = LlmManager::new()
+            .extend_quiz(&mut quiz, &client, "ownership", 3)
+            .await;
+        assert!(matches!(result, Err(QuizlrError::LlmApi(_))));
+        assert!(quiz.questions.is_empty());
+    }
+
+    /// Returns as many questions as asked for, however many are requested
+    struct CountingClient;
+
+    #[async_trait]
+    impl LlmClient for CountingClient {
+        async fn generate(&self, prompt: &str) -> Result<String, QuizlrError> {
+            let batch: usize = prompt
+                .split_whitespace()
+                .nth(1)
+                .and_then(|n| n.parse().ok())
+                .unwrap();
+            let questions: Vec<QuestionType> = (0..batch)
+                .map(|i| true_false(&format!("Statement {}", i)))
+                .collect();
+            Ok(serde_json::to_string(&questions).unwrap())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_questions_reports_progress_per_batch() {
+        let mut reports = Vec::new();
+        let mut record = |done: usize, total: usize| reports.push((done, total));
+
+        let questions = LlmManager::new()
+            .generate_questions(&CountingClient, "ownership", 25, Some(&mut record))
+            .await
+            .unwrap();
+
+        assert_eq!(questions.len(), 25);
+        assert_eq!(reports, vec![(10, 25), (20, 25), (25, 25)]);
+    }
+
+    /// One question per call naming the topic; fails for "broken". Tracks
+    /// the most calls it ever had in flight.
+    #[derive(Default)]
+    struct TopicClient {
+        in_flight: std::sync::atomic::AtomicUsize,
+        max_in_flight: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl LlmClient for TopicClient {
+        async fn generate(&self, prompt: &str) -> Result<String, QuizlrError> {
+            use std::sync::atomic::Ordering;
+
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            let topic = prompt.split('"').nth(1).unwrap();
+            if topic == "broken" {
+                return Err(QuizlrError::LlmApi("model unavailable".to_string()));
+            }
+            Ok(serde_json::to_string(&vec![true_false(topic)]).unwrap())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_questions_batch_keeps_order_and_failures() {
+        let client = TopicClient::default();
+        let requests = vec![
+            ("ownership".to_string(), 1, 0.2),
+            ("broken".to_string(), 1, 0.5),
+            ("lifetimes".to_string(), 1, 0.8),
+        ];
+
+        let results = LlmManager::new()
+            .with_max_concurrency(2)
+            .generate_questions_batch(&client, &requests)
+            .await;
+
+        assert_eq!(results.len(), 3);
+        let ownership = results[0].as_ref().unwrap();
+        assert_eq!(ownership[0].question_type, true_false("ownership"));
+        assert_eq!(ownership[0].difficulty, 0.2);
+        assert!(matches!(results[1], Err(QuizlrError::LlmApi(_))));
+        let lifetimes = results[2].as_ref().unwrap();
+        assert_eq!(lifetimes[0].question_type, true_false("lifetimes"));
+        assert_eq!(lifetimes[0].difficulty, 0.8);
+        assert_ne!(ownership[0].topic_id, lifetimes[0].topic_id);
+
+        let max_in_flight = client
+            .max_in_flight
+            .load(std::sync::atomic::Ordering::SeqCst);
+        assert_eq!(max_in_flight, 2);
+    }
+
+    #[tokio::test]
+    async fn test_generated_explanation_and_difficulty() {
+        let client = MockClient {
+            response: r#"[
+                {"type": "TrueFalse",
+                 "data": {"statement": "Rust has a borrow checker", "correct_answer": true},
+                 "difficulty": 0.3,
+                 "explanation": "The borrow checker enforces ownership rules"},
+                {"type": "TrueFalse",
+                 "data": {"statement": "Rust has lifetimes", "correct_answer": true},
+                 "difficulty": 1.7},
+                {"type": "TrueFalse",
+                 "data": {"statement": "Rust has traits", "correct_answer": true}}
+            ]"#
+            .to_string(),
+        };
+
+        let results = LlmManager::new()
+            .generate_questions_batch(&client, &[("ownership".to_string(), 3, 0.6)])
+            .await;
+        let questions = results[0].as_ref().unwrap();
+
+        assert_eq!(questions.len(), 3);
+        assert_eq!(questions[0].difficulty, 0.3);
+        assert_eq!(
+            questions[0].get_explanation(),
+            Some("The borrow checker enforces ownership rules")
+        );
+        // Out of range estimates are clamped, missing ones use the request's
+        assert_eq!(questions[1].difficulty, 1.0);
+        assert_eq!(questions[1].get_explanation(), None);
+        assert_eq!(questions[2].difficulty, 0.6);
+    }
+
+    /// Never answers; counts calls in flight, decrementing when a call's
+    /// future is dropped
+    #[derive(Default)]
+    struct HangingClient {
+        in_flight: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    struct InFlightGuard(Arc<std::sync::atomic::AtomicUsize>);
+
+    impl Drop for InFlightGuard {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    #[async_trait]
+    impl LlmClient for HangingClient {
+        async fn generate(&self, _prompt: &str) -> Result<String, QuizlrError> {
+            self.in_flight
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let _guard = InFlightGuard(self.in_flight.clone());
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dropped_generation_leaves_no_state() {
+        use std::sync::atomic::Ordering;
+
+        let client = HangingClient::default();
+        let mut quiz = Quiz::new("Rust".to_string());
+        let manager = LlmManager::new();
+
+        {
+            let extend = manager.extend_quiz(&mut quiz, &client, "ownership", 3);
+            futures::pin_mut!(extend);
+            assert!(futures::poll!(extend.as_mut()).is_pending());
+            assert_eq!(client.in_flight.load(Ordering::SeqCst), 1);
+        }
+
+        assert_eq!(client.in_flight.load(Ordering::SeqCst), 0);
+        assert!(quiz.questions.is_empty());
+        assert!(quiz.metadata.is_empty());
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let result = manager
+            .extend_quiz_cancellable(&mut quiz, &client, "ownership", 3, &token)
+            .await;
+        assert!(matches!(result, Err(QuizlrError::Cancelled)));
+        assert_eq!(client.in_flight.load(Ordering::SeqCst), 0);
+        assert!(quiz.questions.is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+//! OpenAI chat completions over a pluggable HTTP transport
+
+use super::LlmClient;
+use crate::error::QuizlrError;
+use crate::http::{HttpClient, HttpMethod, HttpRequest};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::sync::Arc;
+
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+pub struct OpenAiClient {
+    http: Arc<dyn HttpClient>,
+    api_key: String,
+    model: String,
+    base_url: String,
+}
+
+impl OpenAiClient {
+    pub fn new(http: Arc<dyn HttpClient>, api_key: String) -> Self {
+        Self {
+            http,
+            api_key,
+            model: DEFAULT_MODEL.to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+
+    pub fn with_model(mut self, model: String) -> Self {
+        self.model = model;
+        self
+    }
+
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    content: String,
+}
+
+#[async_trait]
+impl LlmClient for OpenAiClient {
+    async fn generate(&self, prompt: &str) -> Result<String, QuizlrError> {
+        let body = serde_json::json!({
+            "model": self.model,
+            "messages": [{ "role": "user", "content": prompt }],
+        });
+        let request = HttpRequest::new(
+            HttpMethod::Post,
+            format!("{}/chat/completions", self.base_url),
+        )
+        .header("Authorization", format!("Bearer {}", self.api_key))
+        .json(&body)?;
+
+        let response = self.http.request(request).await?;
+        if !response.is_success() {
+            return Err(QuizlrError::LlmApi(format!(
+                "OpenAI returned {}: {}",
+                response.status,
+                response.text()
+            )));
+        }
+
+        let chat: ChatResponse = response
+            .json()
+            .map_err(|e| QuizlrError::LlmApi(format!("Unexpected OpenAI response: {}", e)))?;
+        chat.choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content)
+            .ok_or_else(|| QuizlrError::LlmApi("OpenAI returned no choices".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{HttpResponse, MockHttpClient};
+
+    #[tokio::test]
+    async fn test_openai_client_over_mock_transport() {
+        let http = Arc::new(MockHttpClient::new());
+        http.push_json(
+            200,
+            &serde_json::json!({
+                "choices": [{ "message": { "role": "assistant", "content": "Hello" } }]
+            }),
+        )
+        .unwrap();
+        http.push_response(HttpResponse {
+            status: 429,
+            body: b"rate limited".to_vec(),
+        });
+
+        let client = OpenAiClient::new(http.clone(), "sk-test".to_string());
+        assert_eq!(client.generate("Say hello").await.unwrap(), "Hello");
+        assert!(matches!(
+            client.generate("Again").await,
+            Err(QuizlrError::LlmApi(message)) if message.contains("429")
+        ));
+
+        let requests = http.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method, HttpMethod::Post);
+        assert_eq!(
+            requests[0].url,
+            "https://api.openai.com/v1/chat/completions"
+        );
+        assert!(requests[0]
+            .headers
+            .contains(&("Authorization".to_string(), "Bearer sk-test".to_string())));
+        let sent: serde_json::Value =
+            serde_json::from_slice(requests[0].body.as_deref().unwrap()).unwrap();
+        assert_eq!(sent["messages"][0]["content"], "Say hello");
+    }
+}
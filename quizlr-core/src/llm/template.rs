@@ -0,0 +1,166 @@
+//! Offline stand-in for a model, templating questions from known facts
+
+use super::{requested_count, LlmClient};
+use crate::error::QuizlrError;
+use crate::quiz::QuestionType;
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Most options offered on a templated multiple choice question
+const MAX_OPTIONS: usize = 4;
+
+/// An [`LlmClient`] that never touches the network. Each call fills
+/// question templates from a list of `(term, definition)` facts, answering
+/// in the same JSON format a model would, so it drops into
+/// `LlmManager::generate_questions` unchanged. Output is deterministic:
+/// calls walk through the facts in order, alternating true/false and
+/// multiple choice questions, and pick up where the previous call stopped.
+pub struct TemplateLlmClient {
+    facts: Vec<(String, String)>,
+    cursor: AtomicUsize,
+}
+
+impl TemplateLlmClient {
+    pub fn new(facts: Vec<(String, String)>) -> Self {
+        Self {
+            facts,
+            cursor: AtomicUsize::new(0),
+        }
+    }
+
+    /// The `k`th question in the sequence. Even positions are true/false
+    /// statements, alternately true and false; odd ones ask which term
+    /// matches a definition. A single fact only gives true statements.
+    fn question(&self, k: usize) -> QuestionType {
+        let n = self.facts.len();
+        let (term, definition) = &self.facts[k % n];
+        let explanation = Some(format!("{}: {}", term, definition));
+
+        if n < 2 || k.is_multiple_of(2) {
+            let is_true = n < 2 || (k / 2).is_multiple_of(2);
+            let shown = if is_true {
+                definition
+            } else {
+                &self.facts[(k + 1) % n].1
+            };
+            return QuestionType::TrueFalse {
+                statement: format!("{} is {}", term, shown),
+                correct_answer: is_true,
+                explanation,
+            };
+        }
+
+        let option_count = n.min(MAX_OPTIONS);
+        let mut options: Vec<String> = (0..option_count)
+            .map(|offset| self.facts[(k + offset) % n].0.clone())
+            .collect();
+        let correct_index = k % option_count;
+        options.swap(0, correct_index);
+        QuestionType::MultipleChoice {
+            question: format!("Which term means {}?", definition),
+            options,
+            correct_index,
+            alternate_correct: vec![],
+            explanation,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmClient for TemplateLlmClient {
+    async fn generate(&self, prompt: &str) -> Result<String, QuizlrError> {
+        if self.facts.is_empty() {
+            return Err(QuizlrError::LlmApi(
+                "Template client has no facts to work from".to_string(),
+            ));
+        }
+        let count = requested_count(prompt).ok_or_else(|| {
+            QuizlrError::LlmApi("Template client only answers generation prompts".to_string())
+        })?;
+
+        let start = self.cursor.fetch_add(count, Ordering::SeqCst);
+        let questions: Vec<QuestionType> =
+            (start..start + count).map(|k| self.question(k)).collect();
+        Ok(serde_json::to_string(&questions)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::LlmManager;
+    use crate::quiz::Question;
+    use std::collections::HashSet;
+    use uuid::Uuid;
+
+    fn facts() -> Vec<(String, String)> {
+        [
+            ("Ownership", "each value having a single owner"),
+            ("Borrowing", "referring to a value without taking ownership"),
+            ("Lifetimes", "how long references stay valid"),
+            ("Traits", "shared behaviour types can implement"),
+            ("Macros", "code that writes code"),
+        ]
+        .into_iter()
+        .map(|(term, definition)| (term.to_string(), definition.to_string()))
+        .collect()
+    }
+
+    #[tokio::test]
+    async fn test_generates_requested_count_of_varied_questions() {
+        let client = TemplateLlmClient::new(facts());
+        let generated = LlmManager::new()
+            .generate_questions(&client, "Rust", 12, None)
+            .await
+            .unwrap();
+        assert_eq!(generated.len(), 12);
+
+        let questions: Vec<Question> = generated
+            .into_iter()
+            .map(|g| g.into_question(Uuid::new_v4(), 0.5))
+            .collect();
+        for question in &questions {
+            assert!(question.validate().is_ok());
+            let key = question.correct_answer().unwrap();
+            assert_eq!(question.validate_answer(&key), Ok(true));
+        }
+
+        let distinct: HashSet<u64> = questions.iter().map(Question::content_hash).collect();
+        assert_eq!(distinct.len(), 12);
+        let kinds = |matches: fn(&QuestionType) -> bool| {
+            questions
+                .iter()
+                .filter(|q| matches(&q.question_type))
+                .count()
+        };
+        assert_eq!(kinds(|t| matches!(t, QuestionType::TrueFalse { .. })), 6);
+        assert_eq!(
+            kinds(|t| matches!(t, QuestionType::MultipleChoice { .. })),
+            6
+        );
+        assert_eq!(
+            kinds(|t| matches!(
+                t,
+                QuestionType::TrueFalse {
+                    correct_answer: false,
+                    ..
+                }
+            )),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_needs_facts_and_a_generation_prompt() {
+        let empty = TemplateLlmClient::new(Vec::new());
+        assert!(matches!(
+            empty
+                .generate("Write 3 quiz questions about \"Rust\".")
+                .await,
+            Err(QuizlrError::LlmApi(_))
+        ));
+
+        let client = TemplateLlmClient::new(facts());
+        assert!(client.generate("Hello").await.is_err());
+    }
+}
@@ -0,0 +1,395 @@
+//! Running scores for live tickers, updated one answer at a time
+
+use super::scoring::{
+    attempt_credit, hint_credit, DifficultyLevel, Score, ScoreComponents, ScoringStrategy,
+};
+use super::session::{counts_speed, QuestionResponse, QuizSession, TimingMode};
+use super::Question;
+use serde::{Deserialize, Serialize};
+
+/// Keeps running totals for a [`ScoringStrategy`] so the current score
+/// costs O(1) per answer instead of rescoring the whole session. Once every
+/// response has been pushed, [`ScoreAccumulator::current`] agrees with
+/// [`ScoringStrategy::calculate_score`] on the same session.
+///
+/// The accumulator serializes with its strategy and running totals, so a
+/// partly scored session can be stored between requests and resumed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreAccumulator {
+    strategy: ScoringStrategy,
+    #[serde(default)]
+    timing_mode: Option<TimingMode>,
+    total_questions: usize,
+    max_difficulty_points: f32,
+    expected_avg_time: f32,
+
+    responses: usize,
+    correct: usize,
+    #[serde(default)]
+    hinted_credit: f32,
+    time_points: f32,
+    attempt_points: f32,
+    difficulty_points: f32,
+    answered_weight: f32,
+    correct_weight: f32,
+    time_sum: f32,
+    time_sum_sq: f64,
+    current_streak: usize,
+    max_streak: usize,
+}
+
+impl ScoreAccumulator {
+    /// Start an empty accumulator for `session` over `questions`, the full
+    /// question list of the quiz. The session's timing mode and hint
+    /// penalty are applied the same way batch scoring applies them.
+    pub fn new(strategy: ScoringStrategy, questions: &[Question], session: &QuizSession) -> Self {
+        let max_difficulty_points = match &strategy {
+            ScoringStrategy::DifficultyWeighted { .. } => questions
+                .iter()
+                .map(|q| difficulty_multiplier(&strategy, q))
+                .sum(),
+            _ => 0.0,
+        };
+        let expected_avg_time = questions
+            .iter()
+            .map(|q| q.estimated_time_seconds as f32)
+            .sum::<f32>()
+            / questions.len().max(1) as f32;
+
+        Self {
+            strategy,
+            timing_mode: session.timing_mode,
+            total_questions: questions.len(),
+            max_difficulty_points,
+            expected_avg_time,
+            responses: 0,
+            correct: 0,
+            hinted_credit: 0.0,
+            time_points: 0.0,
+            attempt_points: 0.0,
+            difficulty_points: 0.0,
+            answered_weight: 0.0,
+            correct_weight: 0.0,
+            time_sum: 0.0,
+            time_sum_sq: 0.0,
+            current_streak: 0,
+            max_streak: 0,
+        }
+    }
+
+    /// Add one response to `question`, in submission order
+    pub fn push(&mut self, response: &QuestionResponse, question: &Question) {
+        let time = response.time_taken_seconds as f32;
+        self.responses += 1;
+        self.time_sum += time;
+        self.time_sum_sq += f64::from(time) * f64::from(time);
+
+        if response.is_correct {
+            self.correct += 1;
+            self.hinted_credit +=
+                hint_credit(response.hints_used, self.strategy.hint_penalty_per_use());
+            self.current_streak += 1;
+            self.max_streak = self.max_streak.max(self.current_streak);
+        } else {
+            self.current_streak = 0;
+        }
+
+        match &self.strategy {
+            ScoringStrategy::Simple { .. } => {}
+            ScoringStrategy::TimeWeighted {
+                base_time_seconds,
+                penalty_per_second,
+            } => {
+                let base_points = if response.is_correct { 1.0 } else { 0.0 };
+                let time_penalty = if counts_speed(self.timing_mode)
+                    && response.time_taken_seconds > *base_time_seconds
+                {
+                    (response.time_taken_seconds - base_time_seconds) as f32 * penalty_per_second
+                } else {
+                    0.0
+                };
+                self.time_points += (base_points - time_penalty).max(0.0);
+            }
+            ScoringStrategy::DifficultyWeighted { .. } => {
+                if response.is_correct {
+                    self.difficulty_points += difficulty_multiplier(&self.strategy, question);
+                }
+            }
+            ScoringStrategy::Adaptive { buckets, .. } => {
+                let weight = buckets.weight(question.difficulty);
+                self.answered_weight += weight;
+                if response.is_correct {
+                    self.correct_weight += weight;
+                }
+            }
+            ScoringStrategy::AttemptWeighted { decay_per_attempt } => {
+                if response.is_correct {
+                    self.attempt_points += attempt_credit(response.attempts, *decay_per_attempt);
+                }
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.responses
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.responses == 0
+    }
+
+    /// Score of everything pushed so far
+    pub fn current(&self) -> Score {
+        let raw_score = self.ratio(self.correct as f32, self.total_questions as f32);
+        let mut score = Score {
+            raw_score,
+            weighted_score: raw_score,
+            percentile: None,
+            z_score: None,
+            time_bonus: 0.0,
+            difficulty_bonus: 0.0,
+            streak_bonus: 0.0,
+            components: ScoreComponents {
+                correctness: raw_score,
+                speed: 0.0,
+                difficulty: 0.0,
+                consistency: 0.0,
+            },
+        };
+
+        let hinted_score = self.ratio(self.hinted_credit, self.total_questions as f32);
+        match &self.strategy {
+            ScoringStrategy::Simple { .. } => score.weighted_score = hinted_score,
+            ScoringStrategy::TimeWeighted { .. } => {
+                score.weighted_score = self.ratio(self.time_points, self.total_questions as f32);
+                score.time_bonus = score.weighted_score - raw_score;
+                score.components.speed = score.time_bonus;
+            }
+            ScoringStrategy::DifficultyWeighted { .. } => {
+                score.weighted_score =
+                    self.ratio(self.difficulty_points, self.max_difficulty_points);
+                score.difficulty_bonus = score.weighted_score - raw_score;
+                score.components.difficulty = score.difficulty_bonus;
+            }
+            ScoringStrategy::AttemptWeighted { .. } => {
+                score.weighted_score = self.ratio(self.attempt_points, self.total_questions as f32);
+            }
+            ScoringStrategy::Adaptive {
+                time_weight,
+                difficulty_weight,
+                streak_weight,
+                consistency_weight,
+                ..
+            } => {
+                let timed = counts_speed(self.timing_mode);
+                let time_weight = if timed { *time_weight } else { 0.0 };
+                let total_weight =
+                    time_weight + difficulty_weight + streak_weight + consistency_weight;
+
+                let time_score = if self.responses == 0 || !timed {
+                    0.0
+                } else {
+                    let avg_time = self.time_sum / self.responses as f32;
+                    (self.expected_avg_time / avg_time.max(1.0)).min(1.0)
+                };
+                let difficulty_score = self.ratio(self.correct_weight, self.answered_weight);
+                let streak_score = self.ratio(self.max_streak as f32, self.responses as f32);
+                let consistency_score = self.consistency();
+
+                score.weighted_score = (hinted_score
+                    + time_score * time_weight
+                    + difficulty_score * difficulty_weight
+                    + streak_score * streak_weight
+                    + consistency_score * consistency_weight)
+                    / (1.0 + total_weight);
+                score.time_bonus = time_score * time_weight;
+                score.difficulty_bonus = difficulty_score * difficulty_weight;
+                score.streak_bonus = streak_score * streak_weight;
+                score.components.correctness = hinted_score;
+                score.components.speed = time_score;
+                score.components.difficulty = difficulty_score;
+                score.components.consistency = consistency_score;
+            }
+        }
+
+        score
+    }
+
+    fn ratio(&self, numerator: f32, denominator: f32) -> f32 {
+        if denominator > 0.0 {
+            numerator / denominator
+        } else {
+            0.0
+        }
+    }
+
+    /// `1 / (1 + cv)` of the response times, as in batch adaptive scoring
+    fn consistency(&self) -> f32 {
+        match self.responses {
+            0 => 0.0,
+            1 => 1.0,
+            n => {
+                let n = n as f64;
+                let mean = f64::from(self.time_sum) / n;
+                let variance = (self.time_sum_sq / n - mean * mean).max(0.0);
+                let cv = (variance.sqrt() / mean) as f32;
+                (1.0 / (1.0 + cv)).min(1.0)
+            }
+        }
+    }
+}
+
+fn difficulty_multiplier(strategy: &ScoringStrategy, question: &Question) -> f32 {
+    match strategy {
+        ScoringStrategy::DifficultyWeighted {
+            easy_multiplier,
+            medium_multiplier,
+            hard_multiplier,
+            buckets,
+        } => match buckets.level(question.difficulty) {
+            DifficultyLevel::Easy => *easy_multiplier,
+            DifficultyLevel::Medium => *medium_multiplier,
+            DifficultyLevel::Hard => *hard_multiplier,
+        },
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quiz::scoring::DifficultyBuckets;
+    use crate::quiz::{Answer, QuestionType, QuizSession};
+    use uuid::Uuid;
+
+    fn question(difficulty: f32) -> Question {
+        Question::new(
+            QuestionType::TrueFalse {
+                statement: format!("Difficulty {}", difficulty),
+                correct_answer: true,
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            difficulty,
+        )
+    }
+
+    #[test]
+    fn test_accumulator_matches_batch_score() {
+        let questions: Vec<Question> = [0.1, 0.4, 0.5, 0.8, 0.9].map(question).to_vec();
+        let mut session = QuizSession::new(Uuid::new_v4(), None);
+        session.timing_mode = Some(TimingMode::PerQuestion(60));
+        session.start().unwrap();
+        for (question, (correct, time)) in
+            questions
+                .iter()
+                .zip([(true, 20), (true, 75), (false, 40), (true, 130), (true, 55)])
+        {
+            session
+                .submit_answer(question, Answer::TrueFalse(correct), time)
+                .unwrap();
+        }
+
+        let strategies = [
+            ScoringStrategy::Simple {
+                hint_penalty_per_use: 0.0,
+            },
+            ScoringStrategy::TimeWeighted {
+                base_time_seconds: 60,
+                penalty_per_second: 0.01,
+            },
+            ScoringStrategy::DifficultyWeighted {
+                easy_multiplier: 1.0,
+                medium_multiplier: 1.5,
+                hard_multiplier: 2.0,
+                buckets: DifficultyBuckets::default(),
+            },
+            ScoringStrategy::Adaptive {
+                time_weight: 0.2,
+                difficulty_weight: 0.3,
+                streak_weight: 0.2,
+                consistency_weight: 0.1,
+                buckets: DifficultyBuckets::default(),
+                hint_penalty_per_use: 0.0,
+            },
+            ScoringStrategy::AttemptWeighted {
+                decay_per_attempt: 0.25,
+            },
+        ];
+
+        for strategy in strategies {
+            let mut accumulator = ScoreAccumulator::new(strategy.clone(), &questions, &session);
+            for (response, question) in session.responses.iter().zip(&questions) {
+                accumulator.push(response, question);
+            }
+            assert_eq!(accumulator.len(), questions.len());
+
+            let live = accumulator.current();
+            let batch = strategy.calculate_score(&session, &questions);
+            let close = |a: f32, b: f32| (a - b).abs() < 1e-5;
+            assert!(close(live.raw_score, batch.raw_score), "{:?}", strategy);
+            assert!(
+                close(live.weighted_score, batch.weighted_score),
+                "{:?}",
+                strategy
+            );
+            assert!(close(live.time_bonus, batch.time_bonus), "{:?}", strategy);
+            assert!(close(live.difficulty_bonus, batch.difficulty_bonus));
+            assert!(close(live.streak_bonus, batch.streak_bonus));
+            assert!(close(live.components.speed, batch.components.speed));
+            assert!(close(
+                live.components.difficulty,
+                batch.components.difficulty
+            ));
+            assert!(close(
+                live.components.consistency,
+                batch.components.consistency
+            ));
+        }
+    }
+
+    #[test]
+    fn test_resumed_accumulator_matches_batch_score() {
+        let questions: Vec<Question> = [0.2, 0.5, 0.7, 0.9].map(question).to_vec();
+        let mut session = QuizSession::new(Uuid::new_v4(), None);
+        session.timing_mode = Some(TimingMode::Total(600));
+        session.start().unwrap();
+        for (question, (correct, time)) in
+            questions
+                .iter()
+                .zip([(true, 30), (false, 90), (true, 45), (true, 60)])
+        {
+            session
+                .submit_answer(question, Answer::TrueFalse(correct), time)
+                .unwrap();
+        }
+        let strategy = ScoringStrategy::Adaptive {
+            time_weight: 0.2,
+            difficulty_weight: 0.3,
+            streak_weight: 0.2,
+            consistency_weight: 0.1,
+            buckets: DifficultyBuckets::default(),
+            hint_penalty_per_use: 0.0,
+        };
+
+        let mut accumulator = ScoreAccumulator::new(strategy.clone(), &questions, &session);
+        let mut pairs = session.responses.iter().zip(&questions);
+        for (response, question) in pairs.by_ref().take(2) {
+            accumulator.push(response, question);
+        }
+
+        // Stored between requests, without the original strategy at hand
+        let stored = serde_json::to_string(&accumulator).unwrap();
+        let mut resumed: ScoreAccumulator = serde_json::from_str(&stored).unwrap();
+        assert_eq!(resumed.len(), 2);
+        for (response, question) in pairs {
+            resumed.push(response, question);
+        }
+
+        let live = resumed.current();
+        let batch = strategy.calculate_score(&session, &questions);
+        assert!((live.raw_score - batch.raw_score).abs() < 1e-5);
+        assert!((live.weighted_score - batch.weighted_score).abs() < 1e-5);
+        assert!((live.components.consistency - batch.components.consistency).abs() < 1e-5);
+    }
+}
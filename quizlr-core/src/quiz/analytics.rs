@@ -0,0 +1,264 @@
+//! Item analysis across many attempts at a quiz
+
+use super::question::Answer;
+use super::quiz_impl::Quiz;
+use super::session::QuizSession;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// How many times each option index was chosen for a question, counting
+/// each session's final answer. A distractor picked more often than the
+/// correct option points at a common misconception. Multi-select answers
+/// count once per selected option; other answer types are ignored, so
+/// non-choice questions give an empty map.
+pub fn option_distribution(question_id: Uuid, sessions: &[QuizSession]) -> HashMap<usize, usize> {
+    let mut counts = HashMap::new();
+    let answers = sessions
+        .iter()
+        .flat_map(|session| &session.responses)
+        .filter(|response| response.question_id == question_id)
+        .map(|response| &response.answer);
+
+    for answer in answers {
+        match answer {
+            Answer::MultipleChoice(index) => *counts.entry(*index).or_default() += 1,
+            Answer::MultiSelect(indices) => {
+                for index in indices {
+                    *counts.entry(*index).or_default() += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    counts
+}
+
+/// [`option_distribution`] as `(option index, count)` pairs sorted by
+/// option index, for stable display
+pub fn option_distribution_sorted(
+    question_id: Uuid,
+    sessions: &[QuizSession],
+) -> Vec<(usize, usize)> {
+    let mut counts: Vec<_> = option_distribution(question_id, sessions)
+        .into_iter()
+        .collect();
+    counts.sort_unstable();
+    counts
+}
+
+/// Cronbach's alpha for `quiz` over a set of sessions: how consistently
+/// its questions measure the same thing, from 1.0 for perfectly consistent
+/// items down through 0 (and below) for items that disagree.
+///
+/// Each question scores 1 for a correct final answer and 0 otherwise, so
+/// skipped and unanswered questions count as incorrect. Returns `None` with
+/// fewer than two questions or two sessions, or when every session has the
+/// same total and alpha is undefined.
+pub fn cronbach_alpha(quiz: &Quiz, sessions: &[QuizSession]) -> Option<f32> {
+    let items = quiz.questions.len();
+    if items < 2 || sessions.len() < 2 {
+        return None;
+    }
+
+    let matrix: Vec<Vec<f32>> = sessions
+        .iter()
+        .map(|session| {
+            quiz.questions
+                .iter()
+                .map(|question| {
+                    let correct = session
+                        .responses
+                        .iter()
+                        .any(|r| r.question_id == question.id && r.is_correct);
+                    if correct {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let item_variance: f32 = (0..items)
+        .map(|item| variance(matrix.iter().map(|row| row[item])))
+        .sum();
+    let total_variance = variance(matrix.iter().map(|row| row.iter().sum()));
+    if total_variance == 0.0 {
+        return None;
+    }
+
+    let k = items as f32;
+    Some(k / (k - 1.0) * (1.0 - item_variance / total_variance))
+}
+
+/// Population variance
+fn variance(values: impl Iterator<Item = f32> + Clone) -> f32 {
+    let n = values.clone().count() as f32;
+    let mean = values.clone().sum::<f32>() / n;
+    values.map(|v| (v - mean).powi(2)).sum::<f32>() / n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quiz::{Question, QuestionType, QuizBuilder};
+
+    fn session_answering(question: &Question, answer: Answer) -> QuizSession {
+        let mut session = QuizSession::new(Uuid::new_v4(), None);
+        session.start().unwrap();
+        session.submit_answer(question, answer, 10).unwrap();
+        session
+    }
+
+    #[test]
+    fn test_popular_distractor_stands_out() {
+        let question = Question::new(
+            QuestionType::MultipleChoice {
+                question: "Which type owns its data?".to_string(),
+                options: vec![
+                    "&str".to_string(),
+                    "String".to_string(),
+                    "&[u8]".to_string(),
+                ],
+                correct_index: 1,
+                alternate_correct: vec![],
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+        );
+        let sessions: Vec<QuizSession> = [0, 0, 0, 1, 2]
+            .into_iter()
+            .map(|i| session_answering(&question, Answer::MultipleChoice(i)))
+            .collect();
+
+        let distribution = option_distribution(question.id, &sessions);
+        assert_eq!(distribution, HashMap::from([(0, 3), (1, 1), (2, 1)]));
+        let most_picked = distribution.iter().max_by_key(|(_, n)| **n).unwrap();
+        assert_eq!(*most_picked.0, 0);
+
+        assert!(option_distribution(Uuid::new_v4(), &sessions).is_empty());
+    }
+
+    #[test]
+    fn test_non_choice_question_is_empty() {
+        let question = Question::new(
+            QuestionType::TrueFalse {
+                statement: "Rust has a garbage collector".to_string(),
+                correct_answer: false,
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+        );
+        let sessions = vec![session_answering(&question, Answer::TrueFalse(true))];
+        assert!(option_distribution(question.id, &sessions).is_empty());
+    }
+
+    #[test]
+    fn test_sorted_distribution_is_stable() {
+        let question = Question::new(
+            QuestionType::MultiSelect {
+                question: "Which are integer types?".to_string(),
+                options: (0..6).map(|i| format!("Type {}", i)).collect(),
+                correct_indices: vec![1, 4],
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+        );
+        let sessions = vec![
+            session_answering(&question, Answer::MultiSelect(vec![5, 1, 3])),
+            session_answering(&question, Answer::MultiSelect(vec![4, 1])),
+            session_answering(&question, Answer::MultiSelect(vec![0])),
+        ];
+
+        let expected = vec![(0, 1), (1, 2), (3, 1), (4, 1), (5, 1)];
+        for _ in 0..10 {
+            assert_eq!(option_distribution_sorted(question.id, &sessions), expected);
+        }
+    }
+
+    /// A quiz of `items` true/false questions and one session per row of
+    /// `matrix`, answering correctly where the row says so and skipping
+    /// the rest
+    fn quiz_with_responses(items: usize, matrix: &[&[bool]]) -> (Quiz, Vec<QuizSession>) {
+        let questions: Vec<Question> = (0..items)
+            .map(|i| {
+                Question::new(
+                    QuestionType::TrueFalse {
+                        statement: format!("Statement {}", i),
+                        correct_answer: true,
+                        explanation: None,
+                    },
+                    Uuid::new_v4(),
+                    0.5,
+                )
+            })
+            .collect();
+        let quiz = QuizBuilder::new("Reliability".to_string())
+            .add_questions(questions)
+            .build();
+
+        let sessions = matrix
+            .iter()
+            .map(|row| {
+                let mut session = QuizSession::new(quiz.id, None);
+                session.start().unwrap();
+                for (index, &correct) in row.iter().enumerate() {
+                    if correct {
+                        session
+                            .submit_answer(&quiz.questions[index], Answer::TrueFalse(true), 10)
+                            .unwrap();
+                    } else {
+                        session.skip_question(index);
+                    }
+                }
+                session
+            })
+            .collect();
+        (quiz, sessions)
+    }
+
+    #[test]
+    fn test_cronbach_alpha_consistent_responses() {
+        // Strong learners get everything right, weak ones nothing
+        let (quiz, sessions) = quiz_with_responses(
+            3,
+            &[
+                &[true, true, true],
+                &[true, true, true],
+                &[false, false, false],
+                &[false, false, false],
+            ],
+        );
+        let alpha = cronbach_alpha(&quiz, &sessions).unwrap();
+        assert!((alpha - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_cronbach_alpha_inconsistent_responses() {
+        // Getting one question right says nothing about the other
+        let (quiz, sessions) = quiz_with_responses(
+            2,
+            &[
+                &[true, false],
+                &[false, true],
+                &[true, false],
+                &[true, true],
+            ],
+        );
+        let alpha = cronbach_alpha(&quiz, &sessions).unwrap();
+        assert!(alpha < 0.0);
+    }
+
+    #[test]
+    fn test_cronbach_alpha_needs_two_items_and_sessions() {
+        let (quiz, sessions) = quiz_with_responses(1, &[&[true], &[false]]);
+        assert_eq!(cronbach_alpha(&quiz, &sessions), None);
+
+        let (quiz, sessions) = quiz_with_responses(2, &[&[true, false]]);
+        assert_eq!(cronbach_alpha(&quiz, &sessions), None);
+    }
+}
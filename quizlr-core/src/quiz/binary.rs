@@ -0,0 +1,115 @@
+//! Compact binary encoding for stored quizzes, sessions and scores
+//!
+//! Values are encoded as MessagePack behind a one-byte format version.
+//! MessagePack is used rather than bincode or postcard because it is
+//! self-describing: metadata holds arbitrary `serde_json::Value`s, which
+//! only self-describing formats can decode. Structs keep their field
+//! names, so data written before a field was added still decodes through
+//! the field's serde default.
+
+use super::quiz_impl::Quiz;
+use super::scoring::Score;
+use super::session::QuizSession;
+use crate::error::{QuizlrError, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Leading byte of every encoding, bumped on incompatible format changes
+const FORMAT_VERSION: u8 = 1;
+
+/// Types with a binary encoding
+pub trait BinaryFormat: Serialize + DeserializeOwned {}
+
+impl BinaryFormat for Quiz {}
+impl BinaryFormat for QuizSession {}
+impl BinaryFormat for Score {}
+
+pub fn to_bytes<T: BinaryFormat>(value: &T) -> Result<Vec<u8>> {
+    let mut bytes = vec![FORMAT_VERSION];
+    rmp_serde::encode::write_named(&mut bytes, value)
+        .map_err(|e| QuizlrError::InvalidInput(format!("Unencodable value: {}", e)))?;
+    Ok(bytes)
+}
+
+pub fn from_bytes<T: BinaryFormat>(bytes: &[u8]) -> Result<T> {
+    match bytes.split_first() {
+        Some((&FORMAT_VERSION, body)) => rmp_serde::from_slice(body)
+            .map_err(|e| QuizlrError::InvalidInput(format!("Undecodable binary data: {}", e))),
+        Some((version, _)) => Err(QuizlrError::InvalidInput(format!(
+            "Unsupported binary format version {}",
+            version
+        ))),
+        None => Err(QuizlrError::InvalidInput("Empty binary data".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quiz::{Answer, Question, QuestionType, QuizBuilder, ScoringStrategy};
+    use uuid::Uuid;
+
+    fn sample_quiz() -> Quiz {
+        let topic_id = Uuid::new_v4();
+        let questions = (0..20).map(|i| {
+            Question::new(
+                QuestionType::MultipleChoice {
+                    question: format!("Question {}", i),
+                    options: vec!["A".to_string(), "B".to_string(), "C".to_string()],
+                    correct_index: i % 3,
+                    alternate_correct: vec![],
+                    explanation: Some("Because".to_string()),
+                },
+                topic_id,
+                0.5,
+            )
+        });
+        QuizBuilder::new("Sample".to_string())
+            .add_questions(questions.collect())
+            .add_metadata("source".to_string(), serde_json::json!({"book": 1}))
+            .build()
+    }
+
+    fn assert_round_trip<T: BinaryFormat>(value: &T) -> usize {
+        let bytes = to_bytes(value).unwrap();
+        let decoded: T = from_bytes(&bytes).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(value).unwrap()
+        );
+        bytes.len()
+    }
+
+    #[test]
+    fn test_round_trip_and_smaller_than_json() {
+        let quiz = sample_quiz();
+        let binary_len = assert_round_trip(&quiz);
+        assert!(binary_len < serde_json::to_vec(&quiz).unwrap().len());
+
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+        for question in &quiz.questions[..5] {
+            session
+                .submit_answer(question, Answer::MultipleChoice(0), 10)
+                .unwrap();
+        }
+        assert_round_trip(&session);
+        assert_round_trip(
+            &ScoringStrategy::Simple {
+                hint_penalty_per_use: 0.0,
+            }
+            .calculate_score(&session, &quiz.questions),
+        );
+    }
+
+    #[test]
+    fn test_rejects_unknown_version() {
+        let mut bytes = to_bytes(&sample_quiz()).unwrap();
+        bytes[0] = FORMAT_VERSION + 1;
+        assert!(matches!(
+            from_bytes::<Quiz>(&bytes),
+            Err(QuizlrError::InvalidInput(_))
+        ));
+        assert!(from_bytes::<Quiz>(&[]).is_err());
+    }
+}
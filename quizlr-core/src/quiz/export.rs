@@ -0,0 +1,648 @@
+//! Export to IMS QTI 2.1 for learning management systems
+//!
+//! Only question types with a direct QTI interaction are exported: true/false
+//! and multiple choice become single-choice items, multi-select becomes a
+//! multiple-response item and fill-in-the-blank becomes text entry. QTI 2.1
+//! keeps each item in its own document, so the export is a content package:
+//! an `imsmanifest.xml`, an `assessmentTest` referencing every item through
+//! `assessmentItemRef`, and one file per item. Zip the files as they are
+//! for an LMS import.
+
+use super::question::{Question, QuestionType};
+use super::quiz_impl::Quiz;
+use std::collections::BTreeMap;
+
+const QTI_NAMESPACE: &str = "http://www.imsglobal.org/xsd/imsqti_v2p1";
+const CP_NAMESPACE: &str = "http://www.imsglobal.org/xsd/imscp_v1p1";
+
+/// Path of the package manifest
+pub const MANIFEST_PATH: &str = "imsmanifest.xml";
+
+/// A QTI 2.1 content package, as file contents keyed by their path inside
+/// the package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QtiPackage {
+    pub files: BTreeMap<String, String>,
+    /// Path of the `assessmentTest` document
+    pub test_path: String,
+}
+
+impl QtiPackage {
+    pub fn file(&self, path: &str) -> Option<&str> {
+        self.files.get(path).map(String::as_str)
+    }
+
+    pub fn manifest(&self) -> &str {
+        self.file(MANIFEST_PATH).unwrap_or_default()
+    }
+
+    pub fn test(&self) -> &str {
+        self.file(&self.test_path).unwrap_or_default()
+    }
+}
+
+/// Export `quiz` as a QTI 2.1 content package, silently skipping
+/// unsupported questions. Use [`to_qti_with_warnings`] to learn what was
+/// left out.
+pub fn to_qti(quiz: &Quiz) -> crate::Result<QtiPackage> {
+    to_qti_with_warnings(quiz).map(|(package, _)| package)
+}
+
+/// Export `quiz` as a QTI 2.1 content package, also returning one warning
+/// per question that was skipped because QTI has no matching interaction
+/// for it or because it fails [`Question::validate`]
+pub fn to_qti_with_warnings(quiz: &Quiz) -> crate::Result<(QtiPackage, Vec<String>)> {
+    let mut files = BTreeMap::new();
+    let mut items = Vec::new();
+    let mut warnings = Vec::new();
+
+    for (index, question) in quiz.questions.iter().enumerate() {
+        // An invalid answer key would produce items whose declarations don't
+        // line up with their interactions
+        if let Err(problem) = question.validate() {
+            warnings.push(format!(
+                "Question {} ({}) skipped: {}",
+                index,
+                kind_name(&question.question_type),
+                problem
+            ));
+            continue;
+        }
+
+        match item_xml(question) {
+            Some(item) => {
+                let id = item_id(question);
+                let href = format!("items/{}.xml", id);
+                files.insert(href.clone(), item);
+                items.push((id, href));
+            }
+            None => warnings.push(format!(
+                "Question {} ({}) skipped: not supported by QTI export",
+                index,
+                kind_name(&question.question_type)
+            )),
+        }
+    }
+
+    let test_id = format!("test-{}", quiz.id.simple());
+    let test_path = format!("{}.xml", test_id);
+    let item_refs: String = items
+        .iter()
+        .map(|(id, href)| {
+            format!(
+                "<assessmentItemRef identifier=\"{}\" href=\"{}\"/>\n",
+                id, href
+            )
+        })
+        .collect();
+    files.insert(
+        test_path.clone(),
+        format!(
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                "<assessmentTest xmlns=\"{ns}\" identifier=\"{id}\" title=\"{title}\">\n",
+                "<testPart identifier=\"part-1\" navigationMode=\"linear\" submissionMode=\"individual\">\n",
+                "<assessmentSection identifier=\"section-1\" title=\"{title}\" visible=\"true\">\n",
+                "{refs}",
+                "</assessmentSection>\n",
+                "</testPart>\n",
+                "</assessmentTest>\n",
+            ),
+            ns = QTI_NAMESPACE,
+            id = test_id,
+            title = escape(&quiz.title),
+            refs = item_refs,
+        ),
+    );
+
+    let dependencies: String = items
+        .iter()
+        .map(|(id, _)| format!("<dependency identifierref=\"{}\"/>\n", id))
+        .collect();
+    let item_resources: String = items
+        .iter()
+        .map(|(id, href)| {
+            format!(
+                "<resource identifier=\"{id}\" type=\"imsqti_item_xmlv2p1\" href=\"{href}\">\n<file href=\"{href}\"/>\n</resource>\n",
+                id = id,
+                href = href,
+            )
+        })
+        .collect();
+    files.insert(
+        MANIFEST_PATH.to_string(),
+        format!(
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                "<manifest xmlns=\"{ns}\" identifier=\"manifest-{quiz}\">\n",
+                "<organizations/>\n",
+                "<resources>\n",
+                "<resource identifier=\"{test}\" type=\"imsqti_test_xmlv2p1\" href=\"{path}\">\n",
+                "<file href=\"{path}\"/>\n",
+                "{dependencies}",
+                "</resource>\n",
+                "{items}",
+                "</resources>\n",
+                "</manifest>\n",
+            ),
+            ns = CP_NAMESPACE,
+            quiz = quiz.id.simple(),
+            test = test_id,
+            path = test_path,
+            dependencies = dependencies,
+            items = item_resources,
+        ),
+    );
+
+    Ok((QtiPackage { files, test_path }, warnings))
+}
+
+fn item_id(question: &Question) -> String {
+    format!("item-{}", question.id.simple())
+}
+
+/// A standalone `assessmentItem` document, or `None` for unsupported
+/// question types
+fn item_xml(question: &Question) -> Option<String> {
+    let mut declarations = String::new();
+    let mut conditions = Vec::new();
+
+    let body = match &question.question_type {
+        QuestionType::TrueFalse {
+            statement,
+            correct_answer,
+            ..
+        } => {
+            let options = ["True".to_string(), "False".to_string()];
+            let correct = if *correct_answer { 0 } else { 1 };
+            declarations.push_str(&choice_declaration("single", &[correct]));
+            conditions.push(match_correct("RESPONSE"));
+            choice_interaction(statement, &options, 1)
+        }
+        QuestionType::MultipleChoice {
+            question: prompt,
+            options,
+            correct_index,
+            alternate_correct,
+            ..
+        } => {
+            declarations.push_str(&choice_declaration("single", &[*correct_index]));
+            if alternate_correct.is_empty() {
+                conditions.push(match_correct("RESPONSE"));
+            } else {
+                let accepted: String = std::iter::once(correct_index)
+                    .chain(alternate_correct)
+                    .map(|i| {
+                        format!(
+                            "<baseValue baseType=\"identifier\">{}</baseValue>",
+                            choice_id(*i)
+                        )
+                    })
+                    .collect();
+                conditions.push(format!(
+                    "<member><variable identifier=\"RESPONSE\"/><multiple>{}</multiple></member>",
+                    accepted
+                ));
+            }
+            choice_interaction(prompt, options, 1)
+        }
+        QuestionType::MultiSelect {
+            question: prompt,
+            options,
+            correct_indices,
+            ..
+        } => {
+            declarations.push_str(&choice_declaration("multiple", correct_indices));
+            conditions.push(match_correct("RESPONSE"));
+            choice_interaction(prompt, options, 0)
+        }
+        QuestionType::FillInTheBlank {
+            template,
+            correct_answers,
+            case_sensitive,
+            ..
+        } => {
+            let mut body = String::from("<p>");
+            for (blank, segment) in template.split("{}").enumerate() {
+                if blank > 0 {
+                    body.push_str(&format!(
+                        "<textEntryInteraction responseIdentifier=\"{}\"/>",
+                        blank_id(blank - 1)
+                    ));
+                }
+                body.push_str(&escape(segment));
+            }
+            body.push_str("</p>\n");
+
+            for (blank, answer) in correct_answers.iter().enumerate() {
+                let id = blank_id(blank);
+                declarations.push_str(&format!(
+                    concat!(
+                        "<responseDeclaration identifier=\"{id}\" cardinality=\"single\" baseType=\"string\">",
+                        "<correctResponse><value>{answer}</value></correctResponse>",
+                        "</responseDeclaration>\n",
+                    ),
+                    id = id,
+                    answer = escape(answer),
+                ));
+                conditions.push(format!(
+                    "<stringMatch caseSensitive=\"{}\"><variable identifier=\"{id}\"/><correct identifier=\"{id}\"/></stringMatch>",
+                    case_sensitive,
+                    id = id,
+                ));
+            }
+            body
+        }
+        QuestionType::MatchPairs { .. }
+        | QuestionType::InteractiveInterview { .. }
+        | QuestionType::TopicExplanation { .. }
+        | QuestionType::Essay { .. }
+        | QuestionType::Custom { .. } => return None,
+    };
+
+    Some(format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<assessmentItem xmlns=\"{ns}\" identifier=\"{id}\" title=\"{title}\" adaptive=\"false\" timeDependent=\"false\">\n",
+            "{declarations}",
+            "<outcomeDeclaration identifier=\"SCORE\" cardinality=\"single\" baseType=\"float\"/>\n",
+            "<itemBody>\n{body}</itemBody>\n",
+            "{processing}",
+            "</assessmentItem>\n",
+        ),
+        ns = QTI_NAMESPACE,
+        id = item_id(question),
+        title = kind_name(&question.question_type),
+        declarations = declarations,
+        body = body,
+        processing = all_correct_processing(&conditions),
+    ))
+}
+
+fn choice_id(index: usize) -> String {
+    format!("choice-{}", index)
+}
+
+fn blank_id(index: usize) -> String {
+    format!("RESPONSE-{}", index)
+}
+
+fn choice_declaration(cardinality: &str, correct: &[usize]) -> String {
+    let values: String = correct
+        .iter()
+        .map(|&i| format!("<value>{}</value>", choice_id(i)))
+        .collect();
+    format!(
+        concat!(
+            "<responseDeclaration identifier=\"RESPONSE\" cardinality=\"{}\" baseType=\"identifier\">",
+            "<correctResponse>{}</correctResponse>",
+            "</responseDeclaration>\n",
+        ),
+        cardinality, values
+    )
+}
+
+/// `max_choices` of 0 means unlimited, as in QTI
+fn choice_interaction(prompt: &str, options: &[String], max_choices: usize) -> String {
+    let choices: String = options
+        .iter()
+        .enumerate()
+        .map(|(i, option)| {
+            format!(
+                "<simpleChoice identifier=\"{}\">{}</simpleChoice>\n",
+                choice_id(i),
+                escape(option)
+            )
+        })
+        .collect();
+    format!(
+        "<choiceInteraction responseIdentifier=\"RESPONSE\" shuffle=\"false\" maxChoices=\"{}\">\n<prompt>{}</prompt>\n{}</choiceInteraction>\n",
+        max_choices,
+        escape(prompt),
+        choices
+    )
+}
+
+fn match_correct(id: &str) -> String {
+    format!(
+        "<match><variable identifier=\"{id}\"/><correct identifier=\"{id}\"/></match>",
+        id = id
+    )
+}
+
+/// Score 1 when every condition holds, 0 otherwise
+fn all_correct_processing(conditions: &[String]) -> String {
+    format!(
+        concat!(
+            "<responseProcessing><responseCondition>",
+            "<responseIf><and>{}</and>",
+            "<setOutcomeValue identifier=\"SCORE\"><baseValue baseType=\"float\">1</baseValue></setOutcomeValue>",
+            "</responseIf>",
+            "<responseElse>",
+            "<setOutcomeValue identifier=\"SCORE\"><baseValue baseType=\"float\">0</baseValue></setOutcomeValue>",
+            "</responseElse>",
+            "</responseCondition></responseProcessing>\n",
+        ),
+        conditions.concat()
+    )
+}
+
+fn kind_name(question_type: &QuestionType) -> &'static str {
+    match question_type {
+        QuestionType::TrueFalse { .. } => "TrueFalse",
+        QuestionType::MultipleChoice { .. } => "MultipleChoice",
+        QuestionType::MultiSelect { .. } => "MultiSelect",
+        QuestionType::FillInTheBlank { .. } => "FillInTheBlank",
+        QuestionType::MatchPairs { .. } => "MatchPairs",
+        QuestionType::InteractiveInterview { .. } => "InteractiveInterview",
+        QuestionType::TopicExplanation { .. } => "TopicExplanation",
+        QuestionType::Essay { .. } => "Essay",
+        QuestionType::Custom { .. } => "Custom",
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quiz::QuizBuilder;
+    use uuid::Uuid;
+
+    /// An element of a parsed document, enough to check its structure
+    #[derive(Debug)]
+    struct Element {
+        name: String,
+        attributes: BTreeMap<String, String>,
+        children: Vec<Element>,
+    }
+
+    impl Element {
+        fn attr(&self, name: &str) -> &str {
+            self.attributes.get(name).map_or("", String::as_str)
+        }
+
+        fn child(&self, name: &str) -> &Element {
+            self.children
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap_or_else(|| panic!("<{}> has no <{}>", self.name, name))
+        }
+
+        fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+            self.children.iter().filter(move |c| c.name == name)
+        }
+
+        fn count(&self, name: &str) -> usize {
+            let own = usize::from(self.name == name);
+            own + self.children.iter().map(|c| c.count(name)).sum::<usize>()
+        }
+    }
+
+    /// Minimal XML parser: checks every start tag is closed in order and no
+    /// raw `&` or `<` appears in text, and returns the root element
+    fn parse(xml: &str) -> Element {
+        let body = xml
+            .strip_prefix("<?xml version=\"1.0\" encoding=\"UTF-8\"?>")
+            .expect("missing XML declaration");
+        let mut stack = vec![Element {
+            name: String::new(),
+            attributes: BTreeMap::new(),
+            children: Vec::new(),
+        }];
+        let mut rest = body;
+        while let Some(start) = rest.find('<') {
+            let text = &rest[..start];
+            for (i, _) in text.match_indices('&') {
+                let entity = &text[i..];
+                assert!(
+                    ["&amp;", "&lt;", "&gt;", "&quot;", "&apos;"]
+                        .iter()
+                        .any(|e| entity.starts_with(e)),
+                    "raw & in {:?}",
+                    text
+                );
+            }
+            let end = rest[start..].find('>').expect("unterminated tag") + start;
+            let tag = &rest[start + 1..end];
+            assert!(!tag.contains('<'), "raw < in tag {:?}", tag);
+            if let Some(name) = tag.strip_prefix('/') {
+                let element = stack.pop().expect("close without open");
+                assert_eq!(element.name, name, "mismatched close");
+                stack
+                    .last_mut()
+                    .expect("close of root")
+                    .children
+                    .push(element);
+            } else {
+                let (tag, empty) = match tag.strip_suffix('/') {
+                    Some(tag) => (tag, true),
+                    None => (tag, false),
+                };
+                let (name, mut attrs) = tag.split_once(' ').unwrap_or((tag, ""));
+                let mut attributes = BTreeMap::new();
+                while let Some((key, after)) = attrs.split_once("=\"") {
+                    let (value, after) = after.split_once('"').expect("unterminated attribute");
+                    attributes.insert(key.trim().to_string(), value.to_string());
+                    attrs = after;
+                }
+                let element = Element {
+                    name: name.to_string(),
+                    attributes,
+                    children: Vec::new(),
+                };
+                if empty {
+                    stack.last_mut().unwrap().children.push(element);
+                } else {
+                    stack.push(element);
+                }
+            }
+            rest = &rest[end + 1..];
+        }
+        assert_eq!(stack.len(), 1, "unclosed tags");
+        let mut document = stack.pop().unwrap();
+        assert_eq!(document.children.len(), 1, "expected a single root element");
+        document.children.pop().unwrap()
+    }
+
+    #[test]
+    fn test_to_qti_exports_supported_questions() {
+        let topic = Uuid::new_v4();
+        let quiz = QuizBuilder::new("Rust & <Friends>".to_string())
+            .add_question(Question::new(
+                QuestionType::TrueFalse {
+                    statement: "Rust has \"zero-cost\" abstractions".to_string(),
+                    correct_answer: true,
+                    explanation: None,
+                },
+                topic,
+                0.3,
+            ))
+            .add_question(Question::new(
+                QuestionType::MultipleChoice {
+                    question: "Pick 1 < 2".to_string(),
+                    options: vec!["a".to_string(), "b & c".to_string()],
+                    correct_index: 1,
+                    alternate_correct: vec![0],
+                    explanation: None,
+                },
+                topic,
+                0.5,
+            ))
+            .add_question(Question::new(
+                QuestionType::MultiSelect {
+                    question: "Pick".to_string(),
+                    options: vec!["x".to_string(), "y".to_string(), "z".to_string()],
+                    correct_indices: vec![0, 2],
+                    explanation: None,
+                },
+                topic,
+                0.5,
+            ))
+            .add_question(Question::new(
+                QuestionType::FillInTheBlank {
+                    template: "{} borrows, {} owns".to_string(),
+                    correct_answers: vec!["&T".to_string(), "T".to_string()],
+                    case_sensitive: true,
+                    per_blank_credit: false,
+                    explanation: None,
+                },
+                topic,
+                0.5,
+            ))
+            .add_question(Question::new(
+                QuestionType::TopicExplanation {
+                    topic: "Lifetimes".to_string(),
+                    prompt: "Explain".to_string(),
+                    key_concepts: vec![],
+                    min_word_count: 50,
+                },
+                topic,
+                0.7,
+            ))
+            .build();
+
+        let (package, warnings) = to_qti_with_warnings(&quiz).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("Question 4 (TopicExplanation)"));
+        assert_eq!(package.files.len(), 6);
+
+        // The test only references items; none are inlined
+        let test = parse(package.test());
+        assert_eq!(test.name, "assessmentTest");
+        assert_eq!(test.attr("xmlns"), QTI_NAMESPACE);
+        assert_eq!(test.attr("title"), "Rust &amp; &lt;Friends&gt;");
+        assert_eq!(test.count("assessmentItem"), 0);
+        let section = test.child("testPart").child("assessmentSection");
+        let refs: Vec<&Element> = section.children_named("assessmentItemRef").collect();
+        assert_eq!(refs.len(), 4);
+        assert_eq!(section.children.len(), 4);
+
+        // Each reference resolves to a standalone item document
+        let items: Vec<Element> = refs
+            .iter()
+            .map(|item_ref| {
+                let item = parse(package.file(item_ref.attr("href")).expect("dangling href"));
+                assert_eq!(item.name, "assessmentItem");
+                assert_eq!(item.attr("xmlns"), QTI_NAMESPACE);
+                assert_eq!(item.attr("identifier"), item_ref.attr("identifier"));
+                let order: Vec<&str> = item.children.iter().map(|c| c.name.as_str()).collect();
+                let body = order.iter().position(|n| *n == "itemBody").unwrap();
+                assert!(order[..body].contains(&"responseDeclaration"));
+                assert!(order[..body].contains(&"outcomeDeclaration"));
+                assert_eq!(order[body + 1..], ["responseProcessing"]);
+                item
+            })
+            .collect();
+        assert_eq!(items[0].count("choiceInteraction"), 1);
+        assert_eq!(items[1].count("member"), 1);
+        assert_eq!(
+            items[2]
+                .child("itemBody")
+                .child("choiceInteraction")
+                .attr("maxChoices"),
+            "0"
+        );
+        assert_eq!(items[3].count("textEntryInteraction"), 2);
+        let blank = items[3]
+            .child("responseDeclaration")
+            .child("correctResponse");
+        assert_eq!(blank.children_named("value").count(), 1);
+        assert!(package
+            .file(refs[3].attr("href"))
+            .unwrap()
+            .contains("<value>&amp;T</value>"));
+
+        // The manifest lists the test, depending on every item, and each item
+        let manifest = parse(package.manifest());
+        assert_eq!(manifest.name, "manifest");
+        let resources: Vec<&Element> = manifest
+            .child("resources")
+            .children_named("resource")
+            .collect();
+        assert_eq!(resources.len(), 5);
+        assert_eq!(resources[0].attr("type"), "imsqti_test_xmlv2p1");
+        assert_eq!(resources[0].attr("href"), package.test_path);
+        let dependencies: Vec<&str> = resources[0]
+            .children_named("dependency")
+            .map(|d| d.attr("identifierref"))
+            .collect();
+        for (resource, item_ref) in resources[1..].iter().zip(&refs) {
+            assert_eq!(resource.attr("type"), "imsqti_item_xmlv2p1");
+            assert_eq!(resource.attr("href"), item_ref.attr("href"));
+            assert_eq!(resource.child("file").attr("href"), item_ref.attr("href"));
+            assert!(dependencies.contains(&resource.attr("identifier")));
+        }
+
+        assert_eq!(to_qti(&quiz).unwrap(), package);
+    }
+
+    #[test]
+    fn test_to_qti_skips_mismatched_blanks() {
+        let topic = Uuid::new_v4();
+        let fill_in = |template: &str, answers: &[&str]| {
+            Question::new(
+                QuestionType::FillInTheBlank {
+                    template: template.to_string(),
+                    correct_answers: answers.iter().map(|a| a.to_string()).collect(),
+                    case_sensitive: false,
+                    per_blank_credit: false,
+                    explanation: None,
+                },
+                topic,
+                0.5,
+            )
+        };
+        let quiz = QuizBuilder::new("Blanks".to_string())
+            .add_question(fill_in("{} and {}", &["a"]))
+            .add_question(fill_in("No blanks here", &[]))
+            .add_question(fill_in("Just {}", &["one"]))
+            .build();
+
+        let (package, warnings) = to_qti_with_warnings(&quiz).unwrap();
+        assert_eq!(
+            warnings,
+            vec![
+                "Question 0 (FillInTheBlank) skipped: template has 2 blanks but 1 answers"
+                    .to_string(),
+                "Question 1 (FillInTheBlank) skipped: template has 0 blanks but 0 answers"
+                    .to_string(),
+            ]
+        );
+        // The manifest, the test and the one valid item
+        assert_eq!(package.files.len(), 3);
+    }
+}
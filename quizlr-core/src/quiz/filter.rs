@@ -0,0 +1,82 @@
+//! Selecting a subset of a question bank
+
+use super::question::Question;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Conjunction of optional clauses; an empty filter matches everything
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuestionFilter {
+    pub topic_id: Option<Uuid>,
+    pub tag: Option<String>,
+    pub locale: Option<String>,
+}
+
+impl QuestionFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn topic(mut self, topic_id: Uuid) -> Self {
+        self.topic_id = Some(topic_id);
+        self
+    }
+
+    pub fn tag(mut self, tag: String) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+
+    /// Keep questions in `locale` or any of its subtags, so `"es"` also
+    /// matches `"es-MX"`. Questions without a locale never match.
+    pub fn locale(mut self, locale: String) -> Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    pub fn matches(&self, question: &Question) -> bool {
+        if self.topic_id.is_some_and(|id| id != question.topic_id) {
+            return false;
+        }
+        if let Some(tag) = &self.tag {
+            if !question.tags.contains(tag) {
+                return false;
+            }
+        }
+        if let Some(range) = &self.locale {
+            match &question.locale {
+                Some(locale) if locale_matches(range, locale) => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    pub fn apply<'a>(&self, questions: &'a [Question]) -> Vec<&'a Question> {
+        questions.iter().filter(|q| self.matches(q)).collect()
+    }
+}
+
+/// BCP-47 basic filtering (RFC 4647): case-insensitive, and a range matches
+/// a tag equal to it or extending it by whole subtags
+fn locale_matches(range: &str, locale: &str) -> bool {
+    let range = range.to_ascii_lowercase();
+    let locale = locale.to_ascii_lowercase();
+    locale == range
+        || locale
+            .strip_prefix(&range)
+            .is_some_and(|rest| rest.starts_with('-'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_range_matching() {
+        assert!(locale_matches("es", "es"));
+        assert!(locale_matches("es", "ES-mx"));
+        assert!(!locale_matches("es", "est"));
+        assert!(!locale_matches("es-MX", "es"));
+    }
+}
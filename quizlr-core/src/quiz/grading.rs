@@ -0,0 +1,132 @@
+//! Grading for question types defined outside this crate
+//!
+//! A `QuestionType::Custom` question names its kind and carries free-form
+//! data. Applications register a constructor per kind that turns that data
+//! into something [`Gradeable`]; `Question::validate_answer` and
+//! `Question::partial_credit` then grade custom questions through it.
+
+use super::question::Answer;
+use crate::error::{QuizlrError, Result};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+/// A custom question, ready to grade answers
+pub trait Gradeable: Send + Sync {
+    /// Credit for `answer`, from 0.0 to 1.0; out of range values are
+    /// clamped. Only full credit counts as correct.
+    fn grade(&self, answer: &Answer) -> Result<f32>;
+}
+
+/// Builds a custom question's grader from its data
+pub type GradeableConstructor = fn(data: &serde_json::Value) -> Result<Box<dyn Gradeable>>;
+
+fn registry() -> &'static RwLock<HashMap<String, GradeableConstructor>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<String, GradeableConstructor>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Register the grader for custom questions of `kind`, replacing any
+/// registered before. Registration is process-wide.
+pub fn register_custom_type(kind: impl Into<String>, constructor: GradeableConstructor) {
+    registry()
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(kind.into(), constructor);
+}
+
+pub fn is_custom_type_registered(kind: &str) -> bool {
+    registry()
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .contains_key(kind)
+}
+
+/// Grade `answer` to a custom question of `kind` with the given data
+pub(super) fn grade_custom(kind: &str, data: &serde_json::Value, answer: &Answer) -> Result<f32> {
+    let constructor = registry()
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .get(kind)
+        .copied()
+        .ok_or_else(|| {
+            QuizlrError::NotFound(format!("No grader registered for question kind {}", kind))
+        })?;
+    let credit = constructor(data)?.grade(answer)?;
+    Ok(credit.clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quiz::{Question, QuestionType};
+    use serde::Deserialize;
+    use uuid::Uuid;
+
+    /// Numeric answer with a tolerance; half credit within twice the
+    /// tolerance
+    #[derive(Deserialize)]
+    struct Numeric {
+        answer: f64,
+        tolerance: f64,
+    }
+
+    impl Gradeable for Numeric {
+        fn grade(&self, answer: &Answer) -> Result<f32> {
+            let Answer::Custom(value) = answer else {
+                return Err(QuizlrError::InvalidInput("expected a number".to_string()));
+            };
+            let given = value
+                .as_f64()
+                .ok_or_else(|| QuizlrError::InvalidInput("expected a number".to_string()))?;
+            let error = (given - self.answer).abs();
+            Ok(if error <= self.tolerance {
+                1.0
+            } else if error <= 2.0 * self.tolerance {
+                0.5
+            } else {
+                0.0
+            })
+        }
+    }
+
+    fn numeric(data: &serde_json::Value) -> Result<Box<dyn Gradeable>> {
+        Ok(Box::new(Numeric::deserialize(data)?))
+    }
+
+    fn custom_question(kind: &str) -> Question {
+        Question::new(
+            QuestionType::Custom {
+                kind: kind.to_string(),
+                data: serde_json::json!({"answer": 9.81, "tolerance": 0.1}),
+            },
+            Uuid::new_v4(),
+            0.5,
+        )
+    }
+
+    #[test]
+    fn test_registered_custom_type_is_graded() {
+        register_custom_type("test-numeric", numeric);
+        assert!(is_custom_type_registered("test-numeric"));
+        let question = custom_question("test-numeric");
+
+        let answer = |value: f64| Answer::Custom(serde_json::json!(value));
+        assert_eq!(question.validate_answer(&answer(9.8)), Ok(true));
+        assert_eq!(question.validate_answer(&answer(9.95)), Ok(false));
+        assert_eq!(question.partial_credit(&answer(9.95)).unwrap(), 0.5);
+        assert_eq!(question.partial_credit(&answer(12.0)).unwrap(), 0.0);
+        assert!(question.validate_answer(&Answer::TrueFalse(true)).is_err());
+    }
+
+    #[test]
+    fn test_unregistered_custom_type_errors() {
+        let question = custom_question("test-never-registered");
+        assert!(question
+            .validate_answer(&Answer::Custom(serde_json::json!(9.81)))
+            .is_err());
+        assert!(matches!(
+            question.partial_credit(&Answer::Custom(serde_json::json!(9.81))),
+            Err(QuizlrError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,412 @@
+//! Building one question bank from many sources
+
+use super::question::{Question, QuestionType};
+use super::quiz_impl::{Quiz, QuizBuilder};
+use crate::error::QuizlrError;
+use std::collections::HashSet;
+use std::fmt;
+use uuid::Uuid;
+
+/// Difficulty given to imported questions that carry none
+const DEFAULT_DIFFICULTY: f32 = 0.5;
+
+/// Something an importer skipped or had to guess at
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportWarning {
+    /// 1-based source line, when the issue is tied to one
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ImportWarning {
+    pub fn at_line(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line: Some(line),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ImportWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "Line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// What an importer parsed, along with whatever it had to skip
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportOutcome<T> {
+    pub value: T,
+    pub warnings: Vec<ImportWarning>,
+}
+
+impl<T> ImportOutcome<T> {
+    pub fn has_warnings(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+}
+
+/// Accumulates questions across several imports, dropping any whose
+/// content matches a question already imported
+#[derive(Debug, Clone, Default)]
+pub struct ImportSession {
+    questions: Vec<Question>,
+    seen: HashSet<u64>,
+    prefilter: Option<BloomFilter>,
+    exact_checks: usize,
+    duplicates_skipped: usize,
+}
+
+impl ImportSession {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Screen incoming questions with a bloom filter sized for
+    /// `expected_questions` at the given false-positive rate. Only questions
+    /// the filter flags as probable duplicates get the exact content-hash
+    /// check, so results stay exact; a lower rate means fewer exact checks
+    /// at the cost of a larger filter.
+    pub fn with_bloom_filter(
+        mut self,
+        expected_questions: usize,
+        false_positive_rate: f64,
+    ) -> crate::Result<Self> {
+        if !(false_positive_rate > 0.0 && false_positive_rate < 1.0) {
+            return Err(QuizlrError::InvalidInput(format!(
+                "False-positive rate must be between 0 and 1, got {}",
+                false_positive_rate
+            )));
+        }
+
+        let mut filter = BloomFilter::new(expected_questions, false_positive_rate);
+        for &hash in &self.seen {
+            filter.insert(hash);
+        }
+        self.prefilter = Some(filter);
+        Ok(self)
+    }
+
+    /// Import questions, returning how many were new
+    pub fn import_questions(&mut self, questions: impl IntoIterator<Item = Question>) -> usize {
+        let before = self.questions.len();
+        for question in questions {
+            if self.is_new(question.content_hash()) {
+                self.questions.push(question);
+            } else {
+                self.duplicates_skipped += 1;
+            }
+        }
+        self.questions.len() - before
+    }
+
+    /// Record `hash`, returning whether it hadn't been seen before
+    fn is_new(&mut self, hash: u64) -> bool {
+        if let Some(filter) = &mut self.prefilter {
+            if !filter.insert(hash) {
+                // Definitely unseen, no exact check needed
+                self.seen.insert(hash);
+                return true;
+            }
+        }
+        self.exact_checks += 1;
+        self.seen.insert(hash)
+    }
+
+    /// Import every question of an existing quiz
+    pub fn import_quiz(&mut self, quiz: Quiz) -> usize {
+        self.import_questions(quiz.questions)
+    }
+
+    /// Import from JSON holding either a full quiz or a bare question array
+    pub fn import_json(&mut self, json: &str) -> crate::Result<usize> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let questions: Vec<Question> = if value.is_array() {
+            serde_json::from_value(value)?
+        } else if value.get("questions").is_some() {
+            serde_json::from_value::<Quiz>(value)?.questions
+        } else {
+            return Err(QuizlrError::InvalidInput(
+                "expected a quiz or an array of questions".to_string(),
+            ));
+        };
+        Ok(self.import_questions(questions))
+    }
+
+    pub fn questions(&self) -> &[Question] {
+        &self.questions
+    }
+
+    pub fn len(&self) -> usize {
+        self.questions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.questions.is_empty()
+    }
+
+    pub fn duplicates_skipped(&self) -> usize {
+        self.duplicates_skipped
+    }
+
+    /// How many imported questions needed the exact duplicate check
+    pub fn exact_checks(&self) -> usize {
+        self.exact_checks
+    }
+
+    pub fn into_quiz(self, title: String) -> Quiz {
+        QuizBuilder::new(title)
+            .add_questions(self.questions)
+            .build()
+    }
+}
+
+/// Bit-array bloom filter over question content hashes, probing with
+/// double hashing
+#[derive(Debug, Clone)]
+struct BloomFilter {
+    bits: Vec<u64>,
+    bit_count: u64,
+    probes: u32,
+}
+
+impl BloomFilter {
+    fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        let n = expected_items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let bit_count = (-n * false_positive_rate.ln() / (ln2 * ln2))
+            .ceil()
+            .max(64.0) as u64;
+        let probes = ((bit_count as f64 / n) * ln2).round().max(1.0) as u32;
+        Self {
+            bits: vec![0; bit_count.div_ceil(64) as usize],
+            bit_count,
+            probes,
+        }
+    }
+
+    /// Set the bits for `hash`, returning whether they were all already set,
+    /// i.e. whether `hash` was probably inserted before
+    fn insert(&mut self, hash: u64) -> bool {
+        let h1 = hash;
+        // Odd, so successive probes never cycle back early
+        let h2 = mix(hash) | 1;
+        let mut present = true;
+        for i in 0..u64::from(self.probes) {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) % self.bit_count;
+            let (word, mask) = ((bit / 64) as usize, 1u64 << (bit % 64));
+            present &= self.bits[word] & mask != 0;
+            self.bits[word] |= mask;
+        }
+        present
+    }
+}
+
+/// splitmix64 finalizer, to derive a second independent hash
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// Parse Anki's plain-text export: one note per line, tab-separated
+/// front, back and an optional space-separated tags column. Each note
+/// becomes a `FillInTheBlank` asking for the back given the front. Lines
+/// starting with `#` are Anki's file headers and are skipped. Lines missing
+/// a front or back are skipped with a warning. Questions get the nil topic
+/// id until assigned one.
+pub fn from_anki_txt(text: &str) -> ImportOutcome<Vec<Question>> {
+    let mut questions = Vec::new();
+    let mut warnings = Vec::new();
+
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split('\t');
+        let front = fields.next().map(decode_html_entities).unwrap_or_default();
+        let back = fields.next().map(decode_html_entities).unwrap_or_default();
+        if front.trim().is_empty() || back.trim().is_empty() {
+            warnings.push(ImportWarning::at_line(
+                line_no + 1,
+                "expected tab-separated front and back",
+            ));
+            continue;
+        }
+        let tags: Vec<String> = fields
+            .next()
+            .map(|t| t.split_whitespace().map(decode_html_entities).collect())
+            .unwrap_or_default();
+
+        let mut question = Question::new(
+            QuestionType::FillInTheBlank {
+                template: format!("{} {{}}", front.trim()),
+                correct_answers: vec![back.trim().to_string()],
+                case_sensitive: false,
+                per_blank_credit: false,
+                explanation: None,
+            },
+            Uuid::nil(),
+            DEFAULT_DIFFICULTY,
+        );
+        question.tags = tags;
+        questions.push(question);
+    }
+
+    ImportOutcome {
+        value: questions,
+        warnings,
+    }
+}
+
+/// Decode the HTML entities Anki commonly writes into exported fields
+fn decode_html_entities(field: &str) -> String {
+    field
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        // Last, so "&amp;lt;" decodes to "&lt;" rather than "<"
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn question(statement: &str) -> Question {
+        Question::new(
+            QuestionType::TrueFalse {
+                statement: statement.to_string(),
+                correct_answer: true,
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+        )
+    }
+
+    #[test]
+    fn test_same_question_imported_once() {
+        let mut import = ImportSession::new();
+        assert_eq!(
+            import.import_questions(vec![question("A"), question("B")]),
+            2
+        );
+
+        // Same content under a fresh id, as from a second source file
+        let json = serde_json::to_string(&vec![question("A"), question("C")]).unwrap();
+        assert_eq!(import.import_json(&json).unwrap(), 1);
+        assert_eq!(import.duplicates_skipped(), 1);
+
+        let quiz = import.into_quiz("Bank".to_string());
+        assert_eq!(quiz.title, "Bank");
+        assert_eq!(quiz.questions.len(), 3);
+    }
+
+    #[test]
+    fn test_import_json_accepts_quiz() {
+        let quiz = QuizBuilder::new("Source".to_string())
+            .add_question(question("A"))
+            .build();
+        let mut import = ImportSession::new();
+        import.import_quiz(quiz.clone());
+
+        let json = serde_json::to_string(&quiz).unwrap();
+        assert_eq!(import.import_json(&json).unwrap(), 0);
+        assert_eq!(import.len(), 1);
+        assert_eq!(import.duplicates_skipped(), 1);
+
+        assert!(matches!(
+            import.import_json("{\"title\": \"nope\"}"),
+            Err(QuizlrError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_anki_txt_tagged_line() {
+        let text = "#separator:tab\n#html:true\nCapital of France?\tParis\tgeography europe\n";
+        let questions = from_anki_txt(text).value;
+        assert_eq!(questions.len(), 1);
+        assert_eq!(questions[0].tags, vec!["geography", "europe"]);
+        match &questions[0].question_type {
+            QuestionType::FillInTheBlank {
+                template,
+                correct_answers,
+                ..
+            } => {
+                assert_eq!(template, "Capital of France? {}");
+                assert_eq!(correct_answers, &vec!["Paris".to_string()]);
+            }
+            other => panic!("expected fill in the blank, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_from_anki_txt_untagged_line_with_entities() {
+        let outcome = from_anki_txt("Is 1 &lt; 2?\tYes &amp; always\r\n\n");
+        assert!(!outcome.has_warnings());
+        let questions = outcome.value;
+        assert_eq!(questions.len(), 1);
+        assert!(questions[0].tags.is_empty());
+        match &questions[0].question_type {
+            QuestionType::FillInTheBlank {
+                template,
+                correct_answers,
+                ..
+            } => {
+                assert_eq!(template, "Is 1 < 2? {}");
+                assert_eq!(correct_answers, &vec!["Yes & always".to_string()]);
+            }
+            other => panic!("expected fill in the blank, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_from_anki_txt_warns_on_bad_line() {
+        let text = "Capital of France?\tParis\nfront only\nCapital of Spain?\tMadrid\n";
+        let outcome = from_anki_txt(text);
+
+        assert_eq!(outcome.value.len(), 2);
+        assert_eq!(
+            outcome.warnings,
+            vec![ImportWarning::at_line(
+                2,
+                "expected tab-separated front and back"
+            )]
+        );
+        assert_eq!(
+            outcome.warnings[0].to_string(),
+            "Line 2: expected tab-separated front and back"
+        );
+    }
+
+    #[test]
+    fn test_bloom_prefilter_keeps_dedupe_exact() {
+        let unique: Vec<Question> = (0..5000).map(|i| question(&format!("Q{}", i))).collect();
+        let repeats: Vec<Question> = (0..5000)
+            .step_by(5)
+            .map(|i| question(&format!("Q{}", i)))
+            .collect();
+
+        let mut import = ImportSession::new().with_bloom_filter(5000, 0.01).unwrap();
+        assert_eq!(import.import_questions(unique), 5000);
+        assert_eq!(import.import_questions(repeats), 0);
+        assert_eq!(import.len(), 5000);
+        assert_eq!(import.duplicates_skipped(), 1000);
+
+        // Every duplicate is checked exactly; only false positives add more
+        assert!(import.exact_checks() >= 1000);
+        assert!(import.exact_checks() < 1000 + 500);
+
+        assert!(ImportSession::new().with_bloom_filter(10, 0.0).is_err());
+        assert!(ImportSession::new().with_bloom_filter(10, 1.0).is_err());
+    }
+}
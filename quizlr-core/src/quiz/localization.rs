@@ -0,0 +1,35 @@
+//! Localization hooks for user-facing grade and feedback strings
+
+/// Supplies translated labels and messages for session results
+pub trait Localizer {
+    /// Display label for a letter grade (`"A"` through `"F"`)
+    fn grade_label(&self, grade: &str) -> String;
+
+    /// Message shown when a session meets the pass threshold
+    fn pass_message(&self, score: f32) -> String;
+
+    /// Message shown when a session falls short of the pass threshold
+    fn fail_message(&self, score: f32, pass_threshold: f32) -> String;
+}
+
+/// The default, English-language localizer
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnglishLocalizer;
+
+impl Localizer for EnglishLocalizer {
+    fn grade_label(&self, grade: &str) -> String {
+        grade.to_string()
+    }
+
+    fn pass_message(&self, score: f32) -> String {
+        format!("Passed with {:.0}%", score * 100.0)
+    }
+
+    fn fail_message(&self, score: f32, pass_threshold: f32) -> String {
+        format!(
+            "Scored {:.0}%, {:.0}% needed to pass",
+            score * 100.0,
+            pass_threshold * 100.0
+        )
+    }
+}
@@ -1,7 +1,21 @@
+mod accumulator;
+pub mod analytics;
+#[cfg(feature = "binary")]
+pub mod binary;
+pub mod export;
+mod filter;
+mod grading;
+pub mod import;
+mod localization;
+mod normalize;
+mod pool;
 mod question;
 mod quiz_impl;
 mod scoring;
+mod sections;
 mod session;
+mod student;
+mod transcript;
 
 #[cfg(test)]
 mod question_tests;
@@ -10,9 +24,31 @@ mod quiz_impl_tests;
 #[cfg(test)]
 mod scoring_tests;
 #[cfg(test)]
+mod sections_tests;
+#[cfg(test)]
 mod session_tests;
 
-pub use question::{Answer, Question, QuestionType};
-pub use quiz_impl::{Quiz, QuizBuilder};
-pub use scoring::{Score, ScoringStrategy};
-pub use session::{QuizSession, SessionState};
+pub use accumulator::ScoreAccumulator;
+pub use filter::QuestionFilter;
+pub use grading::{
+    is_custom_type_registered, register_custom_type, Gradeable, GradeableConstructor,
+};
+pub use localization::{EnglishLocalizer, Localizer};
+pub use normalize::{NormalizationStep, TextNormalizer};
+pub use pool::QuestionPool;
+pub use question::{Answer, MediaAsset, MediaKind, Question, QuestionType, RubricCriterion};
+pub use quiz_impl::{
+    BankQuizBuilder, Quiz, QuizBuilder, QuizDiff, QuizTxn, QuizValidationError, SettingChange,
+};
+pub use scoring::{z_score, DifficultyBuckets, DifficultyLevel, Score, ScoringStrategy};
+pub use sections::{
+    passed_all_sections, section_results, section_results_sorted, sections_by_tag,
+    sections_by_tag_sorted, weighted_section_score, SectionPolicy, SectionResult,
+};
+pub use session::{
+    AbandonReason, AttemptRecord, ExpiryPolicy, ExpiryReason, Pace, PaceReport, PauseRecord,
+    QuestionResponse, QuizSession, QuizSessionBuilder, ReviewItem, ReviewSession, SessionState,
+    SessionSummary, SubmissionResult, TimingMode,
+};
+pub use student::{QuestionView, QuizView, StudentQuestion, StudentQuestionType, StudentQuiz};
+pub use transcript::Transcript;
@@ -0,0 +1,186 @@
+//! Configurable leniency for comparing typed answers
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// One normalization step. Enabled steps always run in the order declared
+/// here, whatever order they were enabled in: accents come off before
+/// punctuation is stripped, and whitespace is tidied last so gaps left by
+/// removed punctuation are cleaned up too.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NormalizationStep {
+    Unaccent,
+    Lowercase,
+    StripPunctuation,
+    CollapseWhitespace,
+    Trim,
+}
+
+/// A set of enabled [`NormalizationStep`]s applied to both the typed answer
+/// and the answer key before comparing. The default enables nothing.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TextNormalizer {
+    steps: BTreeSet<NormalizationStep>,
+}
+
+impl TextNormalizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trim, lowercase, strip punctuation, collapse spaces and unaccent
+    pub fn lenient() -> Self {
+        Self::new()
+            .with(NormalizationStep::Unaccent)
+            .with(NormalizationStep::Lowercase)
+            .with(NormalizationStep::StripPunctuation)
+            .with(NormalizationStep::CollapseWhitespace)
+            .with(NormalizationStep::Trim)
+    }
+
+    pub fn with(mut self, step: NormalizationStep) -> Self {
+        self.steps.insert(step);
+        self
+    }
+
+    pub fn has(&self, step: NormalizationStep) -> bool {
+        self.steps.contains(&step)
+    }
+
+    pub fn steps(&self) -> impl Iterator<Item = NormalizationStep> + '_ {
+        self.steps.iter().copied()
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for step in &self.steps {
+            text = match step {
+                NormalizationStep::Unaccent => text.chars().map(unaccent).collect(),
+                NormalizationStep::Lowercase => text.to_lowercase(),
+                NormalizationStep::StripPunctuation => text
+                    .chars()
+                    .filter(|c| !c.is_ascii_punctuation() && !is_unicode_punctuation(*c))
+                    .collect(),
+                NormalizationStep::CollapseWhitespace => {
+                    let mut collapsed = String::with_capacity(text.len());
+                    let mut in_space = false;
+                    for c in text.chars() {
+                        if c.is_whitespace() {
+                            if !in_space {
+                                collapsed.push(' ');
+                            }
+                            in_space = true;
+                        } else {
+                            collapsed.push(c);
+                            in_space = false;
+                        }
+                    }
+                    collapsed
+                }
+                NormalizationStep::Trim => text.trim().to_string(),
+            };
+        }
+        text
+    }
+
+    /// Whether `user` and `correct` are equal once both are normalized
+    pub fn matches(&self, user: &str, correct: &str) -> bool {
+        self.apply(user) == self.apply(correct)
+    }
+}
+
+fn is_unicode_punctuation(c: char) -> bool {
+    matches!(
+        c,
+        '¡' | '¿' | '«' | '»' | '‘' | '’' | '“' | '”' | '–' | '—' | '…' | '·'
+    )
+}
+
+/// Base letter for accented Latin letters; other characters are unchanged
+fn unaccent(c: char) -> char {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => 'a',
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => 'A',
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => 'c',
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => 'C',
+        'ď' | 'đ' => 'd',
+        'Ď' | 'Đ' => 'D',
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => 'e',
+        'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => 'E',
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => 'g',
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => 'G',
+        'ĥ' | 'ħ' => 'h',
+        'Ĥ' | 'Ħ' => 'H',
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => 'i',
+        'Ì' | 'Í' | 'Î' | 'Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => 'I',
+        'ĵ' => 'j',
+        'Ĵ' => 'J',
+        'ķ' => 'k',
+        'Ķ' => 'K',
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => 'l',
+        'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => 'L',
+        'ñ' | 'ń' | 'ņ' | 'ň' => 'n',
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' => 'N',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => 'o',
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => 'O',
+        'ŕ' | 'ŗ' | 'ř' => 'r',
+        'Ŕ' | 'Ŗ' | 'Ř' => 'R',
+        'ś' | 'ŝ' | 'ş' | 'š' => 's',
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' => 'S',
+        'ţ' | 'ť' | 'ŧ' => 't',
+        'Ţ' | 'Ť' | 'Ŧ' => 'T',
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => 'u',
+        'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => 'U',
+        'ŵ' => 'w',
+        'Ŵ' => 'W',
+        'ý' | 'ÿ' | 'ŷ' => 'y',
+        'Ý' | 'Ŷ' | 'Ÿ' => 'Y',
+        'ź' | 'ż' | 'ž' => 'z',
+        'Ź' | 'Ż' | 'Ž' => 'Z',
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cafe_needs_unaccent_and_strip_punctuation() {
+        let base = TextNormalizer::new().with(NormalizationStep::Lowercase);
+        assert!(!base.matches("Café!", "cafe"));
+        assert!(!base
+            .clone()
+            .with(NormalizationStep::Unaccent)
+            .matches("Café!", "cafe"));
+        assert!(!base
+            .clone()
+            .with(NormalizationStep::StripPunctuation)
+            .matches("Café!", "cafe"));
+
+        let both = base
+            .with(NormalizationStep::StripPunctuation)
+            .with(NormalizationStep::Unaccent);
+        assert!(both.matches("Café!", "cafe"));
+        assert!(TextNormalizer::lenient().matches("  Café !  ", "cafe"));
+    }
+
+    #[test]
+    fn test_steps_run_in_declared_order() {
+        // Enabled in reverse; whitespace is still tidied after punctuation goes
+        let normalizer = TextNormalizer::new()
+            .with(NormalizationStep::Trim)
+            .with(NormalizationStep::CollapseWhitespace)
+            .with(NormalizationStep::StripPunctuation);
+        assert_eq!(normalizer.apply(" well - known ! "), "well known");
+        assert_eq!(
+            normalizer.steps().collect::<Vec<_>>(),
+            vec![
+                NormalizationStep::StripPunctuation,
+                NormalizationStep::CollapseWhitespace,
+                NormalizationStep::Trim,
+            ]
+        );
+        assert_eq!(TextNormalizer::new().apply(" As Is! "), " As Is! ");
+    }
+}
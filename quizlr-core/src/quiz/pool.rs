@@ -0,0 +1,143 @@
+//! Question pools for drawing randomized exams
+
+use super::question::Question;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Weight used for questions with no tag listed in the draw weights
+const DEFAULT_WEIGHT: f32 = 1.0;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuestionPool {
+    questions: Vec<Question>,
+}
+
+impl QuestionPool {
+    pub fn new(questions: Vec<Question>) -> Self {
+        Self { questions }
+    }
+
+    pub fn questions(&self) -> &[Question] {
+        &self.questions
+    }
+
+    pub fn len(&self) -> usize {
+        self.questions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.questions.is_empty()
+    }
+
+    /// Sampling weight of a question: the largest weight among its tags, or
+    /// the default weight if none of its tags are listed
+    fn weight_of(question: &Question, weights_by_tag: &HashMap<String, f32>) -> f32 {
+        question
+            .tags
+            .iter()
+            .filter_map(|tag| weights_by_tag.get(tag).copied())
+            .reduce(f32::max)
+            .unwrap_or(DEFAULT_WEIGHT)
+            .max(0.0)
+    }
+
+    /// Draw up to `n` distinct questions, with each question's chance of
+    /// being picked proportional to its tag weight
+    pub fn draw<R: Rng + ?Sized>(
+        &self,
+        n: usize,
+        weights_by_tag: &HashMap<String, f32>,
+        rng: &mut R,
+    ) -> Vec<&Question> {
+        let n = n.min(self.questions.len());
+        match self
+            .questions
+            .choose_multiple_weighted(rng, n, |q| Self::weight_of(q, weights_by_tag))
+        {
+            Ok(drawn) => drawn.collect(),
+            // Only fails on invalid weights, which `weight_of` rules out
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+impl From<Vec<Question>> for QuestionPool {
+    fn from(questions: Vec<Question>) -> Self {
+        Self::new(questions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quiz::QuestionType;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+    use uuid::Uuid;
+
+    fn tagged(tag: &str) -> Question {
+        let mut question = Question::new(
+            QuestionType::TrueFalse {
+                statement: format!("{} question", tag),
+                correct_answer: true,
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+        );
+        question.tags.push(tag.to_string());
+        question
+    }
+
+    fn pool() -> QuestionPool {
+        let mut questions: Vec<Question> = (0..10).map(|_| tagged("security")).collect();
+        questions.extend((0..10).map(|_| tagged("misc")));
+        QuestionPool::new(questions)
+    }
+
+    #[test]
+    fn test_draw_has_no_duplicates() {
+        let pool = pool();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let drawn = pool.draw(15, &HashMap::new(), &mut rng);
+        let ids: HashSet<Uuid> = drawn.iter().map(|q| q.id).collect();
+        assert_eq!(drawn.len(), 15);
+        assert_eq!(ids.len(), 15);
+
+        assert_eq!(pool.draw(50, &HashMap::new(), &mut rng).len(), 20);
+    }
+
+    #[test]
+    fn test_weighted_frequencies() {
+        let pool = pool();
+        let weights = HashMap::from([("security".to_string(), 3.0), ("misc".to_string(), 1.0)]);
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let trials = 10_000;
+        let security = (0..trials)
+            .filter(|_| {
+                let drawn = pool.draw(1, &weights, &mut rng);
+                drawn[0].tags.contains(&"security".to_string())
+            })
+            .count();
+
+        // Expect 30 / (30 + 10) = 75% security
+        let fraction = security as f32 / trials as f32;
+        assert!(
+            (fraction - 0.75).abs() < 0.03,
+            "security fraction {}",
+            fraction
+        );
+    }
+
+    #[test]
+    fn test_unlisted_tags_use_default_weight() {
+        let question = tagged("other");
+        let weights = HashMap::from([("security".to_string(), 5.0)]);
+        assert_eq!(QuestionPool::weight_of(&question, &weights), DEFAULT_WEIGHT);
+    }
+}
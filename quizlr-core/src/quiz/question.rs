@@ -1,4 +1,11 @@
+use super::grading::grade_custom;
+use super::normalize::{NormalizationStep, TextNormalizer};
+use crate::clock::{Clock, SystemClock};
+use crate::error::QuizlrError;
 use chrono::{DateTime, Utc};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -15,6 +22,8 @@ pub enum QuestionType {
         question: String,
         options: Vec<String>,
         correct_index: usize,
+        #[serde(default)]
+        alternate_correct: Vec<usize>, // Other options also accepted as correct
         explanation: Option<String>,
     },
     MultiSelect {
@@ -27,6 +36,8 @@ pub enum QuestionType {
         template: String, // Contains {} for blanks
         correct_answers: Vec<String>,
         case_sensitive: bool,
+        #[serde(default)]
+        per_blank_credit: bool, // Partial credit per correct blank
         explanation: Option<String>,
     },
     MatchPairs {
@@ -48,6 +59,17 @@ pub enum QuestionType {
         key_concepts: Vec<String>,
         min_word_count: usize,
     },
+    Essay {
+        prompt: String,
+        rubric: Vec<RubricCriterion>,
+        max_words: Option<usize>,
+    },
+    /// An application-defined type, graded by the grader registered for
+    /// `kind`
+    Custom {
+        kind: String,
+        data: serde_json::Value,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -57,6 +79,12 @@ pub struct FollowUpRule {
     pub weight: f32,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RubricCriterion {
+    pub description: String,
+    pub weight: f32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Question {
     pub id: Uuid,
@@ -66,6 +94,18 @@ pub struct Question {
     pub estimated_time_seconds: u32,
     pub tags: Vec<String>,
     pub citations: Vec<Citation>,
+    #[serde(default)]
+    pub media: Vec<MediaAsset>,
+    #[serde(default)]
+    pub group: Option<Uuid>, // Questions sharing context, e.g. a reading passage
+    #[serde(default)]
+    pub locale: Option<String>, // BCP-47 language tag, e.g. "es-MX"
+    #[serde(default)]
+    pub normalization: TextNormalizer, // Leniency for typed answers
+    #[serde(default)]
+    pub unlock_after: Option<Uuid>, // Hidden until this question is answered
+    #[serde(default)]
+    pub unlock_if_correct: bool, // ...and only if it was answered correctly
     pub metadata: HashMap<String, serde_json::Value>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -80,6 +120,32 @@ pub struct Citation {
     pub confidence: f32, // 0.0 to 1.0
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum MediaKind {
+    Image,
+    Audio,
+    Video,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MediaAsset {
+    pub id: Uuid,
+    pub kind: MediaKind,
+    pub uri: String, // Storage key or external URL
+    pub alt: Option<String>,
+}
+
+impl MediaAsset {
+    pub fn new(kind: MediaKind, uri: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            kind,
+            uri,
+            alt: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
 pub enum Answer {
@@ -96,11 +162,25 @@ pub enum Answer {
         explanation: String,
         time_taken_seconds: u32,
     },
+    Essay(String),
+    Custom(serde_json::Value), // Interpreted by the custom type's grader
+    DontKnow,                  // Honest "I don't know", graded as incorrect
 }
 
 impl Question {
     pub fn new(question_type: QuestionType, topic_id: Uuid, difficulty: f32) -> Self {
-        let now = Utc::now();
+        Self::with_clock(question_type, topic_id, difficulty, &SystemClock)
+    }
+
+    /// Create a question stamped with the time from `clock` instead of the
+    /// system clock
+    pub fn with_clock(
+        question_type: QuestionType,
+        topic_id: Uuid,
+        difficulty: f32,
+        clock: &dyn Clock,
+    ) -> Self {
+        let now = clock.now();
         Self {
             id: Uuid::new_v4(),
             question_type,
@@ -109,6 +189,12 @@ impl Question {
             estimated_time_seconds: 60, // Default 1 minute
             tags: Vec::new(),
             citations: Vec::new(),
+            media: Vec::new(),
+            group: None,
+            locale: None,
+            normalization: TextNormalizer::default(),
+            unlock_after: None,
+            unlock_if_correct: false,
             metadata: HashMap::new(),
             created_at: now,
             updated_at: now,
@@ -117,12 +203,14 @@ impl Question {
 
     pub fn validate_answer(&self, answer: &Answer) -> Result<bool, String> {
         match (&self.question_type, answer) {
+            (_, Answer::DontKnow) => Ok(false),
             (QuestionType::TrueFalse { correct_answer, .. }, Answer::TrueFalse(user_answer)) => {
                 Ok(correct_answer == user_answer)
             }
             (
                 QuestionType::MultipleChoice {
                     correct_index,
+                    alternate_correct,
                     options,
                     ..
                 },
@@ -131,7 +219,7 @@ impl Question {
                 if *user_index >= options.len() {
                     Err("Invalid option index".to_string())
                 } else {
-                    Ok(correct_index == user_index)
+                    Ok(correct_index == user_index || alternate_correct.contains(user_index))
                 }
             }
             (
@@ -163,31 +251,206 @@ impl Question {
                 if user_answers.len() != correct_answers.len() {
                     Err("Wrong number of answers".to_string())
                 } else {
-                    let all_correct =
-                        user_answers
-                            .iter()
-                            .zip(correct_answers.iter())
-                            .all(|(user, correct)| {
-                                if *case_sensitive {
-                                    user == correct
-                                } else {
-                                    user.to_lowercase() == correct.to_lowercase()
-                                }
-                            });
+                    let all_correct = user_answers
+                        .iter()
+                        .zip(correct_answers.iter())
+                        .all(|(user, correct)| self.text_matches(user, correct, *case_sensitive));
                     Ok(all_correct)
                 }
             }
-            (QuestionType::MatchPairs { correct_pairs, .. }, Answer::MatchPairs(user_pairs)) => {
+            (
+                QuestionType::MatchPairs {
+                    correct_pairs,
+                    left_items,
+                    right_items,
+                    ..
+                },
+                Answer::MatchPairs(user_pairs),
+            ) => {
+                if !pairs_in_range(user_pairs, left_items.len(), right_items.len()) {
+                    return Err("pair index out of range".to_string());
+                }
                 let mut user_sorted = user_pairs.clone();
                 let mut correct_sorted = correct_pairs.clone();
                 user_sorted.sort();
                 correct_sorted.sort();
                 Ok(user_sorted == correct_sorted)
             }
+            (QuestionType::Essay { .. }, Answer::Essay(_)) => {
+                Err("Essay answers must be graded against the rubric by an LLM".to_string())
+            }
+            (QuestionType::Custom { kind, data }, _) => grade_custom(kind, data, answer)
+                .map(|credit| credit >= 1.0)
+                .map_err(|e| e.to_string()),
             _ => Err("Answer type does not match question type".to_string()),
         }
     }
 
+    /// Credit from 0.0 to 1.0 for an answer. Match pairs, and fill in the
+    /// blank questions with `per_blank_credit`, earn the fraction they got
+    /// right; everything else is all or nothing.
+    pub fn partial_credit(&self, answer: &Answer) -> crate::Result<f32> {
+        match (&self.question_type, answer) {
+            (_, Answer::DontKnow) => Ok(0.0),
+            (QuestionType::MatchPairs { .. }, _) => self.match_pairs_credit(answer),
+            (QuestionType::Custom { kind, data }, _) => grade_custom(kind, data, answer),
+            (
+                QuestionType::FillInTheBlank {
+                    correct_answers,
+                    case_sensitive,
+                    per_blank_credit: true,
+                    ..
+                },
+                Answer::FillInTheBlank(user_answers),
+            ) => {
+                if user_answers.len() != correct_answers.len() {
+                    return Err(QuizlrError::InvalidInput(
+                        "Wrong number of answers".to_string(),
+                    ));
+                }
+                if correct_answers.is_empty() {
+                    return Ok(1.0);
+                }
+                let matched = user_answers
+                    .iter()
+                    .zip(correct_answers)
+                    .filter(|(user, correct)| self.text_matches(user, correct, *case_sensitive))
+                    .count();
+                Ok(matched as f32 / correct_answers.len() as f32)
+            }
+            _ => match self.validate_answer(answer) {
+                Ok(correct) => Ok(if correct { 1.0 } else { 0.0 }),
+                Err(e) => Err(QuizlrError::InvalidInput(e)),
+            },
+        }
+    }
+
+    /// Compare a typed answer with the key under the question's
+    /// normalization, lowercasing too unless `case_sensitive`
+    fn text_matches(&self, user: &str, correct: &str, case_sensitive: bool) -> bool {
+        if case_sensitive {
+            self.normalization.matches(user, correct)
+        } else {
+            self.normalization
+                .clone()
+                .with(NormalizationStep::Lowercase)
+                .matches(user, correct)
+        }
+    }
+
+    /// Fraction of the correct pairs the user matched, for partial credit.
+    /// Pairs beyond the number of correct pairs count against the total,
+    /// so submitting every possible pairing doesn't earn full credit.
+    pub fn match_pairs_credit(&self, answer: &Answer) -> crate::Result<f32> {
+        let (
+            QuestionType::MatchPairs {
+                left_items,
+                right_items,
+                correct_pairs,
+                ..
+            },
+            Answer::MatchPairs(user_pairs),
+        ) = (&self.question_type, answer)
+        else {
+            return Err(QuizlrError::InvalidInput(
+                "Match pairs credit needs a match pairs question and answer".to_string(),
+            ));
+        };
+        if !pairs_in_range(user_pairs, left_items.len(), right_items.len()) {
+            return Err(QuizlrError::InvalidInput(
+                "pair index out of range".to_string(),
+            ));
+        }
+
+        let mut submitted = user_pairs.clone();
+        submitted.sort();
+        submitted.dedup();
+        let matched = submitted
+            .iter()
+            .filter(|p| correct_pairs.contains(p))
+            .count();
+        let total = correct_pairs.len().max(submitted.len());
+
+        Ok(if total > 0 {
+            matched as f32 / total as f32
+        } else {
+            1.0
+        })
+    }
+
+    /// Check the author-supplied answer key for internal consistency, e.g.
+    /// that correct indices point at real options.
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.question_type {
+            QuestionType::MultipleChoice {
+                options,
+                correct_index,
+                alternate_correct,
+                ..
+            } if *correct_index >= options.len()
+                || alternate_correct.iter().any(|&idx| idx >= options.len()) =>
+            {
+                Err("correct index out of range".to_string())
+            }
+            QuestionType::MultiSelect {
+                options,
+                correct_indices,
+                ..
+            } if correct_indices.iter().any(|&idx| idx >= options.len()) => {
+                Err("correct index out of range".to_string())
+            }
+            QuestionType::MatchPairs {
+                left_items,
+                right_items,
+                correct_pairs,
+                ..
+            } if !pairs_in_range(correct_pairs, left_items.len(), right_items.len()) => {
+                Err("pair index out of range".to_string())
+            }
+            QuestionType::FillInTheBlank {
+                template,
+                correct_answers,
+                ..
+            } if correct_answers.is_empty()
+                || template.matches("{}").count() != correct_answers.len() =>
+            {
+                Err(format!(
+                    "template has {} blanks but {} answers",
+                    template.matches("{}").count(),
+                    correct_answers.len()
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Stable hash of the question's content, ignoring ids, timestamps,
+    /// tags and metadata. Display text is compared case-insensitively with
+    /// whitespace collapsed, so prompts and options that differ only in
+    /// spacing or capitalization hash equally. Case-sensitive answer keys
+    /// and custom data are hashed as written. The value is the same across
+    /// runs and platforms, so it can key caches and dedupe question banks.
+    pub fn content_hash(&self) -> u64 {
+        // Struct fields are emitted in declaration order and custom data
+        // objects have sorted keys, so this serialization is deterministic
+        // and can't fail
+        let content = serde_json::to_vec(&normalized_display_text(&self.question_type))
+            .expect("question types always serialize to JSON");
+        stable_hash(&content)
+    }
+
+    pub fn media_of_kind(&self, kind: MediaKind) -> impl Iterator<Item = &MediaAsset> {
+        self.media.iter().filter(move |m| m.kind == kind)
+    }
+
+    /// Sum of rubric criterion weights, or 0.0 for non-essay questions
+    pub fn rubric_weight_total(&self) -> f32 {
+        match &self.question_type {
+            QuestionType::Essay { rubric, .. } => rubric.iter().map(|c| c.weight).sum(),
+            _ => 0.0,
+        }
+    }
+
     pub fn get_explanation(&self) -> Option<&str> {
         match &self.question_type {
             QuestionType::TrueFalse { explanation, .. }
@@ -198,6 +461,300 @@ impl Question {
             _ => None,
         }
     }
+
+    /// The answer key as an [`Answer`], for showing "the correct answer
+    /// was ..." feedback. `None` for free-text types graded by judgement.
+    pub fn correct_answer(&self) -> Option<Answer> {
+        match &self.question_type {
+            QuestionType::TrueFalse { correct_answer, .. } => {
+                Some(Answer::TrueFalse(*correct_answer))
+            }
+            QuestionType::MultipleChoice { correct_index, .. } => {
+                Some(Answer::MultipleChoice(*correct_index))
+            }
+            QuestionType::MultiSelect {
+                correct_indices, ..
+            } => Some(Answer::MultiSelect(correct_indices.clone())),
+            QuestionType::FillInTheBlank {
+                correct_answers, ..
+            } => Some(Answer::FillInTheBlank(correct_answers.clone())),
+            QuestionType::MatchPairs { correct_pairs, .. } => {
+                Some(Answer::MatchPairs(correct_pairs.clone()))
+            }
+            QuestionType::InteractiveInterview { .. }
+            | QuestionType::TopicExplanation { .. }
+            | QuestionType::Essay { .. }
+            | QuestionType::Custom { .. } => None,
+        }
+    }
+
+    /// Text of every answer accepted as correct, for question types whose
+    /// answers are plain text
+    pub fn correct_texts(&self) -> Vec<&str> {
+        match &self.question_type {
+            QuestionType::MultipleChoice {
+                options,
+                correct_index,
+                alternate_correct,
+                ..
+            } => std::iter::once(correct_index)
+                .chain(alternate_correct)
+                .filter_map(|&i| options.get(i).map(String::as_str))
+                .collect(),
+            QuestionType::MultiSelect {
+                options,
+                correct_indices,
+                ..
+            } => correct_indices
+                .iter()
+                .filter_map(|&i| options.get(i).map(String::as_str))
+                .collect(),
+            QuestionType::FillInTheBlank {
+                correct_answers, ..
+            } => correct_answers.iter().map(String::as_str).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Turn the question into a `MultipleChoice` with `count` distractors
+    /// drawn from `pool`. Works on multiple choice questions and on
+    /// single-blank fill-in questions; the pool entries matching any of the
+    /// question's correct answers are never used.
+    pub fn fill_options_from_pool<R: Rng + ?Sized>(
+        &mut self,
+        pool: &[String],
+        count: usize,
+        rng: &mut R,
+    ) -> crate::Result<()> {
+        let (prompt, correct, explanation) = match &self.question_type {
+            QuestionType::MultipleChoice {
+                question,
+                options,
+                correct_index,
+                explanation,
+                ..
+            } => (
+                question.clone(),
+                options.get(*correct_index).cloned(),
+                explanation.clone(),
+            ),
+            QuestionType::FillInTheBlank {
+                template,
+                correct_answers,
+                explanation,
+                ..
+            } if correct_answers.len() == 1 => (
+                template.replace("{}", "___"),
+                correct_answers.first().cloned(),
+                explanation.clone(),
+            ),
+            _ => {
+                return Err(QuizlrError::InvalidInput(
+                    "Only multiple choice and single-blank questions take pooled options"
+                        .to_string(),
+                ))
+            }
+        };
+        let correct = correct.ok_or_else(|| {
+            QuizlrError::InvalidInput("Correct option index out of range".to_string())
+        })?;
+
+        let excluded: Vec<String> = self
+            .correct_texts()
+            .iter()
+            .map(|t| t.trim().to_lowercase())
+            .collect();
+        let candidates: Vec<&String> = pool
+            .iter()
+            .filter(|p| !excluded.contains(&p.trim().to_lowercase()))
+            .collect();
+
+        let mut options: Vec<String> = candidates
+            .choose_multiple(rng, count)
+            .map(|s| (*s).clone())
+            .collect();
+        options.push(correct.clone());
+        options.shuffle(rng);
+        let correct_index = options.iter().position(|o| *o == correct).unwrap_or(0);
+
+        self.question_type = QuestionType::MultipleChoice {
+            question: prompt,
+            options,
+            correct_index,
+            alternate_correct: Vec::new(),
+            explanation,
+        };
+        Ok(())
+    }
+
+    /// The explanation to show as feedback. Falls back to the most
+    /// confident citation with an excerpt when the author wrote none.
+    pub fn effective_explanation(&self) -> Option<String> {
+        if let Some(explanation) = self.get_explanation() {
+            return Some(explanation.to_string());
+        }
+
+        self.citations
+            .iter()
+            .filter(|c| c.excerpt.is_some())
+            .max_by(|a, b| a.confidence.total_cmp(&b.confidence))
+            .and_then(|c| {
+                c.excerpt
+                    .as_ref()
+                    .map(|excerpt| format!("{} ({})", excerpt, c.source))
+            })
+    }
+
+    /// Read a metadata value as `T`. `Ok(None)` if the key is absent.
+    pub fn get_meta<T: DeserializeOwned>(&self, key: &str) -> crate::Result<Option<T>> {
+        get_meta(&self.metadata, key)
+    }
+
+    pub fn set_meta<T: Serialize>(&mut self, key: &str, value: T) -> crate::Result<()> {
+        set_meta(&mut self.metadata, key, value)
+    }
+}
+
+pub(crate) fn get_meta<T: DeserializeOwned>(
+    metadata: &HashMap<String, serde_json::Value>,
+    key: &str,
+) -> crate::Result<Option<T>> {
+    metadata
+        .get(key)
+        .map(|value| T::deserialize(value).map_err(QuizlrError::from))
+        .transpose()
+}
+
+pub(crate) fn set_meta<T: Serialize>(
+    metadata: &mut HashMap<String, serde_json::Value>,
+    key: &str,
+    value: T,
+) -> crate::Result<()> {
+    metadata.insert(key.to_string(), serde_json::to_value(value)?);
+    Ok(())
+}
+
+/// Trim, lowercase and collapse spaces, for comparing display text
+fn content_normalizer() -> TextNormalizer {
+    TextNormalizer::new()
+        .with(NormalizationStep::Lowercase)
+        .with(NormalizationStep::CollapseWhitespace)
+        .with(NormalizationStep::Trim)
+}
+
+/// A copy of `question_type` with its display text normalized. Answer keys
+/// graded case-sensitively and custom data are left as they are, since
+/// changing them changes how the question grades.
+fn normalized_display_text(question_type: &QuestionType) -> QuestionType {
+    let normalizer = content_normalizer();
+    let text = |text: &mut String| *text = normalizer.apply(text);
+    let all = |texts: &mut Vec<String>| texts.iter_mut().for_each(text);
+    let explanation = |explanation: &mut Option<String>| {
+        if let Some(explanation) = explanation {
+            text(explanation);
+        }
+    };
+
+    let mut normalized = question_type.clone();
+    match &mut normalized {
+        QuestionType::TrueFalse {
+            statement,
+            explanation: note,
+            ..
+        } => {
+            text(statement);
+            explanation(note);
+        }
+        QuestionType::MultipleChoice {
+            question,
+            options,
+            explanation: note,
+            ..
+        }
+        | QuestionType::MultiSelect {
+            question,
+            options,
+            explanation: note,
+            ..
+        } => {
+            text(question);
+            all(options);
+            explanation(note);
+        }
+        QuestionType::FillInTheBlank {
+            template,
+            correct_answers,
+            case_sensitive,
+            explanation: note,
+            ..
+        } => {
+            text(template);
+            if !*case_sensitive {
+                all(correct_answers);
+            }
+            explanation(note);
+        }
+        QuestionType::MatchPairs {
+            instruction,
+            left_items,
+            right_items,
+            explanation: note,
+            ..
+        } => {
+            text(instruction);
+            all(left_items);
+            all(right_items);
+            explanation(note);
+        }
+        QuestionType::InteractiveInterview {
+            topic,
+            initial_question,
+            follow_up_rules,
+            ..
+        } => {
+            text(topic);
+            text(initial_question);
+            for rule in follow_up_rules {
+                text(&mut rule.condition);
+                text(&mut rule.follow_up_question);
+            }
+        }
+        QuestionType::TopicExplanation {
+            topic,
+            prompt,
+            key_concepts,
+            ..
+        } => {
+            text(topic);
+            text(prompt);
+            all(key_concepts);
+        }
+        QuestionType::Essay { prompt, rubric, .. } => {
+            text(prompt);
+            for criterion in rubric {
+                text(&mut criterion.description);
+            }
+        }
+        QuestionType::Custom { .. } => {}
+    }
+    normalized
+}
+
+/// 64-bit FNV-1a. Unlike the std `DefaultHasher`, the output is fixed
+/// across processes and Rust versions.
+pub(crate) fn stable_hash(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+fn pairs_in_range(pairs: &[(usize, usize)], left_len: usize, right_len: usize) -> bool {
+    pairs
+        .iter()
+        .all(|&(left, right)| left < left_len && right < right_len)
 }
 
 #[cfg(test)]
@@ -227,6 +784,7 @@ mod tests {
                 question: "What is 2+2?".to_string(),
                 options: vec!["3".to_string(), "4".to_string(), "5".to_string()],
                 correct_index: 1,
+                alternate_correct: Vec::new(),
                 explanation: None,
             },
             Uuid::new_v4(),
@@ -3,7 +3,10 @@
 //! DEVNOTES: Testing all question types and edge cases to ensure
 //! proper validation and behavior across the quiz engine
 
-use crate::quiz::question::{Answer, Citation, FollowUpRule, Question, QuestionType};
+use crate::quiz::normalize::{NormalizationStep, TextNormalizer};
+use crate::quiz::question::{
+    Answer, Citation, FollowUpRule, MediaAsset, MediaKind, Question, QuestionType, RubricCriterion,
+};
 use uuid::Uuid;
 
 #[cfg(test)]
@@ -35,6 +38,7 @@ mod question_type_tests {
                 question: "Which is a Rust keyword?".to_string(),
                 options: vec!["var".to_string(), "let".to_string(), "const".to_string()],
                 correct_index: 1,
+                alternate_correct: Vec::new(),
                 explanation: None,
             },
             Uuid::new_v4(),
@@ -96,6 +100,7 @@ mod question_type_tests {
                 template: "The {} macro is used for printing in Rust".to_string(),
                 correct_answers: vec!["println!".to_string()],
                 case_sensitive: true,
+                per_blank_credit: false,
                 explanation: None,
             },
             Uuid::new_v4(),
@@ -115,6 +120,7 @@ mod question_type_tests {
                 template: "The {} keyword declares a variable".to_string(),
                 correct_answers: vec!["let".to_string()],
                 case_sensitive: false,
+                per_blank_credit: false,
                 explanation: None,
             },
             Uuid::new_v4(),
@@ -140,6 +146,7 @@ mod question_type_tests {
                 template: "{} is to Rust as {} is to JavaScript".to_string(),
                 correct_answers: vec!["cargo".to_string(), "npm".to_string()],
                 case_sensitive: false,
+                per_blank_credit: false,
                 explanation: None,
             },
             Uuid::new_v4(),
@@ -350,4 +357,593 @@ mod question_type_tests {
             panic!("Wrong question type");
         }
     }
+
+    fn create_essay_question() -> Question {
+        Question::new(
+            QuestionType::Essay {
+                prompt: "Discuss the trade-offs of Rust's ownership model".to_string(),
+                rubric: vec![
+                    RubricCriterion {
+                        description: "Explains ownership and borrowing".to_string(),
+                        weight: 0.5,
+                    },
+                    RubricCriterion {
+                        description: "Discusses ergonomics costs".to_string(),
+                        weight: 0.3,
+                    },
+                    RubricCriterion {
+                        description: "Uses concrete examples".to_string(),
+                        weight: 0.2,
+                    },
+                ],
+                max_words: Some(500),
+            },
+            Uuid::new_v4(),
+            0.8,
+        )
+    }
+
+    #[test]
+    fn test_essay_type() {
+        let question = create_essay_question();
+
+        if let QuestionType::Essay {
+            rubric, max_words, ..
+        } = &question.question_type
+        {
+            assert_eq!(rubric.len(), 3);
+            assert_eq!(*max_words, Some(500));
+        } else {
+            panic!("Wrong question type");
+        }
+
+        // Essays can't be auto-validated
+        let result = question.validate_answer(&Answer::Essay("Ownership is...".to_string()));
+        assert!(result.is_err());
+        assert!(question.get_explanation().is_none());
+    }
+
+    #[test]
+    fn test_rubric_weight_total() {
+        let question = create_essay_question();
+        assert!((question.rubric_weight_total() - 1.0).abs() < 1e-6);
+
+        let true_false = Question::new(
+            QuestionType::TrueFalse {
+                statement: "Test".to_string(),
+                correct_answer: true,
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+        );
+        assert_eq!(true_false.rubric_weight_total(), 0.0);
+    }
+
+    #[test]
+    fn test_media_attachments_round_trip() {
+        let mut question = Question::new(
+            QuestionType::TrueFalse {
+                statement: "This is a picture of a crab".to_string(),
+                correct_answer: true,
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.2,
+        );
+        let mut image = MediaAsset::new(MediaKind::Image, "media/ferris.png".to_string());
+        image.alt = Some("Ferris the crab".to_string());
+        question.media.push(image.clone());
+        question.media.push(MediaAsset::new(
+            MediaKind::Audio,
+            "https://example.com/crab.ogg".to_string(),
+        ));
+
+        let json = serde_json::to_string(&question).unwrap();
+        let restored: Question = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.media, question.media);
+        let images: Vec<&MediaAsset> = restored.media_of_kind(MediaKind::Image).collect();
+        assert_eq!(images, vec![&image]);
+        assert_eq!(restored.media_of_kind(MediaKind::Video).count(), 0);
+    }
+
+    #[test]
+    fn test_media_defaults_when_missing() {
+        let question = Question::new(
+            QuestionType::TrueFalse {
+                statement: "Test".to_string(),
+                correct_answer: true,
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.2,
+        );
+        let mut value = serde_json::to_value(&question).unwrap();
+        value.as_object_mut().unwrap().remove("media");
+
+        let restored: Question = serde_json::from_value(value).unwrap();
+        assert!(restored.media.is_empty());
+    }
+
+    fn create_match_pairs_question(correct_pairs: Vec<(usize, usize)>) -> Question {
+        Question::new(
+            QuestionType::MatchPairs {
+                instruction: "Match".to_string(),
+                left_items: vec!["a".to_string(), "b".to_string()],
+                right_items: vec!["1".to_string(), "2".to_string()],
+                correct_pairs,
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+        )
+    }
+
+    #[test]
+    fn test_match_pairs_out_of_range_user_pair() {
+        let question = create_match_pairs_question(vec![(0, 0), (1, 1)]);
+
+        let result = question.validate_answer(&Answer::MatchPairs(vec![(0, 0), (1, 5)]));
+        assert_eq!(result.unwrap_err(), "pair index out of range");
+
+        let result = question.validate_answer(&Answer::MatchPairs(vec![(2, 0), (1, 1)]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_question_validate_answer_key() {
+        assert!(create_match_pairs_question(vec![(0, 1), (1, 0)])
+            .validate()
+            .is_ok());
+        assert_eq!(
+            create_match_pairs_question(vec![(0, 1), (3, 0)])
+                .validate()
+                .unwrap_err(),
+            "pair index out of range"
+        );
+
+        let multiple_choice = Question::new(
+            QuestionType::MultipleChoice {
+                question: "Pick one".to_string(),
+                options: vec!["a".to_string(), "b".to_string()],
+                correct_index: 2,
+                alternate_correct: Vec::new(),
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+        );
+        assert!(multiple_choice.validate().is_err());
+
+        let fill_in = |template: &str, answers: &[&str]| {
+            Question::new(
+                QuestionType::FillInTheBlank {
+                    template: template.to_string(),
+                    correct_answers: answers.iter().map(|a| a.to_string()).collect(),
+                    case_sensitive: false,
+                    per_blank_credit: false,
+                    explanation: None,
+                },
+                Uuid::new_v4(),
+                0.5,
+            )
+        };
+        assert!(fill_in("{} and {}", &["a", "b"]).validate().is_ok());
+        assert_eq!(
+            fill_in("{} and {}", &["a"]).validate().unwrap_err(),
+            "template has 2 blanks but 1 answers"
+        );
+        assert!(fill_in("No blanks", &[]).validate().is_err());
+    }
+
+    #[test]
+    fn test_multiple_choice_alternate_correct() {
+        let question = Question::new(
+            QuestionType::MultipleChoice {
+                question: "Which keyword declares an immutable binding?".to_string(),
+                options: vec![
+                    "let".to_string(),
+                    "const".to_string(),
+                    "static".to_string(),
+                    "mut".to_string(),
+                ],
+                correct_index: 0,
+                alternate_correct: vec![1],
+                explanation: Some("Both let and const bind immutably".to_string()),
+            },
+            Uuid::new_v4(),
+            0.3,
+        );
+
+        assert!(question
+            .validate_answer(&Answer::MultipleChoice(0))
+            .unwrap());
+        assert!(question
+            .validate_answer(&Answer::MultipleChoice(1))
+            .unwrap());
+        assert!(!question
+            .validate_answer(&Answer::MultipleChoice(3))
+            .unwrap());
+        assert_eq!(
+            question.get_explanation(),
+            Some("Both let and const bind immutably")
+        );
+        assert!(question.validate().is_ok());
+    }
+
+    #[test]
+    fn test_multiple_choice_alternate_defaults_empty() {
+        let json = r#"{"type":"MultipleChoice","data":{"question":"Q","options":["a","b"],"correct_index":1,"explanation":null}}"#;
+        let question_type: QuestionType = serde_json::from_str(json).unwrap();
+
+        if let QuestionType::MultipleChoice {
+            alternate_correct, ..
+        } = question_type
+        {
+            assert!(alternate_correct.is_empty());
+        } else {
+            panic!("Wrong question type");
+        }
+    }
+
+    #[test]
+    fn test_content_hash_ignores_identity() {
+        let make = || {
+            Question::new(
+                QuestionType::TrueFalse {
+                    statement: "Rust has a garbage collector".to_string(),
+                    correct_answer: false,
+                    explanation: None,
+                },
+                Uuid::new_v4(),
+                0.2,
+            )
+        };
+        let a = make();
+        let mut b = make();
+        b.tags.push("gc".to_string());
+        b.metadata
+            .insert("source".to_string(), serde_json::json!("import"));
+
+        assert_ne!(a.id, b.id);
+        assert_eq!(a.content_hash(), b.content_hash());
+    }
+
+    #[test]
+    fn test_content_hash_differs_for_different_content() {
+        let base = |statement: &str, correct_answer: bool| {
+            Question::new(
+                QuestionType::TrueFalse {
+                    statement: statement.to_string(),
+                    correct_answer,
+                    explanation: None,
+                },
+                Uuid::new_v4(),
+                0.2,
+            )
+        };
+
+        let original = base("Rust has a garbage collector", false);
+        assert_ne!(
+            original.content_hash(),
+            base("Rust has a borrow checker", false).content_hash()
+        );
+        assert_ne!(
+            original.content_hash(),
+            base("Rust has a garbage collector", true).content_hash()
+        );
+    }
+
+    #[test]
+    fn test_content_hash_ignores_case_and_whitespace() {
+        let choice = |question: &str, options: [&str; 2]| {
+            Question::new(
+                QuestionType::MultipleChoice {
+                    question: question.to_string(),
+                    options: options.iter().map(|o| o.to_string()).collect(),
+                    correct_index: 0,
+                    alternate_correct: vec![],
+                    explanation: None,
+                },
+                Uuid::new_v4(),
+                0.2,
+            )
+        };
+
+        let original = choice("Which keyword declares a constant?", ["const", "let mut"]);
+        let respaced = choice(
+            "  which keyword  declares a\tconstant? ",
+            ["CONST", "let   mut "],
+        );
+        assert_eq!(original.content_hash(), respaced.content_hash());
+        assert_ne!(
+            original.content_hash(),
+            choice("Which keyword declares a constant?", ["const", "let"]).content_hash()
+        );
+    }
+
+    #[test]
+    fn test_content_hash_keeps_graded_text_as_written() {
+        let blank = |answer: &str, case_sensitive: bool| {
+            Question::new(
+                QuestionType::FillInTheBlank {
+                    template: "The formula for table salt is {}".to_string(),
+                    correct_answers: vec![answer.to_string()],
+                    case_sensitive,
+                    per_blank_credit: false,
+                    explanation: None,
+                },
+                Uuid::new_v4(),
+                0.2,
+            )
+        };
+        assert_ne!(
+            blank("NaCl", true).content_hash(),
+            blank("nacl", true).content_hash()
+        );
+        assert_eq!(
+            blank("NaCl", false).content_hash(),
+            blank("nacl", false).content_hash()
+        );
+
+        let custom = |key: &str| {
+            Question::new(
+                QuestionType::Custom {
+                    kind: "chemistry".to_string(),
+                    data: serde_json::json!({ key: "NaCl" }),
+                },
+                Uuid::new_v4(),
+                0.2,
+            )
+        };
+        assert_ne!(
+            custom("Formula").content_hash(),
+            custom("formula").content_hash()
+        );
+    }
+
+    #[test]
+    fn test_stable_hash_known_value() {
+        // FNV-1a reference vectors
+        assert_eq!(crate::quiz::question::stable_hash(b""), 0xcbf29ce484222325);
+        assert_eq!(crate::quiz::question::stable_hash(b"a"), 0xaf63dc4c8601ec8c);
+    }
+
+    fn citation(source: &str, excerpt: Option<&str>, confidence: f32) -> Citation {
+        Citation {
+            id: Uuid::new_v4(),
+            source: source.to_string(),
+            url: None,
+            excerpt: excerpt.map(str::to_string),
+            confidence,
+        }
+    }
+
+    fn true_false(explanation: Option<&str>) -> Question {
+        Question::new(
+            QuestionType::TrueFalse {
+                statement: "Rust has a garbage collector".to_string(),
+                correct_answer: false,
+                explanation: explanation.map(str::to_string),
+            },
+            Uuid::new_v4(),
+            0.3,
+        )
+    }
+
+    #[test]
+    fn test_effective_explanation_prefers_explicit() {
+        let mut question = true_false(Some("Rust uses ownership"));
+        question
+            .citations
+            .push(citation("The Book", Some("Ownership rules"), 0.9));
+
+        assert_eq!(
+            question.effective_explanation().as_deref(),
+            Some("Rust uses ownership")
+        );
+    }
+
+    #[test]
+    fn test_effective_explanation_from_best_citation() {
+        let mut question = true_false(None);
+        question
+            .citations
+            .push(citation("Blog post", Some("Rust has no GC"), 0.4));
+        question.citations.push(citation("The Book", None, 1.0));
+        question.citations.push(citation(
+            "The Book",
+            Some("Memory is freed by ownership"),
+            0.9,
+        ));
+
+        assert_eq!(
+            question.effective_explanation().as_deref(),
+            Some("Memory is freed by ownership (The Book)")
+        );
+    }
+
+    #[test]
+    fn test_effective_explanation_none() {
+        let mut question = true_false(None);
+        assert!(question.effective_explanation().is_none());
+
+        question.citations.push(citation("The Book", None, 1.0));
+        assert!(question.effective_explanation().is_none());
+    }
+
+    #[test]
+    fn test_correct_answer_per_type() {
+        let topic = Uuid::new_v4();
+        let cases = vec![
+            (
+                QuestionType::TrueFalse {
+                    statement: "S".to_string(),
+                    correct_answer: false,
+                    explanation: None,
+                },
+                Some(Answer::TrueFalse(false)),
+            ),
+            (
+                QuestionType::MultipleChoice {
+                    question: "Q".to_string(),
+                    options: vec!["a".to_string(), "b".to_string()],
+                    correct_index: 1,
+                    alternate_correct: Vec::new(),
+                    explanation: None,
+                },
+                Some(Answer::MultipleChoice(1)),
+            ),
+            (
+                QuestionType::MultiSelect {
+                    question: "Q".to_string(),
+                    options: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                    correct_indices: vec![0, 2],
+                    explanation: None,
+                },
+                Some(Answer::MultiSelect(vec![0, 2])),
+            ),
+            (
+                QuestionType::FillInTheBlank {
+                    template: "{} and {}".to_string(),
+                    correct_answers: vec!["x".to_string(), "y".to_string()],
+                    case_sensitive: false,
+                    per_blank_credit: false,
+                    explanation: None,
+                },
+                Some(Answer::FillInTheBlank(vec![
+                    "x".to_string(),
+                    "y".to_string(),
+                ])),
+            ),
+            (
+                QuestionType::MatchPairs {
+                    instruction: "Match".to_string(),
+                    left_items: vec!["l".to_string()],
+                    right_items: vec!["r".to_string()],
+                    correct_pairs: vec![(0, 0)],
+                    explanation: None,
+                },
+                Some(Answer::MatchPairs(vec![(0, 0)])),
+            ),
+            (
+                QuestionType::Essay {
+                    prompt: "Discuss".to_string(),
+                    rubric: Vec::new(),
+                    max_words: None,
+                },
+                None,
+            ),
+        ];
+
+        for (question_type, expected) in cases {
+            let question = Question::new(question_type, topic, 0.5);
+            assert_eq!(
+                serde_json::to_value(question.correct_answer()).unwrap(),
+                serde_json::to_value(expected).unwrap()
+            );
+            if let Some(answer) = question.correct_answer() {
+                assert_eq!(question.validate_answer(&answer), Ok(true));
+            }
+        }
+    }
+
+    #[test]
+    fn test_match_pairs_credit() {
+        let question = create_match_pairs_question(vec![(0, 0), (1, 1)]);
+        let credit = |pairs: Vec<(usize, usize)>| {
+            question
+                .match_pairs_credit(&Answer::MatchPairs(pairs))
+                .unwrap()
+        };
+
+        assert_eq!(credit(vec![(1, 1), (0, 0)]), 1.0);
+        assert_eq!(credit(vec![(0, 0), (1, 0)]), 0.5);
+        assert_eq!(credit(vec![(0, 0)]), 0.5);
+        assert_eq!(credit(vec![(0, 1), (1, 0)]), 0.0);
+        // Extra guesses count against the total
+        assert_eq!(credit(vec![(0, 0), (1, 1), (0, 1), (1, 0)]), 0.5);
+
+        assert!(question
+            .match_pairs_credit(&Answer::MatchPairs(vec![(0, 7)]))
+            .is_err());
+        assert!(question
+            .match_pairs_credit(&Answer::TrueFalse(true))
+            .is_err());
+    }
+
+    #[test]
+    fn test_per_blank_partial_credit() {
+        let blanks = |per_blank_credit: bool| {
+            Question::new(
+                QuestionType::FillInTheBlank {
+                    template: "{} borrows, {} moves, {} clones".to_string(),
+                    correct_answers: vec!["&T".to_string(), "T".to_string(), "Clone".to_string()],
+                    case_sensitive: false,
+                    per_blank_credit,
+                    explanation: None,
+                },
+                Uuid::new_v4(),
+                0.5,
+            )
+        };
+        let answer = |blanks: [&str; 3]| {
+            Answer::FillInTheBlank(blanks.iter().map(|b| b.to_string()).collect())
+        };
+
+        let question = blanks(true);
+        assert_eq!(
+            question
+                .partial_credit(&answer(["&T", "t", "CLONE"]))
+                .unwrap(),
+            1.0
+        );
+        let credit = question
+            .partial_credit(&answer(["&T", "&mut T", "clone"]))
+            .unwrap();
+        assert!((credit - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(
+            question.partial_credit(&answer(["a", "b", "c"])).unwrap(),
+            0.0
+        );
+        assert!(question
+            .partial_credit(&Answer::FillInTheBlank(vec!["&T".to_string()]))
+            .is_err());
+
+        // Without the option a multi-blank answer is all or nothing
+        assert_eq!(
+            blanks(false)
+                .partial_credit(&answer(["&T", "&mut T", "clone"]))
+                .unwrap(),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_fill_in_blank_uses_question_normalization() {
+        let mut question = Question::new(
+            QuestionType::FillInTheBlank {
+                template: "In Paris, order a {} au lait".to_string(),
+                correct_answers: vec!["cafe".to_string()],
+                case_sensitive: false,
+                per_blank_credit: false,
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.3,
+        );
+        let answer = Answer::FillInTheBlank(vec!["Café!".to_string()]);
+        assert_eq!(question.validate_answer(&answer), Ok(false));
+
+        question.normalization = TextNormalizer::new().with(NormalizationStep::Unaccent);
+        assert_eq!(question.validate_answer(&answer), Ok(false));
+
+        question.normalization = question
+            .normalization
+            .clone()
+            .with(NormalizationStep::StripPunctuation);
+        assert_eq!(question.validate_answer(&answer), Ok(true));
+        assert_eq!(question.partial_credit(&answer).unwrap(), 1.0);
+    }
 }
@@ -1,9 +1,69 @@
-use super::question::Question;
+use super::filter::QuestionFilter;
+use super::question::{self, Question};
+use super::student::{QuizView, StudentQuiz};
+use crate::clock::{system_clock, Clock};
+use crate::error::QuizlrError;
 use chrono::{DateTime, Utc};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
 use uuid::Uuid;
 
+/// Difficulty `QuizBuilder::from_bank` aims for unless told otherwise
+const DEFAULT_TARGET_DIFFICULTY: f32 = 0.5;
+
+/// Spread of the difficulty weighting in bank selection; questions this far
+/// from the target are about 60% as likely to be drawn as exact matches
+const DIFFICULTY_SPREAD: f32 = 0.1;
+
+/// A structural problem with one question of a quiz
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuizValidationError {
+    pub question_index: usize,
+    pub question_id: Uuid,
+    pub message: String,
+}
+
+impl fmt::Display for QuizValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Question {}: {}", self.question_index, self.message)
+    }
+}
+
+/// What changed between two versions of a quiz, from [`Quiz::diff`].
+/// Question ids keep the order of the version they appear in, and
+/// settings are listed in a fixed order, so equal inputs always give
+/// equal diffs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuizDiff {
+    pub added: Vec<Uuid>,
+    pub removed: Vec<Uuid>,
+    pub modified: Vec<Uuid>, // Same id, different content hash
+    pub settings: Vec<SettingChange>,
+}
+
+impl QuizDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && self.settings.is_empty()
+    }
+}
+
+/// A quiz-level setting that differs between two versions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettingChange {
+    pub name: String,
+    pub old: serde_json::Value,
+    pub new: serde_json::Value,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Quiz {
     pub id: Uuid,
@@ -22,11 +82,19 @@ pub struct Quiz {
     pub metadata: HashMap<String, serde_json::Value>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    #[serde(skip, default = "system_clock")]
+    clock: Arc<dyn Clock>,
 }
 
 impl Quiz {
     pub fn new(title: String) -> Self {
-        let now = Utc::now();
+        Self::with_clock(title, system_clock())
+    }
+
+    /// Create a quiz that stamps its timestamps from `clock` instead of the
+    /// system clock
+    pub fn with_clock(title: String, clock: Arc<dyn Clock>) -> Self {
+        let now = clock.now();
         Self {
             id: Uuid::new_v4(),
             title,
@@ -44,6 +112,7 @@ impl Quiz {
             metadata: HashMap::new(),
             created_at: now,
             updated_at: now,
+            clock,
         }
     }
 
@@ -54,7 +123,7 @@ impl Quiz {
         self.questions.push(question);
         self.update_difficulty_range();
         self.update_estimated_duration();
-        self.updated_at = Utc::now();
+        self.updated_at = self.clock.now();
     }
 
     pub fn remove_question(&mut self, question_id: Uuid) -> Option<Question> {
@@ -62,13 +131,329 @@ impl Quiz {
             let removed = self.questions.remove(pos);
             self.update_difficulty_range();
             self.update_estimated_duration();
-            self.updated_at = Utc::now();
+            self.updated_at = self.clock.now();
             Some(removed)
         } else {
             None
         }
     }
 
+    /// Remove every question, returning them in their previous order so an
+    /// editor can undo the change. Derived fields are reset.
+    pub fn clear_questions(&mut self) -> Vec<Question> {
+        let removed = std::mem::take(&mut self.questions);
+        self.refresh_derived_fields();
+        removed
+    }
+
+    /// Swap in a new question list, recomputing topics, difficulty range
+    /// and duration from it
+    pub fn replace_questions(&mut self, questions: Vec<Question>) {
+        self.questions = questions;
+        self.refresh_derived_fields();
+    }
+
+    /// Make several question edits all-or-nothing. `edit` works on a copy
+    /// of the questions; the quiz only takes the result if `edit` succeeds
+    /// and every question still validates, after which derived fields are
+    /// recomputed. On any error the quiz is left exactly as it was.
+    pub fn edit_transaction<F>(&mut self, edit: F) -> crate::Result<()>
+    where
+        F: FnOnce(&mut QuizTxn) -> crate::Result<()>,
+    {
+        let mut txn = QuizTxn {
+            questions: self.questions.clone(),
+        };
+        edit(&mut txn)?;
+
+        let original = std::mem::replace(&mut self.questions, txn.questions);
+        if let Err(error) = self.validate() {
+            self.questions = original;
+            return Err(error);
+        }
+        self.refresh_derived_fields();
+        Ok(())
+    }
+
+    /// Every locale tagged on at least one question
+    pub fn available_locales(&self) -> BTreeSet<String> {
+        self.questions
+            .iter()
+            .filter_map(|q| q.locale.clone())
+            .collect()
+    }
+
+    /// Copy of the quiz keeping only the questions `filter` matches, e.g.
+    /// to take the quiz in one language
+    pub fn filtered(&self, filter: &QuestionFilter) -> Quiz {
+        let mut quiz = self.clone();
+        quiz.replace_questions(filter.apply(&self.questions).into_iter().cloned().collect());
+        quiz
+    }
+
+    /// Rebuild `topic_ids` from the questions' topics, in order of first
+    /// use. `remove_question` leaves `topic_ids` alone, since callers may
+    /// list topics no question covers yet on purpose; call this to drop
+    /// topics left behind by removals.
+    pub fn prune_topic_ids(&mut self) {
+        let mut topic_ids = Vec::new();
+        for question in &self.questions {
+            if !topic_ids.contains(&question.topic_id) {
+                topic_ids.push(question.topic_id);
+            }
+        }
+        self.topic_ids = topic_ids;
+    }
+
+    /// Topics in `topic_ids` that no question references
+    pub fn orphaned_topic_ids(&self) -> Vec<Uuid> {
+        self.topic_ids
+            .iter()
+            .filter(|id| !self.questions.iter().any(|q| q.topic_id == **id))
+            .copied()
+            .collect()
+    }
+
+    fn refresh_derived_fields(&mut self) {
+        self.prune_topic_ids();
+        self.update_difficulty_range();
+        self.update_estimated_duration();
+        self.updated_at = self.clock.now();
+    }
+
+    /// Read-only, answer-free snapshot for rendering
+    pub fn view(&self) -> QuizView {
+        QuizView::from(self)
+    }
+
+    /// Serialize a copy of the quiz safe to hand to students: correct
+    /// answers, explanations, citations and metadata are left out.
+    pub fn to_student_json(&self) -> crate::Result<String> {
+        Ok(serde_json::to_string(&StudentQuiz::from(self))?)
+    }
+
+    /// Count questions by difficulty across `buckets` equal-width bins over
+    /// [0.0, 1.0]. The top bin includes 1.0.
+    pub fn difficulty_histogram(&self, buckets: usize) -> crate::Result<Vec<usize>> {
+        if buckets == 0 {
+            return Err(QuizlrError::InvalidInput(
+                "Histogram needs at least one bucket".to_string(),
+            ));
+        }
+
+        let mut histogram = vec![0; buckets];
+        for question in &self.questions {
+            let difficulty = question.difficulty.clamp(0.0, 1.0);
+            let bucket = ((difficulty * buckets as f32) as usize).min(buckets - 1);
+            histogram[bucket] += 1;
+        }
+        Ok(histogram)
+    }
+
+    /// Lowest and highest question difficulty for each topic in the quiz,
+    /// for spotting sections that are much easier or harder than the rest
+    pub fn difficulty_range_by_topic(&self) -> HashMap<Uuid, (f32, f32)> {
+        let mut ranges: HashMap<Uuid, (f32, f32)> = HashMap::new();
+        for question in &self.questions {
+            let range = ranges
+                .entry(question.topic_id)
+                .or_insert((question.difficulty, question.difficulty));
+            range.0 = range.0.min(question.difficulty);
+            range.1 = range.1.max(question.difficulty);
+        }
+        ranges
+    }
+
+    /// Every distinct correct textual answer in the quiz, in question
+    /// order. Other questions' answers make topical distractors.
+    pub fn build_distractor_pool(&self) -> Vec<String> {
+        let mut pool: Vec<String> = Vec::new();
+        for text in self.questions.iter().flat_map(Question::correct_texts) {
+            if !pool.iter().any(|p| p == text) {
+                pool.push(text.to_string());
+            }
+        }
+        pool
+    }
+
+    /// Read a metadata value as `T`. `Ok(None)` if the key is absent.
+    pub fn get_meta<T: DeserializeOwned>(&self, key: &str) -> crate::Result<Option<T>> {
+        question::get_meta(&self.metadata, key)
+    }
+
+    pub fn set_meta<T: Serialize>(&mut self, key: &str, value: T) -> crate::Result<()> {
+        question::set_meta(&mut self.metadata, key, value)
+    }
+
+    /// Check every question's answer key, reporting the first problem found
+    pub fn validate(&self) -> crate::Result<()> {
+        match self.validation_errors().into_iter().next() {
+            Some(error) => Err(QuizlrError::InvalidInput(error.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Every question whose answer key or unlock dependency is invalid,
+    /// in quiz order
+    pub fn validation_errors(&self) -> Vec<QuizValidationError> {
+        self.questions
+            .iter()
+            .enumerate()
+            .filter_map(|(index, question)| {
+                question
+                    .validate()
+                    .err()
+                    .or_else(|| self.unlock_error(question))
+                    .map(|message| QuizValidationError {
+                        question_index: index,
+                        question_id: question.id,
+                        message,
+                    })
+            })
+            .collect()
+    }
+
+    /// Problem with the chain of questions that must be answered before
+    /// `question` unlocks: a link to a question not in the quiz, or a
+    /// chain that loops back on itself
+    fn unlock_error(&self, question: &Question) -> Option<String> {
+        let mut seen = HashSet::from([question.id]);
+        let mut next = question.unlock_after;
+        while let Some(id) = next {
+            if !seen.insert(id) {
+                return Some("unlock dependencies form a cycle".to_string());
+            }
+            next = match self.questions.iter().find(|q| q.id == id) {
+                Some(prerequisite) => prerequisite.unlock_after,
+                None => return Some(format!("unlocks after unknown question {}", id)),
+            };
+        }
+        None
+    }
+
+    /// Hex fingerprint of the quiz's content: title, description,
+    /// settings, tags and each question's content hash in order. Ids and
+    /// timestamps are ignored, so re-saving an unchanged quiz keeps the
+    /// same fingerprint.
+    pub fn fingerprint(&self) -> String {
+        let content = serde_json::json!({
+            "title": self.title,
+            "description": self.description,
+            "pass_threshold": self.pass_threshold,
+            "allow_skip": self.allow_skip,
+            "show_explanations": self.show_explanations,
+            "randomize_questions": self.randomize_questions,
+            "randomize_options": self.randomize_options,
+            "tags": self.tags,
+            "questions": self.questions.iter().map(Question::content_hash).collect::<Vec<_>>(),
+        });
+        // A JSON value with string keys and plain data can't fail to
+        // serialize
+        let bytes = serde_json::to_vec(&content).expect("JSON values always serialize");
+        format!("{:016x}", question::stable_hash(&bytes))
+    }
+
+    /// Changes from this version of the quiz to `other`. Questions are
+    /// matched by id and count as modified when their content hash
+    /// differs; settings are the ones covered by [`Quiz::fingerprint`].
+    pub fn diff(&self, other: &Quiz) -> QuizDiff {
+        let old: HashMap<Uuid, &Question> = self.questions.iter().map(|q| (q.id, q)).collect();
+        let new: HashSet<Uuid> = other.questions.iter().map(|q| q.id).collect();
+
+        let mut diff = QuizDiff {
+            removed: self
+                .questions
+                .iter()
+                .map(|q| q.id)
+                .filter(|id| !new.contains(id))
+                .collect(),
+            ..QuizDiff::default()
+        };
+        for question in &other.questions {
+            match old.get(&question.id) {
+                None => diff.added.push(question.id),
+                Some(previous) if previous.content_hash() != question.content_hash() => {
+                    diff.modified.push(question.id)
+                }
+                Some(_) => {}
+            }
+        }
+
+        diff.settings = self
+            .settings()
+            .into_iter()
+            .zip(other.settings())
+            .filter(|((_, old), (_, new))| old != new)
+            .map(|((name, old), (_, new))| SettingChange {
+                name: name.to_string(),
+                old,
+                new,
+            })
+            .collect();
+        diff
+    }
+
+    fn settings(&self) -> [(&'static str, serde_json::Value); 8] {
+        [
+            ("title", serde_json::json!(self.title)),
+            ("description", serde_json::json!(self.description)),
+            ("pass_threshold", serde_json::json!(self.pass_threshold)),
+            ("allow_skip", serde_json::json!(self.allow_skip)),
+            (
+                "show_explanations",
+                serde_json::json!(self.show_explanations),
+            ),
+            (
+                "randomize_questions",
+                serde_json::json!(self.randomize_questions),
+            ),
+            (
+                "randomize_options",
+                serde_json::json!(self.randomize_options),
+            ),
+            ("tags", serde_json::json!(self.tags)),
+        ]
+    }
+
+    /// Human-readable summary of every problem [`Quiz::validation_errors`]
+    /// finds, for printing from tools: a count per problem kind, then one
+    /// line per question. Reports "OK" with question and topic counts when
+    /// nothing is wrong.
+    pub fn validation_report(&self) -> String {
+        let problems: Vec<(usize, String)> = self
+            .validation_errors()
+            .into_iter()
+            .map(|error| (error.question_index, error.message))
+            .collect();
+
+        if problems.is_empty() {
+            return format!(
+                "OK: {} questions, {} topics",
+                self.questions.len(),
+                self.topic_ids.len()
+            );
+        }
+
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for (_, problem) in &problems {
+            *counts.entry(problem.as_str()).or_default() += 1;
+        }
+
+        let mut report = format!(
+            "{} problems in {} questions\n",
+            problems.len(),
+            self.questions.len()
+        );
+        for (problem, count) in &counts {
+            report.push_str(&format!("  {}: {}\n", problem, count));
+        }
+        report.push('\n');
+        for (index, problem) in &problems {
+            report.push_str(&format!("Question {}: {}\n", index, problem));
+        }
+        report
+    }
+
     fn update_difficulty_range(&mut self) {
         if self.questions.is_empty() {
             self.difficulty_range = (0.0, 1.0);
@@ -96,13 +481,45 @@ impl Quiz {
         self.estimated_duration_minutes = (total_seconds / 60).max(1);
     }
 
+    /// Estimated duration in minutes with each question's time stretched
+    /// by `1 + difficulty * difficulty_factor`, since hard questions tend
+    /// to run long. The stored `estimated_duration_minutes` stays the plain
+    /// sum.
+    pub fn estimated_duration_adjusted(&self, difficulty_factor: f32) -> u32 {
+        let total_seconds: f32 = self
+            .questions
+            .iter()
+            .map(|q| q.estimated_time_seconds as f32 * (1.0 + q.difficulty * difficulty_factor))
+            .sum();
+        ((total_seconds / 60.0) as u32).max(1)
+    }
+
+    /// Feedback explanation for a question, or `None` when the quiz hides
+    /// explanations or the question is not part of this quiz
+    pub fn explanation_for(&self, question_id: Uuid) -> Option<String> {
+        if !self.show_explanations {
+            return None;
+        }
+        self.questions
+            .iter()
+            .find(|q| q.id == question_id)
+            .and_then(Question::effective_explanation)
+    }
+
     pub fn get_questions_for_session(&self) -> Vec<Question> {
+        self.get_questions_for_session_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Same as [`Quiz::get_questions_for_session`] but draws randomness from
+    /// the given RNG, so a seeded RNG gives a reproducible order.
+    pub fn get_questions_for_session_with_rng<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+    ) -> Vec<Question> {
         let mut questions = self.questions.clone();
 
         if self.randomize_questions {
-            use rand::seq::SliceRandom;
-            let mut rng = rand::thread_rng();
-            questions.shuffle(&mut rng);
+            questions.shuffle(rng);
         }
 
         if self.randomize_options {
@@ -112,6 +529,34 @@ impl Quiz {
 
         questions
     }
+
+    /// Shuffle for a session without splitting up question groups: each
+    /// group moves as one unit and is shuffled internally, so its questions
+    /// stay contiguous. Ungrouped questions shuffle freely.
+    pub fn get_questions_grouped_shuffle(&self, seed: u64) -> Vec<Question> {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let mut units: Vec<Vec<Question>> = Vec::new();
+        let mut unit_of_group: HashMap<Uuid, usize> = HashMap::new();
+        for question in &self.questions {
+            match question.group {
+                Some(group) => {
+                    let unit = *unit_of_group.entry(group).or_insert_with(|| {
+                        units.push(Vec::new());
+                        units.len() - 1
+                    });
+                    units[unit].push(question.clone());
+                }
+                None => units.push(vec![question.clone()]),
+            }
+        }
+
+        units.shuffle(&mut rng);
+        for unit in &mut units {
+            unit.shuffle(&mut rng);
+        }
+        units.into_iter().flatten().collect()
+    }
 }
 
 pub struct QuizBuilder {
@@ -125,11 +570,32 @@ impl QuizBuilder {
         }
     }
 
+    /// Assemble a quiz by drawing questions from `bank`, favouring ones
+    /// near a target difficulty
+    pub fn from_bank(title: String, bank: &[Question]) -> BankQuizBuilder<'_> {
+        BankQuizBuilder {
+            title,
+            bank,
+            target_difficulty: DEFAULT_TARGET_DIFFICULTY,
+            count: bank.len(),
+            seed: None,
+        }
+    }
+
     pub fn description(mut self, desc: String) -> Self {
         self.quiz.description = Some(desc);
         self
     }
 
+    /// Read the time from `clock` instead of the system clock
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        let now = clock.now();
+        self.quiz.created_at = now;
+        self.quiz.updated_at = now;
+        self.quiz.clock = clock;
+        self
+    }
+
     pub fn pass_threshold(mut self, threshold: f32) -> Self {
         self.quiz.pass_threshold = threshold.clamp(0.0, 1.0);
         self
@@ -182,6 +648,106 @@ impl QuizBuilder {
     pub fn build(self) -> Quiz {
         self.quiz
     }
+
+    /// Build the quiz, or return every validation problem if any question
+    /// is invalid
+    pub fn try_build(self) -> Result<Quiz, Vec<QuizValidationError>> {
+        let errors = self.quiz.validation_errors();
+        if errors.is_empty() {
+            Ok(self.quiz)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Pending question edits inside [`Quiz::edit_transaction`]
+pub struct QuizTxn {
+    questions: Vec<Question>,
+}
+
+impl QuizTxn {
+    pub fn questions(&self) -> &[Question] {
+        &self.questions
+    }
+
+    pub fn question_mut(&mut self, question_id: Uuid) -> Option<&mut Question> {
+        self.questions.iter_mut().find(|q| q.id == question_id)
+    }
+
+    /// Apply `change` to every question, e.g. to retag a whole quiz
+    pub fn for_each_question(&mut self, change: impl FnMut(&mut Question)) {
+        self.questions.iter_mut().for_each(change);
+    }
+
+    pub fn add_question(&mut self, question: Question) {
+        self.questions.push(question);
+    }
+
+    pub fn remove_question(&mut self, question_id: Uuid) -> Option<Question> {
+        let pos = self.questions.iter().position(|q| q.id == question_id)?;
+        Some(self.questions.remove(pos))
+    }
+}
+
+/// Draws a quiz's questions from a bank, see [`QuizBuilder::from_bank`]
+pub struct BankQuizBuilder<'a> {
+    title: String,
+    bank: &'a [Question],
+    target_difficulty: f32,
+    count: usize,
+    seed: Option<u64>,
+}
+
+impl BankQuizBuilder<'_> {
+    pub fn target_difficulty(mut self, difficulty: f32) -> Self {
+        self.target_difficulty = difficulty.clamp(0.0, 1.0);
+        self
+    }
+
+    /// How many questions to draw; defaults to the whole bank
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Seed the draw so the same bank gives the same quiz
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Draw `count` distinct questions, each weighted by how close its
+    /// difficulty is to the target, so picks cluster around the target
+    /// but aren't limited to the nearest questions. Errors if the bank
+    /// holds fewer than `count` questions.
+    pub fn build(self) -> crate::Result<Quiz> {
+        if self.bank.len() < self.count {
+            return Err(QuizlrError::InvalidInput(format!(
+                "Bank has {} questions, {} requested",
+                self.bank.len(),
+                self.count
+            )));
+        }
+
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let target = self.target_difficulty;
+        let weight = |question: &Question| {
+            let distance = (question.difficulty - target) / DIFFICULTY_SPREAD;
+            (-0.5 * distance * distance).exp()
+        };
+        let selected: Vec<Question> = self
+            .bank
+            .choose_multiple_weighted(&mut rng, self.count, weight)
+            .map_err(|e| QuizlrError::QuizEngine(format!("Question selection failed: {}", e)))?
+            .cloned()
+            .collect();
+
+        Ok(QuizBuilder::new(self.title).add_questions(selected).build())
+    }
 }
 
 #[cfg(test)]
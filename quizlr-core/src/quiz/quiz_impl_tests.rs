@@ -3,8 +3,10 @@
 //! DEVNOTES: Testing quiz creation, modification, and configuration
 //! to ensure proper quiz lifecycle management
 
+use crate::error::QuizlrError;
+use crate::quiz::filter::QuestionFilter;
 use crate::quiz::question::{Question, QuestionType};
-use crate::quiz::quiz_impl::{Quiz, QuizBuilder};
+use crate::quiz::quiz_impl::{Quiz, QuizBuilder, SettingChange};
 use uuid::Uuid;
 
 #[cfg(test)]
@@ -41,6 +43,42 @@ mod quiz_management_tests {
         assert!(!quiz.randomize_options);
     }
 
+    #[test]
+    fn test_timestamps_come_from_the_clock() {
+        use crate::clock::{Clock, FixedClock};
+        use chrono::{Duration, TimeZone, Utc};
+        use std::sync::Arc;
+
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 9, 0, 0).unwrap();
+        let clock = Arc::new(FixedClock::new(start));
+
+        let question = Question::with_clock(
+            QuestionType::TrueFalse {
+                statement: "Clocks can be faked".to_string(),
+                correct_answer: true,
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+            clock.as_ref(),
+        );
+        assert_eq!(question.created_at, start);
+
+        let mut quiz = QuizBuilder::new("Timed".to_string())
+            .clock(clock.clone())
+            .build();
+        assert_eq!(quiz.created_at, start);
+
+        clock.advance(Duration::minutes(5));
+        quiz.add_question(question.clone());
+        assert_eq!(quiz.created_at, start);
+        assert_eq!(quiz.updated_at, clock.now());
+
+        clock.advance(Duration::minutes(5));
+        quiz.remove_question(question.id);
+        assert_eq!(quiz.updated_at, start + Duration::minutes(10));
+    }
+
     #[test]
     fn test_quiz_builder_fluent_api() {
         // Test the builder pattern
@@ -269,4 +307,880 @@ mod quiz_management_tests {
         quiz.remove_question(quiz.questions[0].id);
         assert!(quiz.updated_at > after_add);
     }
+
+    #[test]
+    fn test_get_questions_for_session_with_seeded_rng() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut quiz = Quiz::new("Seeded Quiz".to_string());
+        quiz.randomize_questions = true;
+
+        for i in 0..10 {
+            let mut q = create_sample_question(0.5);
+            q.id = Uuid::from_u128(i as u128);
+            quiz.add_question(q);
+        }
+
+        let order = |seed: u64| -> Vec<Uuid> {
+            quiz.get_questions_for_session_with_rng(&mut StdRng::seed_from_u64(seed))
+                .iter()
+                .map(|q| q.id)
+                .collect()
+        };
+
+        let first = order(42);
+        assert_eq!(first, order(42));
+
+        let original: Vec<Uuid> = (0..10).map(|i| Uuid::from_u128(i as u128)).collect();
+        assert_ne!(first, original);
+
+        let mut sorted = first.clone();
+        sorted.sort();
+        assert_eq!(sorted, original);
+    }
+
+    #[test]
+    fn test_get_questions_for_session_with_rng_respects_flag() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut quiz = Quiz::new("Ordered Quiz".to_string());
+        for i in 0..10 {
+            let mut q = create_sample_question(0.5);
+            q.id = Uuid::from_u128(i as u128);
+            quiz.add_question(q);
+        }
+
+        let questions = quiz.get_questions_for_session_with_rng(&mut StdRng::seed_from_u64(7));
+        for (i, q) in questions.iter().enumerate() {
+            assert_eq!(q.id, Uuid::from_u128(i as u128));
+        }
+    }
+
+    #[test]
+    fn test_validate_out_of_range_author_pair() {
+        let mut quiz = Quiz::new("Matching".to_string());
+        quiz.add_question(create_sample_question(0.5));
+        assert!(quiz.validate().is_ok());
+
+        quiz.add_question(Question::new(
+            QuestionType::MatchPairs {
+                instruction: "Match".to_string(),
+                left_items: vec!["a".to_string(), "b".to_string()],
+                right_items: vec!["1".to_string(), "2".to_string()],
+                correct_pairs: vec![(0, 0), (1, 2)],
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+        ));
+
+        match quiz.validate() {
+            Err(QuizlrError::InvalidInput(message)) => {
+                assert!(message.contains("Question 1"));
+                assert!(message.contains("pair index out of range"));
+            }
+            other => panic!("expected InvalidInput, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_to_student_json_strips_answer_key() {
+        let topic_id = Uuid::new_v4();
+        let quiz = QuizBuilder::new("Shared Quiz".to_string())
+            .add_questions(vec![
+                Question::new(
+                    QuestionType::TrueFalse {
+                        statement: "Rust is memory safe".to_string(),
+                        correct_answer: true,
+                        explanation: Some("SECRET-EXPLANATION".to_string()),
+                    },
+                    topic_id,
+                    0.3,
+                ),
+                Question::new(
+                    QuestionType::MultipleChoice {
+                        question: "Package manager?".to_string(),
+                        options: vec!["npm".to_string(), "cargo".to_string()],
+                        correct_index: 1,
+                        alternate_correct: vec![],
+                        explanation: None,
+                    },
+                    topic_id,
+                    0.3,
+                ),
+                Question::new(
+                    QuestionType::MultiSelect {
+                        question: "Zero-cost?".to_string(),
+                        options: vec!["Traits".to_string(), "GC".to_string()],
+                        correct_indices: vec![0],
+                        explanation: None,
+                    },
+                    topic_id,
+                    0.5,
+                ),
+                Question::new(
+                    QuestionType::FillInTheBlank {
+                        template: "Rust's build tool is {}".to_string(),
+                        correct_answers: vec!["SECRET-BLANK".to_string()],
+                        case_sensitive: false,
+                        per_blank_credit: false,
+                        explanation: None,
+                    },
+                    topic_id,
+                    0.4,
+                ),
+                Question::new(
+                    QuestionType::MatchPairs {
+                        instruction: "Match".to_string(),
+                        left_items: vec!["a".to_string()],
+                        right_items: vec!["1".to_string()],
+                        correct_pairs: vec![(0, 0)],
+                        explanation: None,
+                    },
+                    topic_id,
+                    0.4,
+                ),
+            ])
+            .build();
+
+        let json = quiz.to_student_json().unwrap();
+
+        for leaked in [
+            "correct_index",
+            "alternate_correct",
+            "correct_indices",
+            "correct_answer",
+            "correct_answers",
+            "correct_pairs",
+            "explanation",
+            "SECRET-EXPLANATION",
+            "SECRET-BLANK",
+        ] {
+            assert!(!json.contains(leaked), "student JSON leaked {}", leaked);
+        }
+
+        // Question text and options survive
+        assert!(json.contains("Rust is memory safe"));
+        assert!(json.contains("cargo"));
+        assert!(json.contains("Rust's build tool is {}"));
+
+        let student: crate::quiz::StudentQuiz = serde_json::from_str(&json).unwrap();
+        assert_eq!(student.questions.len(), 5);
+    }
+
+    #[test]
+    fn test_difficulty_histogram_uniform_spread() {
+        let mut quiz = Quiz::new("Spread".to_string());
+        for difficulty in [0.0, 0.1, 0.3, 0.45, 0.55, 0.7, 0.8, 1.0] {
+            quiz.add_question(create_sample_question(difficulty));
+        }
+
+        assert_eq!(quiz.difficulty_histogram(4).unwrap(), vec![2, 2, 2, 2]);
+        assert_eq!(quiz.difficulty_histogram(1).unwrap(), vec![8]);
+    }
+
+    #[test]
+    fn test_difficulty_histogram_same_difficulty() {
+        let mut quiz = Quiz::new("Flat".to_string());
+        for _ in 0..5 {
+            quiz.add_question(create_sample_question(0.5));
+        }
+
+        assert_eq!(quiz.difficulty_histogram(5).unwrap(), vec![0, 0, 5, 0, 0]);
+        assert!(matches!(
+            quiz.difficulty_histogram(0),
+            Err(QuizlrError::InvalidInput(_))
+        ));
+        assert_eq!(
+            Quiz::new("Empty".to_string())
+                .difficulty_histogram(3)
+                .unwrap(),
+            vec![0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_view_omits_correctness_for_every_type() {
+        use crate::quiz::question::{FollowUpRule, RubricCriterion};
+
+        let topic_id = Uuid::new_v4();
+        let question_types = vec![
+            QuestionType::TrueFalse {
+                statement: "TF text".to_string(),
+                correct_answer: true,
+                explanation: Some("why".to_string()),
+            },
+            QuestionType::MultipleChoice {
+                question: "MC text".to_string(),
+                options: vec!["a".to_string(), "b".to_string()],
+                correct_index: 1,
+                alternate_correct: vec![0],
+                explanation: Some("why".to_string()),
+            },
+            QuestionType::MultiSelect {
+                question: "MS text".to_string(),
+                options: vec!["a".to_string(), "b".to_string()],
+                correct_indices: vec![0, 1],
+                explanation: Some("why".to_string()),
+            },
+            QuestionType::FillInTheBlank {
+                template: "FB {} text".to_string(),
+                correct_answers: vec!["hidden".to_string()],
+                case_sensitive: true,
+                per_blank_credit: false,
+                explanation: Some("why".to_string()),
+            },
+            QuestionType::MatchPairs {
+                instruction: "MP text".to_string(),
+                left_items: vec!["l".to_string()],
+                right_items: vec!["r".to_string()],
+                correct_pairs: vec![(0, 0)],
+                explanation: Some("why".to_string()),
+            },
+            QuestionType::InteractiveInterview {
+                topic: "topic".to_string(),
+                initial_question: "II text".to_string(),
+                follow_up_rules: vec![FollowUpRule {
+                    condition: "hidden".to_string(),
+                    follow_up_question: "hidden".to_string(),
+                    weight: 1.0,
+                }],
+                comprehension_threshold: 0.5,
+            },
+            QuestionType::TopicExplanation {
+                topic: "topic".to_string(),
+                prompt: "TE text".to_string(),
+                key_concepts: vec!["hidden".to_string()],
+                min_word_count: 50,
+            },
+            QuestionType::Essay {
+                prompt: "ES text".to_string(),
+                rubric: vec![RubricCriterion {
+                    description: "clarity".to_string(),
+                    weight: 1.0,
+                }],
+                max_words: None,
+            },
+        ];
+
+        let mut quiz = Quiz::new("Every type".to_string());
+        for question_type in question_types {
+            quiz.add_question(Question::new(question_type, topic_id, 0.5));
+        }
+
+        let view = quiz.view();
+        assert_eq!(view.title, "Every type");
+        assert_eq!(view.questions.len(), quiz.questions.len());
+
+        for (question, question_view) in quiz.questions.iter().zip(&view.questions) {
+            assert_eq!(question.id, question_view.id);
+            assert!(question_view.text().ends_with("text"));
+
+            let json = serde_json::to_string(question_view).unwrap();
+            for leaked in [
+                "correct",
+                "explanation",
+                "hidden",
+                "follow_up",
+                "key_concepts",
+            ] {
+                assert!(!json.contains(leaked), "{} leaked in {}", leaked, json);
+            }
+        }
+
+        assert_eq!(
+            view.questions[1].options(),
+            ["a".to_string(), "b".to_string()]
+        );
+        assert!(view.questions[0].options().is_empty());
+    }
+
+    #[test]
+    fn test_explanation_for_respects_show_explanations() {
+        let question = Question::new(
+            QuestionType::TrueFalse {
+                statement: "Sky is blue".to_string(),
+                correct_answer: true,
+                explanation: Some("Rayleigh scattering".to_string()),
+            },
+            Uuid::new_v4(),
+            0.2,
+        );
+        let id = question.id;
+        let mut quiz = QuizBuilder::new("Quiz".to_string())
+            .add_question(question)
+            .build();
+
+        assert_eq!(
+            quiz.explanation_for(id).as_deref(),
+            Some("Rayleigh scattering")
+        );
+        assert!(quiz.explanation_for(Uuid::new_v4()).is_none());
+
+        quiz.show_explanations = false;
+        assert!(quiz.explanation_for(id).is_none());
+    }
+
+    fn short_answer(template: &str, answer: &str) -> Question {
+        Question::new(
+            QuestionType::FillInTheBlank {
+                template: template.to_string(),
+                correct_answers: vec![answer.to_string()],
+                case_sensitive: false,
+                per_blank_credit: false,
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.4,
+        )
+    }
+
+    fn capitals_quiz() -> Quiz {
+        QuizBuilder::new("Capitals".to_string())
+            .add_questions(vec![
+                short_answer("The capital of France is {}", "Paris"),
+                short_answer("The capital of Japan is {}", "Tokyo"),
+                short_answer("The capital of Peru is {}", "Lima"),
+                short_answer("The capital of Kenya is {}", "Nairobi"),
+                short_answer("The city of light is {}", "paris"),
+            ])
+            .build()
+    }
+
+    #[test]
+    fn test_build_distractor_pool() {
+        let pool = capitals_quiz().build_distractor_pool();
+        assert_eq!(pool, vec!["Paris", "Tokyo", "Lima", "Nairobi", "paris"]);
+    }
+
+    #[test]
+    fn test_fill_options_from_pool_excludes_correct_answer() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let quiz = capitals_quiz();
+        let pool = quiz.build_distractor_pool();
+        let mut question = quiz.questions[0].clone();
+
+        let mut rng = StdRng::seed_from_u64(3);
+        question.fill_options_from_pool(&pool, 2, &mut rng).unwrap();
+
+        match &question.question_type {
+            QuestionType::MultipleChoice {
+                question: prompt,
+                options,
+                correct_index,
+                ..
+            } => {
+                assert_eq!(prompt, "The capital of France is ___");
+                assert_eq!(options.len(), 3);
+                assert_eq!(options[*correct_index], "Paris");
+                for (i, option) in options.iter().enumerate() {
+                    assert!(pool.contains(option));
+                    if i != *correct_index {
+                        assert!(!option.eq_ignore_ascii_case("paris"));
+                    }
+                }
+            }
+            other => panic!("expected multiple choice, got {:?}", other),
+        }
+
+        // Same seed, same options
+        let mut again = quiz.questions[0].clone();
+        again
+            .fill_options_from_pool(&pool, 2, &mut StdRng::seed_from_u64(3))
+            .unwrap();
+        assert_eq!(again.question_type, question.question_type);
+
+        let mut true_false = create_sample_question(0.5);
+        assert!(matches!(
+            true_false.fill_options_from_pool(&pool, 2, &mut rng),
+            Err(QuizlrError::InvalidInput(_))
+        ));
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Provenance {
+        author: String,
+        revision: u32,
+    }
+
+    #[test]
+    fn test_typed_metadata_round_trip() {
+        let provenance = Provenance {
+            author: "editor".to_string(),
+            revision: 3,
+        };
+
+        let mut quiz = Quiz::new("Meta".to_string());
+        quiz.set_meta("provenance", &provenance).unwrap();
+        assert_eq!(
+            quiz.get_meta::<Provenance>("provenance").unwrap(),
+            Some(provenance)
+        );
+        assert_eq!(quiz.get_meta::<Provenance>("missing").unwrap(), None);
+
+        let mut question = create_sample_question(0.5);
+        question.set_meta("reviewed", true).unwrap();
+        assert_eq!(question.get_meta::<bool>("reviewed").unwrap(), Some(true));
+        assert!(matches!(
+            question.get_meta::<u32>("reviewed"),
+            Err(QuizlrError::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn test_clear_questions_returns_removed() {
+        let mut quiz = QuizBuilder::new("Clear".to_string())
+            .add_questions(vec![
+                create_sample_question(0.2),
+                create_sample_question(0.9),
+            ])
+            .build();
+        let ids: Vec<Uuid> = quiz.questions.iter().map(|q| q.id).collect();
+        let before = quiz.updated_at;
+
+        let removed = quiz.clear_questions();
+        assert_eq!(removed.iter().map(|q| q.id).collect::<Vec<_>>(), ids);
+        assert!(quiz.questions.is_empty());
+        assert!(quiz.topic_ids.is_empty());
+        assert_eq!(quiz.difficulty_range, (0.0, 1.0));
+        assert_eq!(quiz.estimated_duration_minutes, 1);
+        assert!(quiz.updated_at >= before);
+
+        // Undo
+        quiz.replace_questions(removed);
+        assert_eq!(quiz.questions.len(), 2);
+        assert_eq!(quiz.topic_ids.len(), 2);
+        assert_eq!(quiz.difficulty_range, (0.2, 0.9));
+    }
+
+    #[test]
+    fn test_replace_questions_recomputes_derived_fields() {
+        let mut quiz = QuizBuilder::new("Replace".to_string())
+            .add_question(create_sample_question(0.1))
+            .build();
+
+        let topic = Uuid::new_v4();
+        let mut replacements = vec![create_sample_question(0.4), create_sample_question(0.6)];
+        for question in &mut replacements {
+            question.topic_id = topic;
+            question.estimated_time_seconds = 90;
+        }
+        quiz.replace_questions(replacements);
+
+        assert_eq!(quiz.topic_ids, vec![topic]);
+        assert_eq!(quiz.difficulty_range, (0.4, 0.6));
+        assert_eq!(quiz.estimated_duration_minutes, 3);
+    }
+
+    #[test]
+    fn test_estimated_duration_adjusted_for_hard_questions() {
+        let mut quiz = Quiz::new("Hard".to_string());
+        for difficulty in [1.0, 1.0, 1.0, 0.0] {
+            let mut question = create_sample_question(difficulty);
+            question.estimated_time_seconds = 120;
+            quiz.add_question(question);
+        }
+
+        // Plain: 4 x 2 minutes
+        assert_eq!(quiz.estimated_duration_minutes, 8);
+        assert_eq!(quiz.estimated_duration_adjusted(0.0), 8);
+        // Hard questions take 1.5x: 3 x 3 + 2
+        assert_eq!(quiz.estimated_duration_adjusted(0.5), 11);
+        // The stored field is left alone
+        assert_eq!(quiz.estimated_duration_minutes, 8);
+    }
+
+    #[test]
+    fn test_validation_report_clean() {
+        let quiz = QuizBuilder::new("Clean".to_string())
+            .add_questions(vec![
+                create_sample_question(0.2),
+                create_sample_question(0.4),
+            ])
+            .build();
+        assert_eq!(quiz.validation_report(), "OK: 2 questions, 2 topics");
+    }
+
+    #[test]
+    fn test_validation_report_broken() {
+        let bad_choice = || {
+            Question::new(
+                QuestionType::MultipleChoice {
+                    question: "Pick".to_string(),
+                    options: vec!["a".to_string()],
+                    correct_index: 3,
+                    alternate_correct: Vec::new(),
+                    explanation: None,
+                },
+                Uuid::new_v4(),
+                0.5,
+            )
+        };
+        let bad_pairs = Question::new(
+            QuestionType::MatchPairs {
+                instruction: "Match".to_string(),
+                left_items: vec!["l".to_string()],
+                right_items: vec!["r".to_string()],
+                correct_pairs: vec![(0, 5)],
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+        );
+        let quiz = QuizBuilder::new("Broken".to_string())
+            .add_questions(vec![
+                bad_choice(),
+                create_sample_question(0.5),
+                bad_pairs,
+                bad_choice(),
+            ])
+            .build();
+
+        assert_eq!(
+            quiz.validation_report(),
+            "3 problems in 4 questions\n\
+             \x20 correct index out of range: 2\n\
+             \x20 pair index out of range: 1\n\
+             \n\
+             Question 0: correct index out of range\n\
+             Question 2: pair index out of range\n\
+             Question 3: correct index out of range\n"
+        );
+
+        // Unlock problems are reported alongside answer-key problems
+        let mut first = create_sample_question(0.5);
+        let mut second = create_sample_question(0.5);
+        first.unlock_after = Some(second.id);
+        second.unlock_after = Some(first.id);
+        let quiz = QuizBuilder::new("Looped".to_string())
+            .add_questions(vec![first, second, create_sample_question(0.5)])
+            .build();
+
+        assert!(quiz.validate().is_err());
+        assert_eq!(
+            quiz.validation_report(),
+            "2 problems in 3 questions\n\
+             \x20 unlock dependencies form a cycle: 2\n\
+             \n\
+             Question 0: unlock dependencies form a cycle\n\
+             Question 1: unlock dependencies form a cycle\n"
+        );
+    }
+
+    #[test]
+    fn test_grouped_shuffle_keeps_groups_contiguous() {
+        let passage_a = Uuid::new_v4();
+        let passage_b = Uuid::new_v4();
+        let mut quiz = Quiz::new("Reading".to_string());
+        for i in 0..12 {
+            let mut question = create_sample_question(0.5);
+            question.group = match i % 4 {
+                0 => Some(passage_a),
+                1 => Some(passage_b),
+                _ => None,
+            };
+            quiz.add_question(question);
+        }
+
+        for seed in 0..20 {
+            let shuffled = quiz.get_questions_grouped_shuffle(seed);
+            assert_eq!(shuffled.len(), quiz.questions.len());
+
+            for group in [passage_a, passage_b] {
+                let positions: Vec<usize> = shuffled
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, q)| q.group == Some(group))
+                    .map(|(i, _)| i)
+                    .collect();
+                assert_eq!(positions.len(), 3);
+                assert_eq!(positions[2] - positions[0], 2, "seed {}", seed);
+            }
+        }
+
+        // Deterministic per seed
+        let ids = |qs: Vec<Question>| qs.into_iter().map(|q| q.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(quiz.get_questions_grouped_shuffle(7)),
+            ids(quiz.get_questions_grouped_shuffle(7))
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_tracks_content_not_timestamps() {
+        let mut quiz = QuizBuilder::new("Print".to_string())
+            .add_question(create_sample_question(0.5))
+            .build();
+        let original = quiz.fingerprint();
+        assert_eq!(original.len(), 16);
+
+        // Re-saving only touches timestamps
+        let mut resaved = quiz.clone();
+        resaved.updated_at += chrono::Duration::hours(1);
+        assert_eq!(resaved.fingerprint(), original);
+
+        // An independently built copy matches too
+        let copy = QuizBuilder::new("Print".to_string())
+            .add_question(create_sample_question(0.5))
+            .build();
+        assert_eq!(copy.fingerprint(), original);
+
+        quiz.add_question(create_sample_question(0.9));
+        assert_ne!(quiz.fingerprint(), original);
+
+        resaved.pass_threshold = 0.9;
+        assert_ne!(resaved.fingerprint(), original);
+    }
+
+    #[test]
+    fn test_try_build_rejects_invalid_question() {
+        let bad = Question::new(
+            QuestionType::MultipleChoice {
+                question: "Pick".to_string(),
+                options: vec!["a".to_string(), "b".to_string()],
+                correct_index: 5,
+                alternate_correct: vec![],
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+        );
+        let bad_id = bad.id;
+
+        let errors = QuizBuilder::new("Broken".to_string())
+            .add_question(create_sample_question(0.5))
+            .add_question(bad)
+            .try_build()
+            .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].question_index, 1);
+        assert_eq!(errors[0].question_id, bad_id);
+        assert_eq!(errors[0].message, "correct index out of range");
+
+        let quiz = QuizBuilder::new("Fine".to_string())
+            .add_question(create_sample_question(0.5))
+            .try_build()
+            .unwrap();
+        assert_eq!(quiz.questions.len(), 1);
+    }
+
+    #[test]
+    fn test_filter_mixed_locale_quiz() {
+        let localized = |locale: Option<&str>| {
+            let mut question = create_sample_question(0.5);
+            question.locale = locale.map(str::to_string);
+            question
+        };
+        let quiz = QuizBuilder::new("Bilingual".to_string())
+            .add_question(localized(Some("en")))
+            .add_question(localized(Some("es")))
+            .add_question(localized(Some("es-MX")))
+            .add_question(localized(None))
+            .build();
+
+        let locales: Vec<String> = quiz.available_locales().into_iter().collect();
+        assert_eq!(locales, vec!["en", "es", "es-MX"]);
+
+        let spanish = quiz.filtered(&QuestionFilter::new().locale("es".to_string()));
+        assert_eq!(spanish.questions.len(), 2);
+        assert!(spanish
+            .questions
+            .iter()
+            .all(|q| q.locale.as_deref().unwrap().starts_with("es")));
+        assert_eq!(quiz.questions.len(), 4);
+
+        let mexican = quiz.filtered(&QuestionFilter::new().locale("es-mx".to_string()));
+        assert_eq!(mexican.questions.len(), 1);
+    }
+
+    #[test]
+    fn test_prune_topic_ids_after_removing_last_question_of_topic() {
+        let mut quiz = Quiz::new("Topics".to_string());
+        let first = create_sample_question(0.3);
+        let mut second = create_sample_question(0.5);
+        second.topic_id = first.topic_id;
+        let lone = create_sample_question(0.7);
+        let (kept_topic, lone_topic, lone_id) = (first.topic_id, lone.topic_id, lone.id);
+
+        quiz.add_question(first);
+        quiz.add_question(second);
+        quiz.add_question(lone);
+        assert_eq!(quiz.topic_ids, vec![kept_topic, lone_topic]);
+        assert!(quiz.orphaned_topic_ids().is_empty());
+
+        quiz.remove_question(lone_id);
+        assert_eq!(quiz.topic_ids, vec![kept_topic, lone_topic]);
+        assert_eq!(quiz.orphaned_topic_ids(), vec![lone_topic]);
+
+        quiz.prune_topic_ids();
+        assert_eq!(quiz.topic_ids, vec![kept_topic]);
+        assert!(quiz.orphaned_topic_ids().is_empty());
+    }
+
+    #[test]
+    fn test_from_bank_centers_on_target_difficulty() {
+        let bank: Vec<Question> = (0..200)
+            .map(|i| create_sample_question(i as f32 / 199.0))
+            .collect();
+
+        let quiz = QuizBuilder::from_bank("Medium".to_string(), &bank)
+            .target_difficulty(0.5)
+            .count(20)
+            .seed(42)
+            .build()
+            .unwrap();
+        assert_eq!(quiz.title, "Medium");
+        assert_eq!(quiz.questions.len(), 20);
+
+        let difficulties: Vec<f32> = quiz.questions.iter().map(|q| q.difficulty).collect();
+        let mean = difficulties.iter().sum::<f32>() / 20.0;
+        let mean_distance = difficulties.iter().map(|d| (d - 0.5).abs()).sum::<f32>() / 20.0;
+        assert!((mean - 0.5).abs() < 0.1, "mean difficulty {}", mean);
+        // Uniform sampling from this bank would average 0.25 away
+        assert!(mean_distance < 0.2, "mean distance {}", mean_distance);
+
+        let again = QuizBuilder::from_bank("Medium".to_string(), &bank)
+            .target_difficulty(0.5)
+            .count(20)
+            .seed(42)
+            .build()
+            .unwrap();
+        let ids = |quiz: &Quiz| quiz.questions.iter().map(|q| q.id).collect::<Vec<_>>();
+        assert_eq!(ids(&quiz), ids(&again));
+    }
+
+    #[test]
+    fn test_from_bank_errors_when_bank_too_small() {
+        let bank: Vec<Question> = (0..3).map(|_| create_sample_question(0.5)).collect();
+        let result = QuizBuilder::from_bank("Too many".to_string(), &bank)
+            .count(4)
+            .build();
+        assert!(matches!(result, Err(QuizlrError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_difficulty_range_by_topic() {
+        let easy_topic = Uuid::new_v4();
+        let hard_topic = Uuid::new_v4();
+        let mut quiz = Quiz::new("Two topics".to_string());
+        for (topic_id, difficulty) in [
+            (easy_topic, 0.2),
+            (hard_topic, 0.9),
+            (easy_topic, 0.1),
+            (hard_topic, 0.6),
+            (easy_topic, 0.3),
+        ] {
+            let mut question = create_sample_question(difficulty);
+            question.topic_id = topic_id;
+            quiz.add_question(question);
+        }
+
+        let ranges = quiz.difficulty_range_by_topic();
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[&easy_topic], (0.1, 0.3));
+        assert_eq!(ranges[&hard_topic], (0.6, 0.9));
+        assert_eq!(quiz.difficulty_range, (0.1, 0.9));
+
+        assert!(Quiz::new("Empty".to_string())
+            .difficulty_range_by_topic()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_edit_transaction_rolls_back_on_invalid_edit() {
+        let mut quiz = Quiz::new("Transactional".to_string());
+        let mc = Question::new(
+            QuestionType::MultipleChoice {
+                question: "Pick one".to_string(),
+                options: vec!["A".to_string(), "B".to_string()],
+                correct_index: 0,
+                alternate_correct: vec![],
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+        );
+        let mc_id = mc.id;
+        quiz.add_question(create_sample_question(0.2));
+        quiz.add_question(mc);
+        let before = serde_json::to_value(&quiz).unwrap();
+
+        // Retag everything, then break the answer key of one question
+        let result = quiz.edit_transaction(|txn| {
+            txn.for_each_question(|q| q.tags.push("edited".to_string()));
+            txn.add_question(create_sample_question(0.9));
+            if let QuestionType::MultipleChoice { correct_index, .. } =
+                &mut txn.question_mut(mc_id).unwrap().question_type
+            {
+                *correct_index = 5;
+            }
+            Ok(())
+        });
+        assert!(matches!(result, Err(QuizlrError::InvalidInput(_))));
+        assert_eq!(serde_json::to_value(&quiz).unwrap(), before);
+
+        // An error from the edit itself also discards everything
+        let result = quiz.edit_transaction(|txn| {
+            txn.remove_question(mc_id);
+            Err(QuizlrError::InvalidInput("changed my mind".to_string()))
+        });
+        assert!(result.is_err());
+        assert_eq!(serde_json::to_value(&quiz).unwrap(), before);
+
+        // A valid transaction commits and refreshes derived fields
+        quiz.edit_transaction(|txn| {
+            txn.remove_question(mc_id);
+            txn.add_question(create_sample_question(0.9));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(quiz.questions.len(), 2);
+        assert!(quiz.questions.iter().all(|q| q.id != mc_id));
+        assert_eq!(quiz.difficulty_range, (0.2, 0.9));
+    }
+
+    #[test]
+    fn test_diff_between_versions() {
+        let kept = create_sample_question(0.3);
+        let reworded = create_sample_question(0.5);
+        let dropped = create_sample_question(0.7);
+        let old = QuizBuilder::new("Versions".to_string())
+            .add_questions(vec![kept.clone(), reworded.clone(), dropped.clone()])
+            .build();
+        assert!(old.diff(&old.clone()).is_empty());
+
+        let mut new = old.clone();
+        new.remove_question(dropped.id);
+        if let QuestionType::TrueFalse { statement, .. } = &mut new.questions[1].question_type {
+            *statement = "A clearer statement".to_string();
+        }
+        let added = create_sample_question(0.9);
+        new.add_question(added.clone());
+        new.pass_threshold = 0.8;
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, vec![added.id]);
+        assert_eq!(diff.removed, vec![dropped.id]);
+        assert_eq!(diff.modified, vec![reworded.id]);
+        assert_eq!(
+            diff.settings,
+            vec![SettingChange {
+                name: "pass_threshold".to_string(),
+                old: serde_json::json!(0.7f32),
+                new: serde_json::json!(0.8f32),
+            }]
+        );
+
+        // Deterministic and serializable for display
+        assert_eq!(diff, old.diff(&new));
+        let json = serde_json::to_string(&diff).unwrap();
+        assert_eq!(
+            serde_json::from_str::<crate::quiz::QuizDiff>(&json).unwrap(),
+            diff
+        );
+    }
 }
@@ -1,18 +1,59 @@
 use super::session::{QuestionResponse, QuizSession};
 use super::Question;
+use crate::error::QuizlrError;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Score {
     pub raw_score: f32,
     pub weighted_score: f32,
     pub percentile: Option<f32>,
+    #[serde(default)]
+    pub z_score: Option<f32>, // Standard deviations from the cohort mean
     pub time_bonus: f32,
     pub difficulty_bonus: f32,
     pub streak_bonus: f32,
     pub components: ScoreComponents,
 }
 
+impl Score {
+    /// Flatten the score into stable metric names for time-series stores.
+    /// `percentile` and `z_score` are only included when known.
+    pub fn as_metrics(&self) -> BTreeMap<String, f32> {
+        let mut metrics = BTreeMap::from([
+            ("score.raw".to_string(), self.raw_score),
+            ("score.weighted".to_string(), self.weighted_score),
+            ("bonus.time".to_string(), self.time_bonus),
+            ("bonus.difficulty".to_string(), self.difficulty_bonus),
+            ("bonus.streak".to_string(), self.streak_bonus),
+            (
+                "component.correctness".to_string(),
+                self.components.correctness,
+            ),
+            ("component.speed".to_string(), self.components.speed),
+            (
+                "component.difficulty".to_string(),
+                self.components.difficulty,
+            ),
+            (
+                "component.consistency".to_string(),
+                self.components.consistency,
+            ),
+        ]);
+
+        if let Some(percentile) = self.percentile {
+            metrics.insert("score.percentile".to_string(), percentile);
+        }
+        if let Some(z_score) = self.z_score {
+            metrics.insert("score.z".to_string(), z_score);
+        }
+
+        metrics
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScoreComponents {
     pub correctness: f32,
@@ -21,9 +62,140 @@ pub struct ScoreComponents {
     pub consistency: f32,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DifficultyLevel {
+    Easy,
+    Medium,
+    Hard,
+}
+
+/// Boundaries splitting the 0.0-1.0 difficulty scale into easy, medium and
+/// hard. Shared by every strategy that looks at difficulty so they agree on
+/// what counts as hard.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyBuckets {
+    pub easy_max: f32, // Below this is easy
+    pub hard_min: f32, // At or above this is hard
+}
+
+impl Default for DifficultyBuckets {
+    fn default() -> Self {
+        Self {
+            easy_max: 0.33,
+            hard_min: 0.67,
+        }
+    }
+}
+
+impl DifficultyBuckets {
+    pub fn level(&self, difficulty: f32) -> DifficultyLevel {
+        if difficulty < self.easy_max {
+            DifficultyLevel::Easy
+        } else if difficulty < self.hard_min {
+            DifficultyLevel::Medium
+        } else {
+            DifficultyLevel::Hard
+        }
+    }
+
+    /// Credit for a question at this difficulty: 1, 2 or 3 by level
+    pub(super) fn weight(&self, difficulty: f32) -> f32 {
+        match self.level(difficulty) {
+            DifficultyLevel::Easy => 1.0,
+            DifficultyLevel::Medium => 2.0,
+            DifficultyLevel::Hard => 3.0,
+        }
+    }
+}
+
+/// Settings of [`ScoringStrategy::Adaptive`], passed to the scorer as one
+struct AdaptiveParams<'a> {
+    time_weight: f32,
+    difficulty_weight: f32,
+    streak_weight: f32,
+    consistency_weight: f32,
+    buckets: &'a DifficultyBuckets,
+    hint_penalty_per_use: f32,
+}
+
+/// Questions indexed by id, built once and shared when scoring many
+/// sessions
+struct QuestionIndex<'a> {
+    questions: &'a [Question],
+    by_id: HashMap<Uuid, &'a Question>,
+}
+
+impl<'a> QuestionIndex<'a> {
+    fn new(questions: &'a [Question]) -> Self {
+        Self {
+            questions,
+            by_id: questions.iter().map(|q| (q.id, q)).collect(),
+        }
+    }
+}
+
+/// Credit for a correct answer given on attempt number `attempts`: full
+/// on the first try, `decay` less for each retry, never below zero
+pub(super) fn attempt_credit(attempts: u32, decay: f32) -> f32 {
+    (1.0 - attempts.saturating_sub(1) as f32 * decay).max(0.0)
+}
+
+/// Standard score of `value` within `cohort`: its distance from the cohort
+/// mean in (population) standard deviations. `None` when the cohort has
+/// fewer than two values or no spread.
+pub fn z_score(value: f32, cohort: &[f32]) -> Option<f32> {
+    if cohort.len() < 2 {
+        return None;
+    }
+    let n = cohort.len() as f32;
+    let mean = cohort.iter().sum::<f32>() / n;
+    let variance = cohort.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n;
+    let std_dev = variance.sqrt();
+    if std_dev <= f32::EPSILON {
+        return None;
+    }
+    Some((value - mean) / std_dev)
+}
+
+/// Credit for one correct response after the strategy's hint penalty,
+/// never below zero
+pub(super) fn hint_credit(hints_used: u32, penalty_per_use: f32) -> f32 {
+    (1.0 - hints_used as f32 * penalty_per_use.max(0.0)).max(0.0)
+}
+
+/// Fraction of the quiz answered correctly, with each correct answer
+/// docked `penalty_per_use` for every hint it used
+fn hinted_correctness(session: &QuizSession, index: &QuestionIndex, penalty_per_use: f32) -> f32 {
+    let total = index.questions.len() as f32;
+    if total == 0.0 {
+        return 0.0;
+    }
+    let credit: f32 = session
+        .responses
+        .iter()
+        .filter(|r| r.is_correct && index.by_id.contains_key(&r.question_id))
+        .map(|r| hint_credit(r.hints_used, penalty_per_use))
+        .sum();
+    credit / total
+}
+
+/// Percentage of the cohort scoring below `value`, counting ties as half
+fn percentile_rank(value: f32, cohort: &[f32]) -> Option<f32> {
+    if cohort.is_empty() {
+        return None;
+    }
+    let below = cohort.iter().filter(|&&v| v < value).count() as f32;
+    let tied = cohort.iter().filter(|&&v| v == value).count() as f32;
+    Some((below + tied / 2.0) / cohort.len() as f32 * 100.0)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ScoringStrategy {
-    Simple, // Just correct/incorrect
+    Simple {
+        // Just correct/incorrect
+        #[serde(default)]
+        hint_penalty_per_use: f32, // Credit lost per hint on a correct answer
+    },
     TimeWeighted {
         // Factor in response time
         base_time_seconds: u32,
@@ -34,6 +206,8 @@ pub enum ScoringStrategy {
         easy_multiplier: f32,
         medium_multiplier: f32,
         hard_multiplier: f32,
+        #[serde(default)]
+        buckets: DifficultyBuckets,
     },
     Adaptive {
         // Comprehensive scoring
@@ -41,29 +215,89 @@ pub enum ScoringStrategy {
         difficulty_weight: f32,
         streak_weight: f32,
         consistency_weight: f32,
+        #[serde(default)]
+        buckets: DifficultyBuckets,
+        #[serde(default)]
+        hint_penalty_per_use: f32,
+    },
+    AttemptWeighted {
+        // Correct answers lose credit with each retry
+        decay_per_attempt: f32,
     },
 }
 
 impl ScoringStrategy {
     pub fn calculate_score(&self, session: &QuizSession, questions: &[Question]) -> Score {
+        self.score_with_index(session, &QuestionIndex::new(questions))
+    }
+
+    /// Score a session and place its weighted score within `cohort`, the
+    /// weighted scores of other attempts at the same quiz, filling in
+    /// `percentile` and `z_score`
+    pub fn calculate_score_in_cohort(
+        &self,
+        session: &QuizSession,
+        questions: &[Question],
+        cohort: &[f32],
+    ) -> Score {
+        let mut score = self.calculate_score(session, questions);
+        score.percentile = percentile_rank(score.weighted_score, cohort);
+        score.z_score = z_score(score.weighted_score, cohort);
+        score
+    }
+
+    /// Score every session against the same questions, building the
+    /// question lookup once for the whole batch
+    pub fn calculate_scores(&self, sessions: &[QuizSession], questions: &[Question]) -> Vec<Score> {
+        let index = QuestionIndex::new(questions);
+        sessions
+            .iter()
+            .map(|session| self.score_with_index(session, &index))
+            .collect()
+    }
+
+    /// Parallel version of [`ScoringStrategy::calculate_scores`]. Results
+    /// are in the same order as `sessions`.
+    #[cfg(feature = "native")]
+    pub fn calculate_scores_par(
+        &self,
+        sessions: &[QuizSession],
+        questions: &[Question],
+    ) -> Vec<Score> {
+        use rayon::prelude::*;
+
+        let index = QuestionIndex::new(questions);
+        sessions
+            .par_iter()
+            .map(|session| self.score_with_index(session, &index))
+            .collect()
+    }
+
+    fn score_with_index(&self, session: &QuizSession, index: &QuestionIndex) -> Score {
         match self {
-            ScoringStrategy::Simple => self.simple_score(session, questions),
+            ScoringStrategy::Simple {
+                hint_penalty_per_use,
+            } => {
+                let mut score = self.simple_score(session, index);
+                score.weighted_score = hinted_correctness(session, index, *hint_penalty_per_use);
+                score
+            }
+            ScoringStrategy::AttemptWeighted { decay_per_attempt } => {
+                self.attempt_weighted_score(session, index, *decay_per_attempt)
+            }
             ScoringStrategy::TimeWeighted {
                 base_time_seconds,
                 penalty_per_second,
-            } => self.time_weighted_score(
-                session,
-                questions,
-                *base_time_seconds,
-                *penalty_per_second,
-            ),
+            } => self.time_weighted_score(session, index, *base_time_seconds, *penalty_per_second),
             ScoringStrategy::DifficultyWeighted {
                 easy_multiplier,
                 medium_multiplier,
                 hard_multiplier,
+                buckets,
             } => self.difficulty_weighted_score(
                 session,
-                questions,
+                index,
+                buckets,
                 *easy_multiplier,
                 *medium_multiplier,
                 *hard_multiplier,
@@ -73,20 +307,84 @@ impl ScoringStrategy {
                 difficulty_weight,
                 streak_weight,
                 consistency_weight,
+                buckets,
+                hint_penalty_per_use,
             } => self.adaptive_score(
                 session,
-                questions,
-                *time_weight,
-                *difficulty_weight,
-                *streak_weight,
-                *consistency_weight,
+                index,
+                &AdaptiveParams {
+                    time_weight: *time_weight,
+                    difficulty_weight: *difficulty_weight,
+                    streak_weight: *streak_weight,
+                    consistency_weight: *consistency_weight,
+                    buckets,
+                    hint_penalty_per_use: *hint_penalty_per_use,
+                },
             ),
         }
     }
 
-    fn simple_score(&self, session: &QuizSession, questions: &[Question]) -> Score {
-        let total = questions.len() as f32;
-        let correct = session.responses.iter().filter(|r| r.is_correct).count() as f32;
+    /// Whether the session passes `threshold` on this strategy's weighted
+    /// score. Use [`SessionSummary::passed`](super::SessionSummary::passed)
+    /// when only the plain correct/total ratio matters.
+    pub fn passed(&self, session: &QuizSession, questions: &[Question], threshold: f32) -> bool {
+        self.calculate_score(session, questions).weighted_score >= threshold
+    }
+
+    /// Credit taken off a correct answer per hint used, or 0.0 for
+    /// strategies that ignore hints
+    pub fn hint_penalty_per_use(&self) -> f32 {
+        match self {
+            ScoringStrategy::Simple {
+                hint_penalty_per_use,
+            }
+            | ScoringStrategy::Adaptive {
+                hint_penalty_per_use,
+                ..
+            } => hint_penalty_per_use.max(0.0),
+            _ => 0.0,
+        }
+    }
+
+    /// Difficulty level of a question under this strategy's buckets, or
+    /// `None` for strategies that ignore difficulty
+    pub fn difficulty_level(&self, difficulty: f32) -> Option<DifficultyLevel> {
+        match self {
+            ScoringStrategy::DifficultyWeighted { buckets, .. }
+            | ScoringStrategy::Adaptive { buckets, .. } => Some(buckets.level(difficulty)),
+            _ => None,
+        }
+    }
+
+    /// Like [`ScoringStrategy::calculate_score`], but fails with `NotFound`
+    /// if any response refers to a question missing from `questions`
+    /// instead of silently skipping it.
+    pub fn calculate_score_checked(
+        &self,
+        session: &QuizSession,
+        questions: &[Question],
+    ) -> crate::Result<Score> {
+        if let Some(orphan) = session
+            .responses
+            .iter()
+            .find(|r| !questions.iter().any(|q| q.id == r.question_id))
+        {
+            return Err(QuizlrError::NotFound(format!(
+                "Question {} referenced by a response",
+                orphan.question_id
+            )));
+        }
+
+        Ok(self.calculate_score(session, questions))
+    }
+
+    fn simple_score(&self, session: &QuizSession, index: &QuestionIndex) -> Score {
+        let total = index.questions.len() as f32;
+        let correct = session
+            .responses
+            .iter()
+            .filter(|r| r.is_correct && index.by_id.contains_key(&r.question_id))
+            .count() as f32;
 
         let raw_score = if total > 0.0 { correct / total } else { 0.0 };
 
@@ -94,6 +392,7 @@ impl ScoringStrategy {
             raw_score,
             weighted_score: raw_score,
             percentile: None,
+            z_score: None,
             time_bonus: 0.0,
             difficulty_bonus: 0.0,
             streak_bonus: 0.0,
@@ -109,18 +408,18 @@ impl ScoringStrategy {
     fn time_weighted_score(
         &self,
         session: &QuizSession,
-        questions: &[Question],
+        index: &QuestionIndex,
         base_time_seconds: u32,
         penalty_per_second: f32,
     ) -> Score {
         let mut total_score = 0.0;
-        let question_map: std::collections::HashMap<_, _> =
-            questions.iter().map(|q| (q.id, q)).collect();
 
         for response in &session.responses {
-            if let Some(_question) = question_map.get(&response.question_id) {
+            if let Some(_question) = index.by_id.get(&response.question_id) {
                 let base_points = if response.is_correct { 1.0 } else { 0.0 };
-                let time_penalty = if response.time_taken_seconds > base_time_seconds {
+                let time_penalty = if session.is_timed()
+                    && response.time_taken_seconds > base_time_seconds
+                {
                     (response.time_taken_seconds - base_time_seconds) as f32 * penalty_per_second
                 } else {
                     0.0
@@ -131,7 +430,7 @@ impl ScoringStrategy {
             }
         }
 
-        let max_score = questions.len() as f32;
+        let max_score = index.questions.len() as f32;
         let weighted_score = if max_score > 0.0 {
             total_score / max_score
         } else {
@@ -139,15 +438,54 @@ impl ScoringStrategy {
         };
 
         Score {
-            raw_score: self.simple_score(session, questions).raw_score,
+            raw_score: self.simple_score(session, index).raw_score,
+            weighted_score,
+            percentile: None,
+            z_score: None,
+            time_bonus: weighted_score - self.simple_score(session, index).raw_score,
+            difficulty_bonus: 0.0,
+            streak_bonus: 0.0,
+            components: ScoreComponents {
+                correctness: self.simple_score(session, index).raw_score,
+                speed: weighted_score - self.simple_score(session, index).raw_score,
+                difficulty: 0.0,
+                consistency: 0.0,
+            },
+        }
+    }
+
+    fn attempt_weighted_score(
+        &self,
+        session: &QuizSession,
+        index: &QuestionIndex,
+        decay_per_attempt: f32,
+    ) -> Score {
+        let total_credit: f32 = session
+            .responses
+            .iter()
+            .filter(|r| r.is_correct && index.by_id.contains_key(&r.question_id))
+            .map(|r| attempt_credit(r.attempts, decay_per_attempt))
+            .sum();
+
+        let max_score = index.questions.len() as f32;
+        let weighted_score = if max_score > 0.0 {
+            total_credit / max_score
+        } else {
+            0.0
+        };
+        let raw_score = self.simple_score(session, index).raw_score;
+
+        Score {
+            raw_score,
             weighted_score,
             percentile: None,
-            time_bonus: weighted_score - self.simple_score(session, questions).raw_score,
+            z_score: None,
+            time_bonus: 0.0,
             difficulty_bonus: 0.0,
             streak_bonus: 0.0,
             components: ScoreComponents {
-                correctness: self.simple_score(session, questions).raw_score,
-                speed: weighted_score - self.simple_score(session, questions).raw_score,
+                correctness: raw_score,
+                speed: 0.0,
                 difficulty: 0.0,
                 consistency: 0.0,
             },
@@ -157,7 +495,8 @@ impl ScoringStrategy {
     fn difficulty_weighted_score(
         &self,
         session: &QuizSession,
-        questions: &[Question],
+        index: &QuestionIndex,
+        buckets: &DifficultyBuckets,
         easy_multiplier: f32,
         medium_multiplier: f32,
         hard_multiplier: f32,
@@ -166,26 +505,24 @@ impl ScoringStrategy {
         let mut max_possible = 0.0;
 
         // Calculate max possible from all questions
-        for question in questions {
-            let multiplier = match question.difficulty {
-                d if d < 0.33 => easy_multiplier,
-                d if d < 0.67 => medium_multiplier,
-                _ => hard_multiplier,
+        for question in index.questions {
+            let multiplier = match buckets.level(question.difficulty) {
+                DifficultyLevel::Easy => easy_multiplier,
+                DifficultyLevel::Medium => medium_multiplier,
+                DifficultyLevel::Hard => hard_multiplier,
             };
             max_possible += multiplier;
         }
 
         // Add scores for correct answers
-        let question_map: std::collections::HashMap<_, _> =
-            questions.iter().map(|q| (q.id, q)).collect();
 
         for response in &session.responses {
-            if let Some(question) = question_map.get(&response.question_id) {
+            if let Some(question) = index.by_id.get(&response.question_id) {
                 if response.is_correct {
-                    let multiplier = match question.difficulty {
-                        d if d < 0.33 => easy_multiplier,
-                        d if d < 0.67 => medium_multiplier,
-                        _ => hard_multiplier,
+                    let multiplier = match buckets.level(question.difficulty) {
+                        DifficultyLevel::Easy => easy_multiplier,
+                        DifficultyLevel::Medium => medium_multiplier,
+                        DifficultyLevel::Hard => hard_multiplier,
                     };
                     total_score += multiplier;
                 }
@@ -197,12 +534,13 @@ impl ScoringStrategy {
         } else {
             0.0
         };
-        let raw_score = self.simple_score(session, questions).raw_score;
+        let raw_score = self.simple_score(session, index).raw_score;
 
         Score {
             raw_score,
             weighted_score,
             percentile: None,
+            z_score: None,
             time_bonus: 0.0,
             difficulty_bonus: weighted_score - raw_score,
             streak_bonus: 0.0,
@@ -218,20 +556,28 @@ impl ScoringStrategy {
     fn adaptive_score(
         &self,
         session: &QuizSession,
-        questions: &[Question],
-        time_weight: f32,
-        difficulty_weight: f32,
-        streak_weight: f32,
-        consistency_weight: f32,
+        index: &QuestionIndex,
+        params: &AdaptiveParams,
     ) -> Score {
+        let AdaptiveParams {
+            time_weight,
+            difficulty_weight,
+            streak_weight,
+            consistency_weight,
+            buckets,
+            hint_penalty_per_use,
+        } = *params;
+        // Untimed sessions leave speed out of the blend entirely
+        let time_weight = if session.is_timed() { time_weight } else { 0.0 };
         let total_weight = time_weight + difficulty_weight + streak_weight + consistency_weight;
 
-        // Calculate base correctness score
-        let correctness_score = self.simple_score(session, questions).raw_score;
+        // Calculate base correctness score, less any hint penalties
+        let raw_score = self.simple_score(session, index).raw_score;
+        let correctness_score = hinted_correctness(session, index, hint_penalty_per_use);
 
         // Calculate time score
-        let time_score = if session.responses.is_empty() {
-            0.0 // No time score if no responses
+        let time_score = if session.responses.is_empty() || !session.is_timed() {
+            0.0 // No time score if no responses or untimed
         } else {
             let avg_time: f32 = session
                 .responses
@@ -239,16 +585,17 @@ impl ScoringStrategy {
                 .map(|r| r.time_taken_seconds as f32)
                 .sum::<f32>()
                 / session.responses.len() as f32;
-            let expected_avg_time: f32 = questions
+            let expected_avg_time: f32 = index
+                .questions
                 .iter()
                 .map(|q| q.estimated_time_seconds as f32)
                 .sum::<f32>()
-                / questions.len().max(1) as f32;
+                / index.questions.len().max(1) as f32;
             (expected_avg_time / avg_time.max(1.0)).min(1.0)
         };
 
         // Calculate difficulty score
-        let difficulty_score = self.calculate_difficulty_score(session, questions);
+        let difficulty_score = self.calculate_difficulty_score(session, index, buckets);
 
         // Calculate streak score
         let streak_score = self.calculate_streak_score(&session.responses);
@@ -265,9 +612,10 @@ impl ScoringStrategy {
             / (1.0 + total_weight);
 
         Score {
-            raw_score: correctness_score,
+            raw_score,
             weighted_score,
             percentile: None,
+            z_score: None,
             time_bonus: time_score * time_weight,
             difficulty_bonus: difficulty_score * difficulty_weight,
             streak_bonus: streak_score * streak_weight,
@@ -280,18 +628,21 @@ impl ScoringStrategy {
         }
     }
 
-    fn calculate_difficulty_score(&self, session: &QuizSession, questions: &[Question]) -> f32 {
-        let question_map: std::collections::HashMap<_, _> =
-            questions.iter().map(|q| (q.id, q)).collect();
-
+    fn calculate_difficulty_score(
+        &self,
+        session: &QuizSession,
+        index: &QuestionIndex,
+        buckets: &DifficultyBuckets,
+    ) -> f32 {
         let mut difficulty_sum = 0.0;
         let mut correct_difficulty_sum = 0.0;
 
         for response in &session.responses {
-            if let Some(question) = question_map.get(&response.question_id) {
-                difficulty_sum += question.difficulty;
+            if let Some(question) = index.by_id.get(&response.question_id) {
+                let weight = buckets.weight(question.difficulty);
+                difficulty_sum += weight;
                 if response.is_correct {
-                    correct_difficulty_sum += question.difficulty;
+                    correct_difficulty_sum += weight;
                 }
             }
         }
@@ -357,7 +708,9 @@ mod tests {
 
     #[test]
     fn test_simple_scoring() {
-        let strategy = ScoringStrategy::Simple;
+        let strategy = ScoringStrategy::Simple {
+            hint_penalty_per_use: 0.0,
+        };
         let mut session = QuizSession::new(Uuid::new_v4(), None);
 
         // Create test questions
@@ -390,6 +743,11 @@ mod tests {
             time_taken_seconds: 10,
             attempts: 1,
             submitted_at: chrono::Utc::now(),
+            attempt_history: Vec::new(),
+            confidence: None,
+            first_attempt_correct: true,
+            shown_options: None,
+            hints_used: 0,
         });
 
         session.responses.push(QuestionResponse {
@@ -399,6 +757,11 @@ mod tests {
             time_taken_seconds: 15,
             attempts: 1,
             submitted_at: chrono::Utc::now(),
+            attempt_history: Vec::new(),
+            confidence: None,
+            first_attempt_correct: false,
+            shown_options: None,
+            hints_used: 0,
         });
 
         let score = strategy.calculate_score(&session, &questions);
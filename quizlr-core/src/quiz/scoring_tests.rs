@@ -3,9 +3,10 @@
 //! DEVNOTES: Testing all scoring strategies to ensure accurate
 //! and fair assessment of quiz performance
 
+use crate::error::QuizlrError;
 use crate::quiz::question::{Answer, Question, QuestionType};
-use crate::quiz::scoring::ScoringStrategy;
-use crate::quiz::session::{QuestionResponse, QuizSession};
+use crate::quiz::scoring::{z_score, DifficultyBuckets, DifficultyLevel, ScoringStrategy};
+use crate::quiz::session::{QuestionResponse, QuizSession, TimingMode};
 use chrono::Utc;
 use uuid::Uuid;
 
@@ -37,7 +38,9 @@ mod scoring_strategy_tests {
         correct_mask: Vec<bool>,
         times: Vec<u32>,
     ) -> QuizSession {
+        // Timed, so the speed-based strategies score the response times
         let mut session = QuizSession::new(Uuid::new_v4(), None);
+        session.timing_mode = Some(TimingMode::Total(3600));
 
         for ((question, is_correct), time) in
             questions.iter().zip(correct_mask.iter()).zip(times.iter())
@@ -49,6 +52,11 @@ mod scoring_strategy_tests {
                 time_taken_seconds: *time,
                 attempts: 1,
                 submitted_at: Utc::now(),
+                attempt_history: Vec::new(),
+                confidence: None,
+                first_attempt_correct: *is_correct,
+                shown_options: None,
+                hints_used: 0,
             });
         }
 
@@ -57,7 +65,9 @@ mod scoring_strategy_tests {
 
     #[test]
     fn test_simple_scoring_all_correct() {
-        let strategy = ScoringStrategy::Simple;
+        let strategy = ScoringStrategy::Simple {
+            hint_penalty_per_use: 0.0,
+        };
         let questions = create_questions_with_difficulties(vec![0.3, 0.5, 0.7]);
         let session =
             create_session_with_responses(&questions, vec![true, true, true], vec![30, 45, 60]);
@@ -74,7 +84,9 @@ mod scoring_strategy_tests {
 
     #[test]
     fn test_simple_scoring_partial() {
-        let strategy = ScoringStrategy::Simple;
+        let strategy = ScoringStrategy::Simple {
+            hint_penalty_per_use: 0.0,
+        };
         let questions = create_questions_with_difficulties(vec![0.3, 0.5, 0.7, 0.9]);
         let session = create_session_with_responses(
             &questions,
@@ -90,7 +102,9 @@ mod scoring_strategy_tests {
 
     #[test]
     fn test_simple_scoring_empty() {
-        let strategy = ScoringStrategy::Simple;
+        let strategy = ScoringStrategy::Simple {
+            hint_penalty_per_use: 0.0,
+        };
         let questions = create_questions_with_difficulties(vec![0.5]);
         let session = QuizSession::new(Uuid::new_v4(), None);
 
@@ -152,6 +166,7 @@ mod scoring_strategy_tests {
             easy_multiplier: 1.0,
             medium_multiplier: 1.5,
             hard_multiplier: 2.0,
+            buckets: DifficultyBuckets::default(),
         };
 
         // Easy (< 0.33), Medium (0.33-0.67), Hard (>= 0.67)
@@ -176,6 +191,7 @@ mod scoring_strategy_tests {
             easy_multiplier: 1.0,
             medium_multiplier: 1.5,
             hard_multiplier: 2.0,
+            buckets: DifficultyBuckets::default(),
         };
 
         // Answer hard questions correctly, miss easy ones
@@ -204,6 +220,7 @@ mod scoring_strategy_tests {
             easy_multiplier: 1.0,
             medium_multiplier: 1.5,
             hard_multiplier: 2.0,
+            buckets: DifficultyBuckets::default(),
         };
 
         let questions = create_questions_with_difficulties(vec![0.2, 0.5, 0.8]);
@@ -231,6 +248,8 @@ mod scoring_strategy_tests {
             difficulty_weight: 0.3,
             streak_weight: 0.2,
             consistency_weight: 0.1,
+            buckets: DifficultyBuckets::default(),
+            hint_penalty_per_use: 0.0,
         };
 
         let questions = create_questions_with_difficulties(vec![0.3, 0.5, 0.7, 0.8]);
@@ -260,6 +279,8 @@ mod scoring_strategy_tests {
             difficulty_weight: 0.0,
             streak_weight: 1.0,
             consistency_weight: 0.0,
+            buckets: DifficultyBuckets::default(),
+            hint_penalty_per_use: 0.0,
         };
 
         let questions = create_questions_with_difficulties(vec![0.5; 6]);
@@ -288,6 +309,8 @@ mod scoring_strategy_tests {
             difficulty_weight: 0.0,
             streak_weight: 0.0,
             consistency_weight: 1.0,
+            buckets: DifficultyBuckets::default(),
+            hint_penalty_per_use: 0.0,
         };
 
         let questions = create_questions_with_difficulties(vec![0.5; 4]);
@@ -317,6 +340,8 @@ mod scoring_strategy_tests {
             difficulty_weight: 0.0,
             streak_weight: 0.0,
             consistency_weight: 0.0,
+            buckets: DifficultyBuckets::default(),
+            hint_penalty_per_use: 0.0,
         };
 
         let questions = create_questions_with_difficulties(vec![0.5, 0.5]);
@@ -332,7 +357,9 @@ mod scoring_strategy_tests {
     #[test]
     fn test_scoring_with_no_responses() {
         let strategies = vec![
-            ScoringStrategy::Simple,
+            ScoringStrategy::Simple {
+                hint_penalty_per_use: 0.0,
+            },
             ScoringStrategy::TimeWeighted {
                 base_time_seconds: 60,
                 penalty_per_second: 0.01,
@@ -341,12 +368,15 @@ mod scoring_strategy_tests {
                 easy_multiplier: 1.0,
                 medium_multiplier: 1.5,
                 hard_multiplier: 2.0,
+                buckets: DifficultyBuckets::default(),
             },
             ScoringStrategy::Adaptive {
                 time_weight: 0.5,
                 difficulty_weight: 0.5,
                 streak_weight: 0.5,
                 consistency_weight: 0.5,
+                buckets: DifficultyBuckets::default(),
+                hint_penalty_per_use: 0.0,
             },
         ];
 
@@ -371,6 +401,8 @@ mod scoring_strategy_tests {
             difficulty_weight: 0.3,
             streak_weight: 0.2,
             consistency_weight: 0.2,
+            buckets: DifficultyBuckets::default(),
+            hint_penalty_per_use: 0.0,
         };
 
         let questions = create_questions_with_difficulties(vec![0.5]);
@@ -389,7 +421,9 @@ mod scoring_strategy_tests {
     #[test]
     fn test_scoring_preserves_percentile_field() {
         // Test that percentile field is available for future use
-        let strategy = ScoringStrategy::Simple;
+        let strategy = ScoringStrategy::Simple {
+            hint_penalty_per_use: 0.0,
+        };
         let questions = create_questions_with_difficulties(vec![0.5]);
         let session = create_session_with_responses(&questions, vec![true], vec![60]);
 
@@ -397,4 +431,449 @@ mod scoring_strategy_tests {
 
         assert!(score.percentile.is_none()); // Not implemented yet
     }
+
+    #[test]
+    fn test_score_as_metrics_key_set() {
+        let strategy = ScoringStrategy::Adaptive {
+            time_weight: 0.2,
+            difficulty_weight: 0.3,
+            streak_weight: 0.2,
+            consistency_weight: 0.1,
+            buckets: DifficultyBuckets::default(),
+            hint_penalty_per_use: 0.0,
+        };
+        let questions = create_questions_with_difficulties(vec![0.3, 0.7]);
+        let session = create_session_with_responses(&questions, vec![true, false], vec![50, 70]);
+
+        let mut score = strategy.calculate_score(&session, &questions);
+        let metrics = score.as_metrics();
+
+        let keys: Vec<&str> = metrics.keys().map(String::as_str).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "bonus.difficulty",
+                "bonus.streak",
+                "bonus.time",
+                "component.consistency",
+                "component.correctness",
+                "component.difficulty",
+                "component.speed",
+                "score.raw",
+                "score.weighted",
+            ]
+        );
+        assert_eq!(metrics["score.raw"], score.raw_score);
+        assert_eq!(metrics["component.speed"], score.components.speed);
+
+        score.percentile = Some(0.9);
+        assert_eq!(score.as_metrics().get("score.percentile"), Some(&0.9));
+    }
+
+    #[test]
+    fn test_calculate_score_checked_orphaned_response() {
+        let questions = create_questions_with_difficulties(vec![0.3, 0.5]);
+        let mut session = create_session_with_responses(&questions, vec![true, true], vec![30, 30]);
+
+        let strategy = ScoringStrategy::Simple {
+            hint_penalty_per_use: 0.0,
+        };
+        assert!(strategy
+            .calculate_score_checked(&session, &questions)
+            .is_ok());
+
+        let orphan_id = Uuid::new_v4();
+        let mut orphan = session.responses[0].clone();
+        orphan.question_id = orphan_id;
+        session.responses.push(orphan);
+
+        match strategy.calculate_score_checked(&session, &questions) {
+            Err(QuizlrError::NotFound(message)) => {
+                assert!(message.contains(&orphan_id.to_string()))
+            }
+            other => panic!("expected NotFound, got {:?}", other),
+        }
+
+        // The lenient method still scores, ignoring the orphan
+        let score = strategy.calculate_score(&session, &questions);
+        assert_eq!(score.raw_score, 1.0);
+        assert_eq!(score.weighted_score, 1.0);
+
+        // An orphan can't make up for a wrong answer to a real question
+        session.responses[1].is_correct = false;
+        let score = strategy.calculate_score(&session, &questions);
+        assert_eq!(score.raw_score, 0.5);
+        assert_eq!(score.weighted_score, 0.5);
+    }
+
+    #[test]
+    fn test_calculate_scores_matches_individual_scoring() {
+        let questions = create_questions_with_difficulties(vec![0.2, 0.5, 0.8, 0.4]);
+        let sessions: Vec<QuizSession> = (0..200)
+            .map(|i| {
+                let correct = (0..4).map(|q| (i + q) % 3 != 0).collect();
+                let times = (0..4)
+                    .map(|q| 10 + ((i * 7 + q * 13) % 90) as u32)
+                    .collect();
+                create_session_with_responses(&questions, correct, times)
+            })
+            .collect();
+
+        let strategy = ScoringStrategy::Adaptive {
+            time_weight: 0.2,
+            difficulty_weight: 0.3,
+            streak_weight: 0.2,
+            consistency_weight: 0.3,
+            buckets: DifficultyBuckets::default(),
+            hint_penalty_per_use: 0.0,
+        };
+
+        let serial = strategy.calculate_scores(&sessions, &questions);
+        assert_eq!(serial.len(), sessions.len());
+        for (session, score) in sessions.iter().zip(&serial) {
+            let single = strategy.calculate_score(session, &questions);
+            assert_eq!(score.raw_score, single.raw_score);
+            assert_eq!(score.weighted_score, single.weighted_score);
+        }
+
+        #[cfg(feature = "native")]
+        {
+            let parallel = strategy.calculate_scores_par(&sessions, &questions);
+            assert_eq!(parallel.len(), serial.len());
+            for (p, s) in parallel.iter().zip(&serial) {
+                assert_eq!(p.raw_score, s.raw_score);
+                assert_eq!(p.weighted_score, s.weighted_score);
+                assert_eq!(p.time_bonus, s.time_bonus);
+                assert_eq!(p.difficulty_bonus, s.difficulty_bonus);
+                assert_eq!(p.streak_bonus, s.streak_bonus);
+            }
+        }
+    }
+
+    #[test]
+    fn test_strategies_share_difficulty_buckets() {
+        let weighted = ScoringStrategy::DifficultyWeighted {
+            easy_multiplier: 1.0,
+            medium_multiplier: 2.0,
+            hard_multiplier: 3.0,
+            buckets: DifficultyBuckets::default(),
+        };
+        let adaptive = ScoringStrategy::Adaptive {
+            time_weight: 0.25,
+            difficulty_weight: 0.25,
+            streak_weight: 0.25,
+            consistency_weight: 0.25,
+            buckets: DifficultyBuckets::default(),
+            hint_penalty_per_use: 0.0,
+        };
+
+        assert_eq!(
+            weighted.difficulty_level(0.5),
+            Some(DifficultyLevel::Medium)
+        );
+        assert_eq!(
+            adaptive.difficulty_level(0.5),
+            Some(DifficultyLevel::Medium)
+        );
+        assert_eq!(
+            ScoringStrategy::Simple {
+                hint_penalty_per_use: 0.0,
+            }
+            .difficulty_level(0.5),
+            None
+        );
+
+        // Default boundaries match the historical 0.33 / 0.67 split
+        let buckets = DifficultyBuckets::default();
+        assert_eq!(buckets.level(0.32), DifficultyLevel::Easy);
+        assert_eq!(buckets.level(0.33), DifficultyLevel::Medium);
+        assert_eq!(buckets.level(0.67), DifficultyLevel::Hard);
+
+        // Moving the boundary moves the question for the weighted strategy
+        let strict = DifficultyBuckets {
+            easy_max: 0.2,
+            hard_min: 0.5,
+        };
+        let questions = create_questions_with_difficulties(vec![0.5, 0.1]);
+        let session = create_session_with_responses(&questions, vec![true, false], vec![30, 30]);
+        let strict_weighted = ScoringStrategy::DifficultyWeighted {
+            easy_multiplier: 1.0,
+            medium_multiplier: 2.0,
+            hard_multiplier: 3.0,
+            buckets: strict,
+        };
+        assert_eq!(
+            strict_weighted.difficulty_level(0.5),
+            Some(DifficultyLevel::Hard)
+        );
+        assert_eq!(
+            weighted
+                .calculate_score(&session, &questions)
+                .weighted_score,
+            2.0 / 3.0
+        );
+        assert_eq!(
+            strict_weighted
+                .calculate_score(&session, &questions)
+                .weighted_score,
+            3.0 / 4.0
+        );
+    }
+
+    #[test]
+    fn test_difficulty_buckets_default_when_missing_from_json() {
+        let json = r#"{"DifficultyWeighted":{"easy_multiplier":1.0,"medium_multiplier":1.5,"hard_multiplier":2.0}}"#;
+        let strategy: ScoringStrategy = serde_json::from_str(json).unwrap();
+        assert_eq!(strategy.difficulty_level(0.7), Some(DifficultyLevel::Hard));
+    }
+
+    #[test]
+    fn test_attempt_weighted_first_and_third_attempt() {
+        let questions = create_questions_with_difficulties(vec![0.5, 0.5]);
+        let mut session = QuizSession::new(Uuid::new_v4(), None);
+        session.start().unwrap();
+
+        // First question right on the first try
+        session
+            .submit_answer(&questions[0], Answer::TrueFalse(true), 10)
+            .unwrap();
+        // Second question right on the third try
+        session
+            .submit_answer(&questions[1], Answer::TrueFalse(false), 10)
+            .unwrap();
+        session
+            .submit_answer(&questions[1], Answer::TrueFalse(false), 10)
+            .unwrap();
+        session
+            .submit_answer(&questions[1], Answer::TrueFalse(true), 10)
+            .unwrap();
+        assert_eq!(session.responses[1].attempts, 3);
+
+        let strategy = ScoringStrategy::AttemptWeighted {
+            decay_per_attempt: 0.25,
+        };
+        let score = strategy.calculate_score(&session, &questions);
+        assert_eq!(score.raw_score, 1.0);
+        assert_eq!(score.weighted_score, (1.0 + 0.5) / 2.0);
+
+        // Credit never goes negative
+        let harsh = ScoringStrategy::AttemptWeighted {
+            decay_per_attempt: 0.75,
+        };
+        assert_eq!(
+            harsh.calculate_score(&session, &questions).weighted_score,
+            0.5
+        );
+    }
+
+    #[test]
+    fn test_weighted_pass_differs_from_simple_pass() {
+        // Same simple score either way; which questions were right decides
+        // the weighted outcome
+        let questions = create_questions_with_difficulties(vec![0.1, 0.9, 0.9]);
+        let strategy = ScoringStrategy::DifficultyWeighted {
+            easy_multiplier: 1.0,
+            medium_multiplier: 2.0,
+            hard_multiplier: 3.0,
+            buckets: DifficultyBuckets::default(),
+        };
+
+        // 2/3 simple, 6/7 weighted
+        let hard_right =
+            create_session_with_responses(&questions, vec![false, true, true], vec![30, 30, 30]);
+        assert!(!hard_right.generate_summary().passed(0.8));
+        assert!(strategy.passed(&hard_right, &questions, 0.8));
+
+        // 2/3 simple, 4/7 weighted
+        let easy_right =
+            create_session_with_responses(&questions, vec![true, true, false], vec![30, 30, 30]);
+        assert!(easy_right.generate_summary().passed(0.6));
+        assert!(!strategy.passed(&easy_right, &questions, 0.6));
+    }
+
+    #[test]
+    fn test_untimed_sessions_have_no_time_penalty() {
+        let questions = create_questions_with_difficulties(vec![0.5, 0.5]);
+        let mut session =
+            create_session_with_responses(&questions, vec![true, true], vec![600, 900]);
+
+        let time_weighted = ScoringStrategy::TimeWeighted {
+            base_time_seconds: 60,
+            penalty_per_second: 0.01,
+        };
+        assert!(
+            time_weighted
+                .calculate_score(&session, &questions)
+                .weighted_score
+                < 1.0
+        );
+
+        session.timing_mode = Some(TimingMode::Untimed);
+        let score = time_weighted.calculate_score(&session, &questions);
+        assert_eq!(score.weighted_score, 1.0);
+        assert_eq!(score.time_bonus, 0.0);
+
+        // Adaptive drops the speed component rather than scoring it low
+        let adaptive = ScoringStrategy::Adaptive {
+            time_weight: 0.5,
+            difficulty_weight: 0.0,
+            streak_weight: 0.0,
+            consistency_weight: 0.0,
+            buckets: DifficultyBuckets::default(),
+            hint_penalty_per_use: 0.0,
+        };
+        let score = adaptive.calculate_score(&session, &questions);
+        assert_eq!(score.components.speed, 0.0);
+        assert_eq!(score.time_bonus, 0.0);
+        assert_eq!(score.weighted_score, score.raw_score);
+
+        // Timed modes keep the penalty, whatever their limit
+        session.timing_mode = Some(TimingMode::Total(3600));
+        assert!(
+            time_weighted
+                .calculate_score(&session, &questions)
+                .weighted_score
+                < 1.0
+        );
+    }
+
+    #[test]
+    fn test_sessions_without_timing_mode_keep_time_scoring() {
+        let questions = create_questions_with_difficulties(vec![0.5, 0.5]);
+        let session = create_session_with_responses(&questions, vec![true, true], vec![600, 900]);
+        assert_eq!(
+            QuizSession::new(Uuid::new_v4(), None).timing_mode,
+            Some(TimingMode::Untimed)
+        );
+
+        // A session stored before timing modes existed has no such field,
+        // and is scored as timed
+        let mut stored = serde_json::to_value(&session).unwrap();
+        stored.as_object_mut().unwrap().remove("timing_mode");
+        let restored: QuizSession = serde_json::from_value(stored).unwrap();
+        assert_eq!(restored.timing_mode, None);
+        assert!(restored.is_timed());
+
+        let time_weighted = ScoringStrategy::TimeWeighted {
+            base_time_seconds: 60,
+            penalty_per_second: 0.01,
+        };
+        let before = time_weighted.calculate_score(&session, &questions);
+        let after = time_weighted.calculate_score(&restored, &questions);
+        assert!(after.weighted_score < 1.0);
+        assert_eq!(after.weighted_score, before.weighted_score);
+    }
+
+    #[test]
+    fn test_z_score_against_known_cohort() {
+        // Mean 5, population standard deviation 2
+        let cohort = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        assert!((z_score(9.0, &cohort).unwrap() - 2.0).abs() < 1e-6);
+        assert!((z_score(3.0, &cohort).unwrap() + 1.0).abs() < 1e-6);
+        assert_eq!(z_score(5.0, &cohort), Some(0.0));
+
+        assert_eq!(z_score(1.0, &[0.5]), None);
+        assert_eq!(z_score(1.0, &[0.5, 0.5, 0.5]), None);
+    }
+
+    #[test]
+    fn test_cohort_scoring_fills_percentile_and_z_score() {
+        let questions = create_questions_with_difficulties(vec![0.5, 0.5]);
+        let session = create_session_with_responses(&questions, vec![true, false], vec![30, 30]);
+        let cohort = [0.0, 0.25, 0.5, 0.75, 1.0];
+
+        let score = ScoringStrategy::Simple {
+            hint_penalty_per_use: 0.0,
+        }
+        .calculate_score_in_cohort(&session, &questions, &cohort);
+        assert_eq!(score.weighted_score, 0.5);
+        assert_eq!(score.percentile, Some(50.0));
+        assert_eq!(score.z_score, Some(0.0));
+        assert_eq!(score.as_metrics()["score.z"], 0.0);
+
+        let alone = ScoringStrategy::Simple {
+            hint_penalty_per_use: 0.0,
+        }
+        .calculate_score_in_cohort(&session, &questions, &[]);
+        assert_eq!(alone.percentile, None);
+        assert_eq!(alone.z_score, None);
+    }
+
+    #[test]
+    fn test_hint_penalty_reduces_correct_credit() {
+        use crate::quiz::session::QuizSessionBuilder;
+        use crate::quiz::ScoreAccumulator;
+
+        let questions = create_questions_with_difficulties(vec![0.5, 0.5]);
+        let answer_both = |hints_on_first: u32| {
+            let mut session = QuizSessionBuilder::new(Uuid::new_v4()).build();
+            session.start().unwrap();
+            for _ in 0..hints_on_first {
+                session.record_hint(questions[0].id).unwrap();
+            }
+            for question in &questions {
+                session
+                    .submit_answer(question, Answer::TrueFalse(true), 30)
+                    .unwrap();
+            }
+            session
+        };
+        let unhinted = answer_both(0);
+        let hinted = answer_both(2);
+        assert_eq!(hinted.responses[0].hints_used, 2);
+        assert_eq!(hinted.responses[1].hints_used, 0);
+
+        // Hints can't be taken after the fact on an answered question
+        let mut answered = answer_both(0);
+        assert!(answered.record_hint(questions[0].id).is_err());
+        assert_eq!(answered.responses[0].hints_used, 0);
+
+        let simple = ScoringStrategy::Simple {
+            hint_penalty_per_use: 0.25,
+        };
+        let plain = simple.calculate_score(&unhinted, &questions);
+        let docked = simple.calculate_score(&hinted, &questions);
+        assert_eq!(plain.weighted_score, 1.0);
+        // First answer keeps half its credit after two hints
+        assert_eq!(docked.weighted_score, 0.75);
+        assert_eq!(docked.raw_score, 1.0);
+
+        // Without a penalty, hints cost nothing
+        let lenient = ScoringStrategy::Simple {
+            hint_penalty_per_use: 0.0,
+        };
+        assert_eq!(
+            lenient.calculate_score(&hinted, &questions).weighted_score,
+            1.0
+        );
+
+        let adaptive = ScoringStrategy::Adaptive {
+            time_weight: 0.2,
+            difficulty_weight: 0.3,
+            streak_weight: 0.2,
+            consistency_weight: 0.1,
+            buckets: DifficultyBuckets::default(),
+            hint_penalty_per_use: 0.25,
+        };
+        let plain = adaptive.calculate_score(&unhinted, &questions);
+        let docked = adaptive.calculate_score(&hinted, &questions);
+        assert!(docked.weighted_score < plain.weighted_score);
+
+        // Penalties clamp at zero credit rather than going negative
+        let many = answer_both(10);
+        assert_eq!(
+            simple.calculate_score(&many, &questions).weighted_score,
+            0.5
+        );
+
+        // The running accumulator applies the same penalty
+        for strategy in [simple, adaptive] {
+            let mut accumulator = ScoreAccumulator::new(strategy.clone(), &questions, &hinted);
+            for (response, question) in hinted.responses.iter().zip(&questions) {
+                accumulator.push(response, question);
+            }
+            let batch = strategy.calculate_score(&hinted, &questions);
+            assert!((accumulator.current().weighted_score - batch.weighted_score).abs() < 1e-5);
+        }
+    }
 }
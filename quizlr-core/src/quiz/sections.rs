@@ -0,0 +1,159 @@
+//! Per-section results for exams that must be passed section by section
+
+use super::quiz_impl::Quiz;
+use super::session::QuizSession;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SectionResult {
+    pub correct_answers: usize,
+    pub total_questions: usize,
+    pub score: f32,
+    pub threshold: f32, // The threshold this section was judged against
+    pub weight: f32,    // Share of the section in the weighted aggregate
+    pub passed: bool,
+}
+
+/// Pass thresholds and weights for individual sections. A section without
+/// a threshold is judged against the quiz's pass threshold, and one without
+/// a weight counts once in [`weighted_section_score`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SectionPolicy {
+    pub thresholds: HashMap<String, f32>,
+    pub weights: HashMap<String, f32>,
+}
+
+impl SectionPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn threshold(mut self, section: impl Into<String>, threshold: f32) -> Self {
+        self.thresholds.insert(section.into(), threshold);
+        self
+    }
+
+    /// Negative weights are treated as 0, leaving the section out of the
+    /// aggregate
+    pub fn weight(mut self, section: impl Into<String>, weight: f32) -> Self {
+        self.weights.insert(section.into(), weight.max(0.0));
+        self
+    }
+
+    fn threshold_for(&self, section: &str, quiz: &Quiz) -> f32 {
+        self.thresholds
+            .get(section)
+            .copied()
+            .unwrap_or(quiz.pass_threshold)
+    }
+
+    fn weight_for(&self, section: &str) -> f32 {
+        self.weights.get(section).copied().unwrap_or(1.0).max(0.0)
+    }
+}
+
+/// Group a quiz's questions into sections by tag. A question with several
+/// tags belongs to each of those sections.
+pub fn sections_by_tag(quiz: &Quiz) -> HashMap<String, Vec<Uuid>> {
+    let mut sections: HashMap<String, Vec<Uuid>> = HashMap::new();
+    for question in &quiz.questions {
+        for tag in &question.tags {
+            sections.entry(tag.clone()).or_default().push(question.id);
+        }
+    }
+    sections
+}
+
+/// Score each section of `quiz` separately and check it against its
+/// threshold under `policy`. Unanswered questions count against their
+/// section, and ids not in the quiz are ignored.
+pub fn section_results(
+    session: &QuizSession,
+    quiz: &Quiz,
+    sections: &HashMap<String, Vec<Uuid>>,
+    policy: &SectionPolicy,
+) -> HashMap<String, SectionResult> {
+    sections
+        .iter()
+        .map(|(name, question_ids)| {
+            let in_quiz: Vec<&Uuid> = question_ids
+                .iter()
+                .filter(|id| quiz.questions.iter().any(|q| q.id == **id))
+                .collect();
+            let correct_answers = in_quiz
+                .iter()
+                .filter(|id| {
+                    session
+                        .responses
+                        .iter()
+                        .any(|r| r.question_id == ***id && r.is_correct)
+                })
+                .count();
+            let total_questions = in_quiz.len();
+            let score = if total_questions > 0 {
+                correct_answers as f32 / total_questions as f32
+            } else {
+                0.0
+            };
+            let threshold = policy.threshold_for(name, quiz);
+
+            (
+                name.clone(),
+                SectionResult {
+                    correct_answers,
+                    total_questions,
+                    score,
+                    threshold,
+                    weight: policy.weight_for(name),
+                    passed: score >= threshold,
+                },
+            )
+        })
+        .collect()
+}
+
+/// [`sections_by_tag`] as a list sorted by tag, for stable display
+pub fn sections_by_tag_sorted(quiz: &Quiz) -> Vec<(String, Vec<Uuid>)> {
+    let mut sections: Vec<_> = sections_by_tag(quiz).into_iter().collect();
+    sections.sort_by(|a, b| a.0.cmp(&b.0));
+    sections
+}
+
+/// [`section_results`] as a list sorted by descending score, then by
+/// section name, so the strongest sections come first in a stable order
+pub fn section_results_sorted(
+    session: &QuizSession,
+    quiz: &Quiz,
+    sections: &HashMap<String, Vec<Uuid>>,
+    policy: &SectionPolicy,
+) -> Vec<(String, SectionResult)> {
+    let mut results: Vec<_> = section_results(session, quiz, sections, policy)
+        .into_iter()
+        .collect();
+    results.sort_by(|a, b| b.1.score.total_cmp(&a.1.score).then_with(|| a.0.cmp(&b.0)));
+    results
+}
+
+pub fn passed_all_sections(results: &HashMap<String, SectionResult>) -> bool {
+    results.values().all(|r| r.passed)
+}
+
+/// Overall score as the weighted mean of the section scores. Sections with
+/// no questions in the quiz are left out; with nothing left to weigh the
+/// score is 0.0.
+pub fn weighted_section_score(results: &HashMap<String, SectionResult>) -> f32 {
+    let (weighted_sum, total_weight) = results
+        .values()
+        .filter(|r| r.total_questions > 0 && r.weight > 0.0)
+        .fold((0.0, 0.0), |(sum, total), r| {
+            (sum + r.score * r.weight, total + r.weight)
+        });
+
+    if total_weight > 0.0 {
+        weighted_sum / total_weight
+    } else {
+        0.0
+    }
+}
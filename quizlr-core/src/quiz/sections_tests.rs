@@ -0,0 +1,192 @@
+//! Tests for per-section results
+//!
+//! DEVNOTES: Exams can require passing every section as well as the whole
+//! quiz, with each section held to its own threshold and weight
+
+use crate::quiz::question::{Answer, Question, QuestionType};
+use crate::quiz::quiz_impl::{Quiz, QuizBuilder};
+use crate::quiz::sections::{
+    passed_all_sections, section_results, section_results_sorted, sections_by_tag,
+    sections_by_tag_sorted, weighted_section_score, SectionPolicy,
+};
+use crate::quiz::session::QuizSession;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod section_tests {
+    use super::*;
+
+    fn create_test_question() -> Question {
+        Question::new(
+            QuestionType::TrueFalse {
+                statement: "Test statement".to_string(),
+                correct_answer: true,
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+        )
+    }
+
+    /// Eight reading questions all answered right and two math questions
+    /// with one right, for 9 / 10 overall
+    fn reading_and_math() -> (Quiz, QuizSession) {
+        let mut questions: Vec<Question> = (0..10).map(|_| create_test_question()).collect();
+        for (i, question) in questions.iter_mut().enumerate() {
+            let section = if i < 8 { "reading" } else { "math" };
+            question.tags.push(section.to_string());
+        }
+        let quiz = QuizBuilder::new("Sectioned".to_string())
+            .pass_threshold(0.7)
+            .add_questions(questions.clone())
+            .build();
+
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+        for (i, question) in questions.iter().enumerate() {
+            session
+                .submit_answer(question, Answer::TrueFalse(i != 9), 10)
+                .unwrap();
+        }
+        (quiz, session)
+    }
+
+    #[test]
+    fn test_overall_pass_with_failed_section() {
+        let (quiz, mut session) = reading_and_math();
+
+        let summary = session.complete().unwrap();
+        assert!(summary.passed(quiz.pass_threshold));
+
+        let results = section_results(
+            &session,
+            &quiz,
+            &sections_by_tag(&quiz),
+            &SectionPolicy::default(),
+        );
+        assert_eq!(results.len(), 2);
+        assert!(results["reading"].passed);
+        assert_eq!(results["reading"].score, 1.0);
+        assert!(!results["math"].passed);
+        assert_eq!(results["math"].correct_answers, 1);
+        assert_eq!(results["math"].total_questions, 2);
+        assert!(!passed_all_sections(&results));
+    }
+
+    #[test]
+    fn test_sorted_sections_are_stable() {
+        let mut questions: Vec<Question> = (0..6).map(|_| create_test_question()).collect();
+        for (question, section) in questions
+            .iter_mut()
+            .zip(["math", "art", "math", "history", "art", "history"])
+        {
+            question.tags.push(section.to_string());
+        }
+        let quiz = QuizBuilder::new("Sectioned".to_string())
+            .add_questions(questions.clone())
+            .build();
+
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+        // math 2/2, art 1/2, history 0/2
+        for (i, question) in questions.iter().enumerate() {
+            session
+                .submit_answer(question, Answer::TrueFalse(i < 3), 10)
+                .unwrap();
+        }
+
+        let tags: Vec<String> = sections_by_tag_sorted(&quiz)
+            .into_iter()
+            .map(|(tag, _)| tag)
+            .collect();
+        assert_eq!(tags, vec!["art", "history", "math"]);
+
+        let sections = sections_by_tag(&quiz);
+        let policy = SectionPolicy::default();
+        let order = |results: Vec<(String, _)>| {
+            results
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<String>>()
+        };
+        let first = order(section_results_sorted(&session, &quiz, &sections, &policy));
+        assert_eq!(first, vec!["math", "art", "history"]);
+        for _ in 0..10 {
+            let rebuilt = sections_by_tag(&quiz);
+            assert_eq!(
+                order(section_results_sorted(&session, &quiz, &rebuilt, &policy)),
+                first
+            );
+        }
+    }
+
+    #[test]
+    fn test_unanswered_section_questions_count_against_section() {
+        let q1 = create_test_question();
+        let q2 = create_test_question();
+        let quiz = QuizBuilder::new("Sectioned".to_string())
+            .add_questions(vec![q1.clone(), q2.clone()])
+            .build();
+
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+        session
+            .submit_answer(&q1, Answer::TrueFalse(true), 10)
+            .unwrap();
+
+        let sections = HashMap::from([("all".to_string(), vec![q1.id, q2.id, Uuid::new_v4()])]);
+        let results = section_results(&session, &quiz, &sections, &SectionPolicy::default());
+        assert_eq!(results["all"].total_questions, 2);
+        assert_eq!(results["all"].score, 0.5);
+        assert!(!passed_all_sections(&results));
+    }
+
+    #[test]
+    fn test_per_section_threshold_overrides_quiz_threshold() {
+        let (quiz, session) = reading_and_math();
+        let sections = sections_by_tag(&quiz);
+
+        // Math only needs half right; reading must be perfect
+        let policy = SectionPolicy::new()
+            .threshold("math", 0.5)
+            .threshold("reading", 1.0);
+        let results = section_results(&session, &quiz, &sections, &policy);
+        assert!(results["math"].passed);
+        assert_eq!(results["math"].threshold, 0.5);
+        assert!(results["reading"].passed);
+        assert!(passed_all_sections(&results));
+
+        // Sections without an entry fall back to the quiz threshold
+        let policy = SectionPolicy::new().threshold("reading", 0.9);
+        let results = section_results(&session, &quiz, &sections, &policy);
+        assert_eq!(results["math"].threshold, quiz.pass_threshold);
+        assert!(!results["math"].passed);
+        assert!(!passed_all_sections(&results));
+    }
+
+    #[test]
+    fn test_weighted_section_score() {
+        let (quiz, session) = reading_and_math();
+        let mut sections = sections_by_tag(&quiz);
+
+        // Unweighted, each section counts once: (1.0 + 0.5) / 2
+        let results = section_results(&session, &quiz, &sections, &SectionPolicy::default());
+        assert!((weighted_section_score(&results) - 0.75).abs() < 1e-6);
+
+        // Math counts three times as much: (1.0 + 3 * 0.5) / 4
+        let policy = SectionPolicy::new().weight("math", 3.0);
+        let results = section_results(&session, &quiz, &sections, &policy);
+        assert_eq!(results["math"].weight, 3.0);
+        assert!((weighted_section_score(&results) - 0.625).abs() < 1e-6);
+
+        // Zero-weight and empty sections drop out of the aggregate
+        sections.insert("empty".to_string(), vec![Uuid::new_v4()]);
+        let policy = SectionPolicy::new()
+            .weight("math", 0.0)
+            .weight("reading", -1.0);
+        let results = section_results(&session, &quiz, &sections, &policy);
+        assert_eq!(weighted_section_score(&results), 0.0);
+        assert_eq!(weighted_section_score(&HashMap::new()), 0.0);
+    }
+}
@@ -1,7 +1,11 @@
-use super::{Answer, Question};
+use super::localization::{EnglishLocalizer, Localizer};
+use super::{Answer, Question, QuestionType, Quiz, ScoringStrategy};
+use crate::clock::{system_clock, Clock};
+use crate::error::QuizlrError;
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -13,6 +17,61 @@ pub enum SessionState {
     Abandoned,
 }
 
+/// Why a session was abandoned, recorded for analytics
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AbandonReason {
+    NavigatedAway,
+    TimedOut,
+    UserQuit,
+    Unknown,
+}
+
+/// How long a session may go untouched before it expires. Paused sessions
+/// get their own, usually much longer, allowance.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExpiryPolicy {
+    pub max_idle_active: Duration,
+    pub max_paused: Duration,
+}
+
+impl Default for ExpiryPolicy {
+    fn default() -> Self {
+        Self {
+            max_idle_active: Duration::minutes(30),
+            max_paused: Duration::days(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ExpiryReason {
+    IdleTooLong,
+    PausedTooLong,
+}
+
+/// Whether answer speed counts. Under `Untimed` ("open book") scoring
+/// never penalizes slow answers; the timed modes carry their limit in
+/// seconds, per question or for the whole session. New sessions default to
+/// `Untimed`. Sessions saved before modes existed have none; they score
+/// speed as they always did, with no limit beyond `QuizSession::time_limit`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TimingMode {
+    Untimed,
+    PerQuestion(u32),
+    Total(u32),
+}
+
+impl TimingMode {
+    pub fn is_timed(&self) -> bool {
+        !matches!(self, TimingMode::Untimed)
+    }
+}
+
+/// Whether speed is scored under an optional mode; unset means timed
+pub(super) fn counts_speed(mode: Option<TimingMode>) -> bool {
+    mode.is_none_or(|mode| mode.is_timed())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuizSession {
     pub id: Uuid,
@@ -26,10 +85,25 @@ pub struct QuizSession {
     pub end_time: Option<DateTime<Utc>>,
     pub pause_duration: Duration,
     pub last_activity: DateTime<Utc>,
+    #[serde(default)]
+    pub time_limit: Option<Duration>,
+    #[serde(default)]
+    pub scoring_strategy: Option<ScoringStrategy>,
+    #[serde(default)]
+    pub abandon_reason: Option<AbandonReason>,
+    #[serde(default)]
+    pub timing_mode: Option<TimingMode>,
+    #[serde(default)]
+    pub pause_log: Vec<PauseRecord>,
+    #[serde(default)]
+    pending_hints: HashMap<Uuid, u32>, // Hints taken before the first answer
     pub metadata: HashMap<String, serde_json::Value>,
+    #[serde(skip, default = "system_clock")]
+    clock: Arc<dyn Clock>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredQuestionResponse")]
 pub struct QuestionResponse {
     pub question_id: Uuid,
     pub answer: Answer,
@@ -37,10 +111,106 @@ pub struct QuestionResponse {
     pub time_taken_seconds: u32,
     pub attempts: u32,
     pub submitted_at: DateTime<Utc>,
+    #[serde(default)]
+    pub attempt_history: Vec<AttemptRecord>,
+    #[serde(default)]
+    pub confidence: Option<f32>, // 0.0 to 1.0, self-reported
+    pub first_attempt_correct: bool, // Never overwritten by resubmissions
+    #[serde(default)]
+    pub shown_options: Option<Vec<String>>, // Option texts in the order displayed
+    #[serde(default)]
+    pub hints_used: u32,
+}
+
+/// Serialized form of [`QuestionResponse`], which may predate
+/// `first_attempt_correct`
+#[derive(Deserialize)]
+struct StoredQuestionResponse {
+    question_id: Uuid,
+    answer: Answer,
+    is_correct: bool,
+    time_taken_seconds: u32,
+    attempts: u32,
+    submitted_at: DateTime<Utc>,
+    #[serde(default)]
+    attempt_history: Vec<AttemptRecord>,
+    #[serde(default)]
+    confidence: Option<f32>,
+    #[serde(default)]
+    first_attempt_correct: Option<bool>,
+    #[serde(default)]
+    shown_options: Option<Vec<String>>,
+    #[serde(default)]
+    hints_used: u32,
+}
+
+impl From<StoredQuestionResponse> for QuestionResponse {
+    fn from(stored: StoredQuestionResponse) -> Self {
+        // Older responses didn't record the first attempt; recover it from
+        // the history, or from the final result when there was only one try
+        let first_attempt_correct =
+            stored
+                .first_attempt_correct
+                .unwrap_or_else(|| match stored.attempt_history.first() {
+                    Some(first) => first.is_correct,
+                    None => stored.attempts <= 1 && stored.is_correct,
+                });
+        Self {
+            question_id: stored.question_id,
+            answer: stored.answer,
+            is_correct: stored.is_correct,
+            time_taken_seconds: stored.time_taken_seconds,
+            attempts: stored.attempts,
+            submitted_at: stored.submitted_at,
+            attempt_history: stored.attempt_history,
+            confidence: stored.confidence,
+            first_attempt_correct,
+            shown_options: stored.shown_options,
+            hints_used: stored.hints_used,
+        }
+    }
+}
+
+impl QuestionResponse {
+    /// Whether the final answer was an explicit "I don't know" rather than
+    /// a wrong guess
+    pub fn is_dont_know(&self) -> bool {
+        matches!(self.answer, Answer::DontKnow)
+    }
+}
+
+/// A single submission for a question, kept so retries can be reviewed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttemptRecord {
+    pub answer: Answer,
+    pub is_correct: bool,
+    pub at: DateTime<Utc>,
+}
+
+/// One pause of a session, kept for proctoring audits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PauseRecord {
+    pub reason: String,
+    pub at: DateTime<Utc>,
+    pub resumed_at: Option<DateTime<Utc>>,
+}
+
+/// Outcome of submitting an answer by question id
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubmissionResult {
+    pub question_id: Uuid,
+    pub is_correct: bool,
+    pub attempts: u32,
 }
 
 impl QuizSession {
     pub fn new(quiz_id: Uuid, user_id: Option<Uuid>) -> Self {
+        Self::with_clock(quiz_id, user_id, system_clock())
+    }
+
+    /// Create a session that reads the time from `clock` instead of the
+    /// system clock
+    pub fn with_clock(quiz_id: Uuid, user_id: Option<Uuid>, clock: Arc<dyn Clock>) -> Self {
         Self {
             id: Uuid::new_v4(),
             quiz_id,
@@ -52,8 +222,15 @@ impl QuizSession {
             start_time: None,
             end_time: None,
             pause_duration: Duration::zero(),
-            last_activity: Utc::now(),
+            last_activity: clock.now(),
+            time_limit: None,
+            scoring_strategy: None,
+            abandon_reason: None,
+            timing_mode: Some(TimingMode::Untimed),
+            pause_log: Vec::new(),
+            pending_hints: HashMap::new(),
             metadata: HashMap::new(),
+            clock,
         }
     }
 
@@ -61,8 +238,8 @@ impl QuizSession {
         match self.state {
             SessionState::NotStarted => {
                 self.state = SessionState::InProgress;
-                self.start_time = Some(Utc::now());
-                self.last_activity = Utc::now();
+                self.start_time = Some(self.clock.now());
+                self.last_activity = self.clock.now();
                 Ok(())
             }
             _ => Err("Session already started".to_string()),
@@ -70,10 +247,21 @@ impl QuizSession {
     }
 
     pub fn pause(&mut self) -> Result<(), String> {
+        self.pause_with_reason(String::new())
+    }
+
+    /// Pause, recording why in the session's pause log
+    pub fn pause_with_reason(&mut self, reason: String) -> Result<(), String> {
         match self.state {
             SessionState::InProgress => {
+                let now = self.clock.now();
                 self.state = SessionState::Paused;
-                self.last_activity = Utc::now();
+                self.last_activity = now;
+                self.pause_log.push(PauseRecord {
+                    reason,
+                    at: now,
+                    resumed_at: None,
+                });
                 Ok(())
             }
             _ => Err("Can only pause an in-progress session".to_string()),
@@ -83,10 +271,13 @@ impl QuizSession {
     pub fn resume(&mut self) -> Result<(), String> {
         match self.state {
             SessionState::Paused => {
-                let pause_time = Utc::now() - self.last_activity;
-                self.pause_duration += pause_time;
+                let now = self.clock.now();
+                self.pause_duration += now - self.last_activity;
                 self.state = SessionState::InProgress;
-                self.last_activity = Utc::now();
+                self.last_activity = now;
+                if let Some(record) = self.pause_log.last_mut().filter(|r| r.resumed_at.is_none()) {
+                    record.resumed_at = Some(now);
+                }
                 Ok(())
             }
             _ => Err("Can only resume a paused session".to_string()),
@@ -98,11 +289,90 @@ impl QuizSession {
         question: &Question,
         answer: Answer,
         time_taken_seconds: u32,
+    ) -> Result<bool, String> {
+        self.submit_answer_with_confidence(question, answer, time_taken_seconds, None)
+    }
+
+    /// Submit an answer along with the learner's self-reported confidence
+    /// (0.0 to 1.0) that it is correct.
+    pub fn submit_answer_with_confidence(
+        &mut self,
+        question: &Question,
+        answer: Answer,
+        time_taken_seconds: u32,
+        confidence: Option<f32>,
+    ) -> Result<bool, String> {
+        self.record_submission(question, answer, time_taken_seconds, confidence, None)
+    }
+
+    /// Submit a multiple choice answer together with the option texts in
+    /// the order they were displayed, so a randomized shuffle can be
+    /// reconstructed when reviewing the response later.
+    ///
+    /// The answer's index refers to the displayed order. It is mapped back
+    /// to the question's own option order before grading, and stored that
+    /// way. `shown_options` must be a permutation of the question's options.
+    pub fn submit_answer_with_shown_options(
+        &mut self,
+        question: &Question,
+        answer: Answer,
+        time_taken_seconds: u32,
+        shown_options: Vec<String>,
+    ) -> Result<bool, String> {
+        let QuestionType::MultipleChoice { options, .. } = &question.question_type else {
+            return Err("Shown options only apply to multiple choice questions".to_string());
+        };
+        let mut expected: Vec<&String> = options.iter().collect();
+        let mut shown: Vec<&String> = shown_options.iter().collect();
+        expected.sort();
+        shown.sort();
+        if expected != shown {
+            return Err("Shown options must be a reordering of the question's options".to_string());
+        }
+
+        let answer = match answer {
+            Answer::MultipleChoice(displayed) => {
+                let text = shown_options
+                    .get(displayed)
+                    .ok_or_else(|| "Invalid option index".to_string())?;
+                let original = options
+                    .iter()
+                    .position(|option| option == text)
+                    .ok_or_else(|| {
+                        "Shown option is not one of the question's options".to_string()
+                    })?;
+                Answer::MultipleChoice(original)
+            }
+            other => other,
+        };
+
+        self.record_submission(
+            question,
+            answer,
+            time_taken_seconds,
+            None,
+            Some(shown_options),
+        )
+    }
+
+    fn record_submission(
+        &mut self,
+        question: &Question,
+        answer: Answer,
+        time_taken_seconds: u32,
+        confidence: Option<f32>,
+        shown_options: Option<Vec<String>>,
     ) -> Result<bool, String> {
         if self.state != SessionState::InProgress {
             return Err("Session is not in progress".to_string());
         }
 
+        if let Some(c) = confidence {
+            if !(0.0..=1.0).contains(&c) {
+                return Err("Confidence must be between 0.0 and 1.0".to_string());
+            }
+        }
+
         let is_correct = question.validate_answer(&answer)?;
 
         // Check if we already have a response for this question
@@ -111,12 +381,24 @@ impl QuizSession {
             .iter_mut()
             .find(|r| r.question_id == question.id);
 
+        let now = self.clock.now();
+        let record = AttemptRecord {
+            answer: answer.clone(),
+            is_correct,
+            at: now,
+        };
+
         if let Some(response) = existing_response {
             response.attempts += 1;
             response.answer = answer;
             response.is_correct = is_correct;
             response.time_taken_seconds += time_taken_seconds;
-            response.submitted_at = Utc::now();
+            response.submitted_at = now;
+            response.attempt_history.push(record);
+            response.confidence = confidence;
+            if shown_options.is_some() {
+                response.shown_options = shown_options;
+            }
         } else {
             self.responses.push(QuestionResponse {
                 question_id: question.id,
@@ -124,19 +406,106 @@ impl QuizSession {
                 is_correct,
                 time_taken_seconds,
                 attempts: 1,
-                submitted_at: Utc::now(),
+                submitted_at: now,
+                attempt_history: vec![record],
+                confidence,
+                first_attempt_correct: is_correct,
+                shown_options,
+                hints_used: self.pending_hints.remove(&question.id).unwrap_or(0),
             });
         }
 
-        self.last_activity = Utc::now();
+        self.last_activity = self.clock.now();
         Ok(is_correct)
     }
 
+    /// Record that a hint was shown for `question_id`, returning how many
+    /// hints that question has used. Hints are attached to the question's
+    /// response when it is submitted; once it has been answered, further
+    /// hints are rejected so they can't dock credit after the fact.
+    pub fn record_hint(&mut self, question_id: Uuid) -> Result<u32, String> {
+        if self.state != SessionState::InProgress {
+            return Err("Can only use hints in an in-progress session".to_string());
+        }
+        if self.responses.iter().any(|r| r.question_id == question_id) {
+            return Err("Question has already been answered".to_string());
+        }
+
+        let hints = self.pending_hints.entry(question_id).or_insert(0);
+        *hints += 1;
+        self.last_activity = self.clock.now();
+        Ok(*hints)
+    }
+
+    /// Submit an answer using only the question id, looking the question up
+    /// in `quiz`. Useful for thin frontends that don't hold full questions.
+    pub fn submit_answer_by_id(
+        &mut self,
+        quiz: &Quiz,
+        question_id: Uuid,
+        answer: Answer,
+        time_taken_seconds: u32,
+    ) -> crate::Result<SubmissionResult> {
+        let question = quiz
+            .questions
+            .iter()
+            .find(|q| q.id == question_id)
+            .ok_or_else(|| QuizlrError::NotFound(format!("Question {}", question_id)))?;
+
+        let is_correct = self
+            .submit_answer(question, answer, time_taken_seconds)
+            .map_err(QuizlrError::InvalidInput)?;
+
+        let attempts = self
+            .responses
+            .iter()
+            .find(|r| r.question_id == question_id)
+            .map(|r| r.attempts)
+            .unwrap_or(0);
+
+        Ok(SubmissionResult {
+            question_id,
+            is_correct,
+            attempts,
+        })
+    }
+
+    /// Every attempt made at a question, oldest first
+    pub fn attempt_history(&self, question_id: Uuid) -> &[AttemptRecord] {
+        self.responses
+            .iter()
+            .find(|r| r.question_id == question_id)
+            .map(|r| r.attempt_history.as_slice())
+            .unwrap_or(&[])
+    }
+
     pub fn skip_question(&mut self, question_index: usize) {
         if !self.skipped_questions.contains(&question_index) {
             self.skipped_questions.push(question_index);
         }
-        self.last_activity = Utc::now();
+        self.last_activity = self.clock.now();
+    }
+
+    /// Skip a question, honoring the quiz's `allow_skip` setting
+    pub fn skip_question_checked(
+        &mut self,
+        quiz: &Quiz,
+        question_index: usize,
+    ) -> crate::Result<()> {
+        if !quiz.allow_skip {
+            return Err(QuizlrError::InvalidInput(
+                "This quiz does not allow skipping questions".to_string(),
+            ));
+        }
+        if question_index >= quiz.questions.len() {
+            return Err(QuizlrError::InvalidInput(format!(
+                "Question index {} out of range",
+                question_index
+            )));
+        }
+
+        self.skip_question(question_index);
+        Ok(())
     }
 
     pub fn next_question(&mut self) -> Result<(), String> {
@@ -145,7 +514,7 @@ impl QuizSession {
         }
 
         self.current_question_index += 1;
-        self.last_activity = Utc::now();
+        self.last_activity = self.clock.now();
         Ok(())
     }
 
@@ -156,7 +525,7 @@ impl QuizSession {
 
         if self.current_question_index > 0 {
             self.current_question_index -= 1;
-            self.last_activity = Utc::now();
+            self.last_activity = self.clock.now();
             Ok(())
         } else {
             Err("Already at first question".to_string())
@@ -167,16 +536,65 @@ impl QuizSession {
         match self.state {
             SessionState::InProgress => {
                 self.state = SessionState::Completed;
-                self.end_time = Some(Utc::now());
+                self.end_time = Some(self.clock.now());
                 Ok(self.generate_summary())
             }
             _ => Err("Can only complete an in-progress session".to_string()),
         }
     }
 
+    /// Complete the session only if every question in `quiz` has either been
+    /// answered or explicitly skipped. Skips only count when the quiz allows
+    /// them. Intended for high-stakes exams.
+    pub fn complete_strict(&mut self, quiz: &Quiz) -> crate::Result<SessionSummary> {
+        let unanswered: Vec<usize> = quiz
+            .questions
+            .iter()
+            .enumerate()
+            .filter(|(index, question)| {
+                let skipped = quiz.allow_skip && self.skipped_questions.contains(index);
+                !skipped && !self.responses.iter().any(|r| r.question_id == question.id)
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        if !unanswered.is_empty() {
+            return Err(QuizlrError::InvalidInput(format!(
+                "Unanswered questions: {:?}",
+                unanswered
+            )));
+        }
+
+        self.complete().map_err(QuizlrError::QuizEngine)
+    }
+
     pub fn abandon(&mut self) {
+        self.abandon_with_reason(AbandonReason::Unknown);
+    }
+
+    pub fn abandon_with_reason(&mut self, reason: AbandonReason) {
         self.state = SessionState::Abandoned;
-        self.end_time = Some(Utc::now());
+        self.end_time = Some(self.clock.now());
+        self.abandon_reason = Some(reason);
+    }
+
+    /// Abandon the session if it has sat untouched longer than `policy`
+    /// allows for its state, returning which limit was hit. Only active and
+    /// paused sessions can expire.
+    pub fn expire_if_idle(
+        &mut self,
+        policy: &ExpiryPolicy,
+        now: DateTime<Utc>,
+    ) -> Option<ExpiryReason> {
+        let idle = now - self.last_activity;
+        let reason = match self.state {
+            SessionState::InProgress if idle > policy.max_idle_active => ExpiryReason::IdleTooLong,
+            SessionState::Paused if idle > policy.max_paused => ExpiryReason::PausedTooLong,
+            _ => return None,
+        };
+
+        self.abandon_with_reason(AbandonReason::TimedOut);
+        Some(reason)
     }
 
     pub fn generate_summary(&self) -> SessionSummary {
@@ -193,7 +611,7 @@ impl QuizSession {
         let duration = if let (Some(start), Some(end)) = (self.start_time, self.end_time) {
             end - start - self.pause_duration
         } else if let Some(start) = self.start_time {
-            Utc::now() - start - self.pause_duration
+            self.clock.now() - start - self.pause_duration
         } else {
             Duration::zero()
         };
@@ -217,9 +635,293 @@ impl QuizSession {
             } else {
                 0.0
             },
+            answered_question_ids: self.responses.iter().map(|r| r.question_id).collect(),
+            abandon_reason: self.abandon_reason,
+        }
+    }
+
+    /// Fraction of answered questions that were right on the first try,
+    /// regardless of later resubmissions
+    pub fn first_attempt_score(&self) -> f32 {
+        if self.responses.is_empty() {
+            return 0.0;
+        }
+
+        let first_correct = self
+            .responses
+            .iter()
+            .filter(|r| r.first_attempt_correct)
+            .count();
+        first_correct as f32 / self.responses.len() as f32
+    }
+
+    /// How many questions were answered "I don't know". These score as
+    /// incorrect but point at gaps in knowledge rather than mistakes.
+    pub fn dont_know_count(&self) -> usize {
+        self.responses.iter().filter(|r| r.is_dont_know()).count()
+    }
+
+    pub fn correct_responses(&self) -> impl Iterator<Item = &QuestionResponse> {
+        self.responses.iter().filter(|r| r.is_correct)
+    }
+
+    pub fn incorrect_responses(&self) -> impl Iterator<Item = &QuestionResponse> {
+        self.responses.iter().filter(|r| !r.is_correct)
+    }
+
+    /// Grade every response again against the quiz's current answer keys,
+    /// e.g. after importing a session or correcting a key. Attempt history
+    /// and first-attempt correctness are regraded too. Essay responses are
+    /// left as they are, since they are graded against a rubric. Returns
+    /// how many responses changed between correct and incorrect.
+    ///
+    /// Fails without changing anything if a response refers to a question
+    /// missing from `quiz` or its answer no longer fits the question.
+    pub fn regrade(&mut self, quiz: &Quiz) -> crate::Result<usize> {
+        let mut grades = Vec::with_capacity(self.responses.len());
+        for response in &self.responses {
+            let question = quiz
+                .questions
+                .iter()
+                .find(|q| q.id == response.question_id)
+                .ok_or_else(|| {
+                    QuizlrError::NotFound(format!("Question {}", response.question_id))
+                })?;
+            if matches!(question.question_type, QuestionType::Essay { .. }) {
+                grades.push(None);
+                continue;
+            }
+
+            let grade = |answer: &Answer| {
+                question
+                    .validate_answer(answer)
+                    .map_err(QuizlrError::InvalidInput)
+            };
+            let history = response
+                .attempt_history
+                .iter()
+                .map(|attempt| grade(&attempt.answer))
+                .collect::<crate::Result<Vec<bool>>>()?;
+            grades.push(Some((grade(&response.answer)?, history)));
+        }
+
+        let mut flipped = 0;
+        for (response, grade) in self.responses.iter_mut().zip(grades) {
+            let Some((is_correct, history)) = grade else {
+                continue;
+            };
+            if response.is_correct != is_correct {
+                flipped += 1;
+            }
+            response.is_correct = is_correct;
+            for (attempt, attempt_correct) in response.attempt_history.iter_mut().zip(&history) {
+                attempt.is_correct = *attempt_correct;
+            }
+            if let Some(&first) = history.first() {
+                response.first_attempt_correct = first;
+            }
+        }
+        Ok(flipped)
+    }
+
+    /// Quiz-order indices of the questions unlocked so far: those with no
+    /// unlock dependency, and those whose prerequisite has been answered
+    /// (correctly, when the question asks for it). Answered questions stay
+    /// available.
+    pub fn available_questions(&self, quiz: &Quiz) -> Vec<usize> {
+        quiz.questions
+            .iter()
+            .enumerate()
+            .filter(|(_, question)| match question.unlock_after {
+                None => true,
+                Some(prerequisite) => self
+                    .responses
+                    .iter()
+                    .find(|r| r.question_id == prerequisite)
+                    .is_some_and(|r| r.is_correct || !question.unlock_if_correct),
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Quiz-order index of the earliest question whose current answer is
+    /// wrong, for jumping straight to it on review. Unanswered questions
+    /// are passed over; `None` when every answer is right.
+    pub fn first_incorrect_index(&self, quiz: &Quiz) -> Option<usize> {
+        self.first_index_where(quiz, |r| !r.is_correct)
+    }
+
+    /// Like [`Self::first_incorrect_index`], but also counting questions
+    /// that were only put right by a resubmission
+    pub fn first_incorrect_index_on_first_attempt(&self, quiz: &Quiz) -> Option<usize> {
+        self.first_index_where(quiz, |r| !r.first_attempt_correct)
+    }
+
+    fn first_index_where(
+        &self,
+        quiz: &Quiz,
+        predicate: impl Fn(&QuestionResponse) -> bool,
+    ) -> Option<usize> {
+        let matching: HashSet<Uuid> = self
+            .responses
+            .iter()
+            .filter(|r| predicate(r))
+            .map(|r| r.question_id)
+            .collect();
+        quiz.questions.iter().position(|q| matching.contains(&q.id))
+    }
+
+    /// Questions the user got wrong, in quiz order, each listed once.
+    pub fn mistakes_for_review<'a>(&self, quiz: &'a Quiz) -> Vec<&'a Question> {
+        let wrong: HashSet<Uuid> = self.incorrect_responses().map(|r| r.question_id).collect();
+
+        quiz.questions
+            .iter()
+            .filter(|q| wrong.contains(&q.id))
+            .collect()
+    }
+
+    /// Read-only replay of the attempt in quiz order, for studying it
+    /// afterwards. Borrows the session, so it cannot be changed meanwhile.
+    // Named to read as "turn into a review"; the session stays usable
+    #[allow(clippy::wrong_self_convention)]
+    pub fn into_review<'a>(&'a self, quiz: &'a Quiz) -> ReviewSession<'a> {
+        let items = quiz
+            .questions
+            .iter()
+            .enumerate()
+            .map(|(index, question)| {
+                let response = self.responses.iter().find(|r| r.question_id == question.id);
+                ReviewItem {
+                    question,
+                    given: response.map(|r| &r.answer),
+                    correct: response.is_some_and(|r| r.is_correct),
+                    skipped: response.is_none() && self.skipped_questions.contains(&index),
+                    explanation: if quiz.show_explanations {
+                        question.effective_explanation()
+                    } else {
+                        None
+                    },
+                }
+            })
+            .collect();
+
+        ReviewSession { items }
+    }
+
+    /// Brier score of the learner's confidence ratings against correctness.
+    ///
+    /// Ranges from 0.0 (perfectly calibrated) to 1.0 (confidently wrong every
+    /// time); lower is better. Returns `None` if no response carries a
+    /// confidence rating.
+    pub fn calibration_score(&self) -> Option<f32> {
+        let squared_errors: Vec<f32> = self
+            .responses
+            .iter()
+            .filter_map(|r| {
+                let outcome = if r.is_correct { 1.0 } else { 0.0 };
+                r.confidence.map(|c| (c - outcome).powi(2))
+            })
+            .collect();
+
+        if squared_errors.is_empty() {
+            None
+        } else {
+            Some(squared_errors.iter().sum::<f32>() / squared_errors.len() as f32)
+        }
+    }
+
+    /// Active time spent on the session as of `now`, excluding pauses.
+    /// The clock is frozen while paused and once the session has ended.
+    pub fn elapsed(&self, now: DateTime<Utc>) -> Duration {
+        let Some(start) = self.start_time else {
+            return Duration::zero();
+        };
+
+        let until = match (self.end_time, &self.state) {
+            (Some(end), _) => end,
+            (None, SessionState::Paused) => self.last_activity,
+            (None, _) => now,
+        };
+
+        (until - start - self.pause_duration).max(Duration::zero())
+    }
+
+    /// Time left before the session's time limit as of `now`, clamped at
+    /// zero, or `None` if the session has no limit
+    pub fn remaining(&self, now: DateTime<Utc>) -> Option<Duration> {
+        self.effective_time_limit()
+            .map(|limit| (limit - self.elapsed(now)).max(Duration::zero()))
+    }
+
+    /// Whether scoring counts answer speed for this session
+    pub fn is_timed(&self) -> bool {
+        counts_speed(self.timing_mode)
+    }
+
+    /// Limit for the whole session: a `Total` timing mode's limit, else
+    /// `time_limit`. Untimed sessions have none.
+    pub fn effective_time_limit(&self) -> Option<Duration> {
+        match self.timing_mode {
+            Some(TimingMode::Untimed) => None,
+            Some(TimingMode::Total(seconds)) => Some(Duration::seconds(seconds.into())),
+            _ => self.time_limit,
         }
     }
 
+    /// Limit for each question under a `PerQuestion` timing mode
+    pub fn question_time_limit(&self) -> Option<Duration> {
+        match self.timing_mode {
+            Some(TimingMode::PerQuestion(seconds)) => Some(Duration::seconds(seconds.into())),
+            _ => None,
+        }
+    }
+
+    /// Time between consecutive responses, in submission order. Gap `i`
+    /// is the wait before response `i + 1`. Timestamps that go backwards
+    /// (clock skew, imported data) yield a zero gap.
+    pub fn response_gaps(&self) -> Vec<Duration> {
+        self.responses
+            .windows(2)
+            .map(|pair| (pair[1].submitted_at - pair[0].submitted_at).max(Duration::zero()))
+            .collect()
+    }
+
+    /// Time spent on each answered question, inferred from submission
+    /// timestamps rather than the caller-reported `time_taken_seconds`.
+    /// The first question is measured from `start_time`. Skipped questions
+    /// have no response and are left out; time spent paused is not
+    /// subtracted. In submission order.
+    pub fn reconstruct_times(&self) -> Vec<(Uuid, Duration)> {
+        let mut responses: Vec<&QuestionResponse> = self.responses.iter().collect();
+        responses.sort_by_key(|r| r.submitted_at);
+
+        let mut previous = match (self.start_time, responses.first()) {
+            (Some(start), _) => start,
+            (None, Some(first)) => first.submitted_at,
+            (None, None) => return Vec::new(),
+        };
+        responses
+            .into_iter()
+            .map(|r| {
+                let elapsed = (r.submitted_at - previous).max(Duration::zero());
+                previous = r.submitted_at;
+                (r.question_id, elapsed)
+            })
+            .collect()
+    }
+
+    /// Indices into [`QuizSession::response_gaps`] of gaps longer than
+    /// `threshold`, which may point at lookups or an idle learner
+    pub fn suspicious_gaps(&self, threshold: Duration) -> Vec<usize> {
+        self.response_gaps()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, gap)| *gap > threshold)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
     pub fn get_progress(&self, total_questions: usize) -> f32 {
         if total_questions == 0 {
             return 0.0;
@@ -230,6 +932,103 @@ impl QuizSession {
     }
 }
 
+pub struct QuizSessionBuilder {
+    session: QuizSession,
+    timing_mode: Option<TimingMode>,
+}
+
+impl QuizSessionBuilder {
+    pub fn new(quiz_id: Uuid) -> Self {
+        Self {
+            session: QuizSession::new(quiz_id, None),
+            timing_mode: None,
+        }
+    }
+
+    pub fn user(mut self, user_id: Uuid) -> Self {
+        self.session.user_id = Some(user_id);
+        self
+    }
+
+    pub fn time_limit(mut self, limit: Duration) -> Self {
+        self.session.time_limit = Some(limit);
+        self
+    }
+
+    pub fn strategy(mut self, strategy: ScoringStrategy) -> Self {
+        self.session.scoring_strategy = Some(strategy);
+        self
+    }
+
+    /// Defaults to `Untimed`, or to a `Total` limit matching `time_limit`
+    /// when one is set
+    pub fn timing_mode(mut self, mode: TimingMode) -> Self {
+        self.timing_mode = Some(mode);
+        self
+    }
+
+    pub fn metadata(mut self, key: String, value: serde_json::Value) -> Self {
+        self.session.metadata.insert(key, value);
+        self
+    }
+
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.session.last_activity = clock.now();
+        self.session.clock = clock;
+        self
+    }
+
+    pub fn build(mut self) -> QuizSession {
+        let mode = match (self.timing_mode, self.session.time_limit) {
+            (Some(mode), _) => mode,
+            (None, Some(limit)) => {
+                TimingMode::Total(limit.num_seconds().clamp(0, u32::MAX.into()) as u32)
+            }
+            (None, None) => TimingMode::Untimed,
+        };
+        self.session.timing_mode = Some(mode);
+        self.session
+    }
+}
+
+/// One question of a completed attempt, as shown in review mode
+#[derive(Debug, Clone)]
+pub struct ReviewItem<'a> {
+    pub question: &'a Question,
+    pub given: Option<&'a Answer>,
+    pub correct: bool,
+    pub skipped: bool,
+    pub explanation: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReviewSession<'a> {
+    items: Vec<ReviewItem<'a>>,
+}
+
+impl<'a> ReviewSession<'a> {
+    pub fn iter(&self) -> impl Iterator<Item = &ReviewItem<'a>> {
+        self.items.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for ReviewSession<'a> {
+    type Item = ReviewItem<'a>;
+    type IntoIter = std::vec::IntoIter<ReviewItem<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionSummary {
     pub session_id: Uuid,
@@ -242,6 +1041,43 @@ pub struct SessionSummary {
     pub duration: Duration,
     pub average_time_per_question: u32,
     pub completion_rate: f32,
+    #[serde(default)]
+    pub answered_question_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub abandon_reason: Option<AbandonReason>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Pace {
+    Ahead,
+    OnPace,
+    Behind,
+}
+
+impl Pace {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Pace::Ahead => "ahead",
+            Pace::OnPace => "on pace",
+            Pace::Behind => "behind",
+        }
+    }
+}
+
+/// How a session's actual answering time compares with the quiz estimates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaceReport {
+    pub expected_seconds: u32,
+    pub actual_seconds: u32,
+    /// Actual over expected time; `None` when nothing was estimated
+    pub ratio: Option<f32>,
+    pub pace: Pace,
+}
+
+impl PaceReport {
+    pub fn label(&self) -> &'static str {
+        self.pace.label()
+    }
 }
 
 impl SessionSummary {
@@ -249,6 +1085,37 @@ impl SessionSummary {
         self.score >= pass_threshold
     }
 
+    /// Compare time spent against the estimated time of the questions that
+    /// were answered. Within 20% of the estimate counts as on pace.
+    pub fn pace(&self, quiz: &Quiz) -> PaceReport {
+        let expected_seconds: u32 = quiz
+            .questions
+            .iter()
+            .filter(|q| self.answered_question_ids.contains(&q.id))
+            .map(|q| q.estimated_time_seconds)
+            .sum();
+        let actual_seconds = self.total_time_seconds;
+
+        let ratio = if expected_seconds > 0 {
+            Some(actual_seconds as f32 / expected_seconds as f32)
+        } else {
+            None
+        };
+
+        let pace = match ratio {
+            Some(r) if r < 0.8 => Pace::Ahead,
+            Some(r) if r > 1.2 => Pace::Behind,
+            _ => Pace::OnPace,
+        };
+
+        PaceReport {
+            expected_seconds,
+            actual_seconds,
+            ratio,
+            pace,
+        }
+    }
+
     pub fn get_grade(&self) -> &'static str {
         match self.score {
             s if s >= 0.9 => "A",
@@ -258,12 +1125,32 @@ impl SessionSummary {
             _ => "F",
         }
     }
+
+    /// Letter grade rendered by `localizer`, or in English if `None`
+    pub fn get_grade_localized(&self, localizer: Option<&dyn Localizer>) -> String {
+        localizer
+            .unwrap_or(&EnglishLocalizer)
+            .grade_label(self.get_grade())
+    }
+
+    /// Pass or fail feedback rendered by `localizer`, or in English if `None`
+    pub fn feedback_localized(
+        &self,
+        pass_threshold: f32,
+        localizer: Option<&dyn Localizer>,
+    ) -> String {
+        let localizer = localizer.unwrap_or(&EnglishLocalizer);
+        if self.passed(pass_threshold) {
+            localizer.pass_message(self.score)
+        } else {
+            localizer.fail_message(self.score, pass_threshold)
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::quiz::question::QuestionType;
 
     #[test]
     fn test_session_lifecycle() {
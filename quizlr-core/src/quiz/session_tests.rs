@@ -3,8 +3,15 @@
 //! DEVNOTES: Testing session lifecycle, state transitions, and
 //! answer submission to ensure reliable quiz-taking experience
 
+use crate::error::QuizlrError;
+use crate::quiz::localization::Localizer;
 use crate::quiz::question::{Answer, Question, QuestionType};
-use crate::quiz::session::{QuizSession, SessionState, SessionSummary};
+use crate::quiz::quiz_impl::QuizBuilder;
+use crate::quiz::scoring::ScoringStrategy;
+use crate::quiz::session::{
+    AbandonReason, ExpiryPolicy, ExpiryReason, Pace, QuizSession, QuizSessionBuilder, SessionState,
+    SessionSummary, TimingMode,
+};
 use chrono::Duration;
 use uuid::Uuid;
 
@@ -89,6 +96,25 @@ mod session_management_tests {
         session.abandon();
         assert_eq!(session.state, SessionState::Abandoned);
         assert!(session.end_time.is_some());
+        assert_eq!(session.abandon_reason, Some(AbandonReason::Unknown));
+    }
+
+    #[test]
+    fn test_abandon_reason_persists() {
+        let mut session = QuizSession::new(Uuid::new_v4(), None);
+        session.start().unwrap();
+        assert!(session.generate_summary().abandon_reason.is_none());
+
+        session.abandon_with_reason(AbandonReason::TimedOut);
+        assert_eq!(session.state, SessionState::Abandoned);
+        assert_eq!(
+            session.generate_summary().abandon_reason,
+            Some(AbandonReason::TimedOut)
+        );
+
+        let json = serde_json::to_string(&session).unwrap();
+        let restored: QuizSession = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.abandon_reason, Some(AbandonReason::TimedOut));
     }
 
     #[test]
@@ -286,6 +312,8 @@ mod session_management_tests {
             duration: Duration::zero(),
             average_time_per_question: 0,
             completion_rate: 0.0,
+            answered_question_ids: Vec::new(),
+            abandon_reason: None,
         };
 
         // Test grade assignments
@@ -321,6 +349,8 @@ mod session_management_tests {
             duration: Duration::seconds(300),
             average_time_per_question: 30,
             completion_rate: 1.0,
+            answered_question_ids: Vec::new(),
+            abandon_reason: None,
         };
 
         assert!(summary.passed(0.7)); // Exactly at threshold
@@ -371,4 +401,1124 @@ mod session_management_tests {
         assert_eq!(summary.average_time_per_question, 0);
         assert_eq!(summary.completion_rate, 0.0);
     }
+
+    #[test]
+    fn test_submit_answer_by_id_known_question() {
+        let question = create_test_question();
+        let question_id = question.id;
+        let quiz = QuizBuilder::new("By id".to_string())
+            .add_question(question)
+            .build();
+
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+
+        let result = session
+            .submit_answer_by_id(&quiz, question_id, Answer::TrueFalse(true), 10)
+            .unwrap();
+        assert_eq!(result.question_id, question_id);
+        assert!(result.is_correct);
+        assert_eq!(result.attempts, 1);
+
+        let result = session
+            .submit_answer_by_id(&quiz, question_id, Answer::TrueFalse(false), 5)
+            .unwrap();
+        assert!(!result.is_correct);
+        assert_eq!(result.attempts, 2);
+        assert_eq!(session.responses.len(), 1);
+    }
+
+    #[test]
+    fn test_submit_answer_by_id_unknown_question() {
+        let quiz = QuizBuilder::new("By id".to_string())
+            .add_question(create_test_question())
+            .build();
+
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+
+        let result =
+            session.submit_answer_by_id(&quiz, Uuid::new_v4(), Answer::TrueFalse(true), 10);
+        assert!(matches!(result, Err(QuizlrError::NotFound(_))));
+        assert!(session.responses.is_empty());
+    }
+
+    #[test]
+    fn test_complete_strict_fully_answered() {
+        let q1 = create_test_question();
+        let q2 = create_test_question();
+        let q3 = create_test_question();
+        let quiz = QuizBuilder::new("Strict".to_string())
+            .add_questions(vec![q1.clone(), q2.clone(), q3])
+            .build();
+
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+        session
+            .submit_answer(&q1, Answer::TrueFalse(true), 10)
+            .unwrap();
+        session
+            .submit_answer(&q2, Answer::TrueFalse(false), 10)
+            .unwrap();
+        session.skip_question(2);
+
+        let summary = session.complete_strict(&quiz).unwrap();
+        assert_eq!(summary.correct_answers, 1);
+        assert_eq!(session.state, SessionState::Completed);
+    }
+
+    #[test]
+    fn test_complete_strict_lists_gaps() {
+        let q1 = create_test_question();
+        let quiz = QuizBuilder::new("Strict".to_string())
+            .add_questions(vec![
+                create_test_question(),
+                q1.clone(),
+                create_test_question(),
+                create_test_question(),
+            ])
+            .build();
+
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+        session
+            .submit_answer(&q1, Answer::TrueFalse(true), 10)
+            .unwrap();
+        session.skip_question(3);
+
+        match session.complete_strict(&quiz) {
+            Err(QuizlrError::InvalidInput(message)) => {
+                assert!(
+                    message.contains("[0, 2]"),
+                    "unexpected message: {}",
+                    message
+                );
+            }
+            other => panic!("expected InvalidInput, got {:?}", other),
+        }
+        assert_eq!(session.state, SessionState::InProgress);
+        assert!(session.end_time.is_none());
+    }
+
+    #[test]
+    fn test_correct_and_incorrect_responses() {
+        let questions: Vec<Question> = (0..4).map(|_| create_test_question()).collect();
+        let quiz = QuizBuilder::new("Review".to_string())
+            .add_questions(questions.clone())
+            .build();
+
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+        session
+            .submit_answer(&questions[0], Answer::TrueFalse(true), 10)
+            .unwrap();
+        session
+            .submit_answer(&questions[1], Answer::TrueFalse(false), 10)
+            .unwrap();
+        session
+            .submit_answer(&questions[2], Answer::TrueFalse(false), 10)
+            .unwrap();
+        // Attempted twice, wrong both times
+        session
+            .submit_answer(&questions[2], Answer::TrueFalse(false), 10)
+            .unwrap();
+        session
+            .submit_answer(&questions[3], Answer::TrueFalse(true), 10)
+            .unwrap();
+
+        let correct: Vec<Uuid> = session.correct_responses().map(|r| r.question_id).collect();
+        assert_eq!(correct, vec![questions[0].id, questions[3].id]);
+        assert_eq!(session.incorrect_responses().count(), 2);
+
+        let mistakes = session.mistakes_for_review(&quiz);
+        let mistake_ids: Vec<Uuid> = mistakes.iter().map(|q| q.id).collect();
+        assert_eq!(mistake_ids, vec![questions[1].id, questions[2].id]);
+    }
+
+    #[test]
+    fn test_mistakes_for_review_excludes_corrected_answers() {
+        let question = create_test_question();
+        let quiz = QuizBuilder::new("Review".to_string())
+            .add_question(question.clone())
+            .build();
+
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+        session
+            .submit_answer(&question, Answer::TrueFalse(false), 10)
+            .unwrap();
+        session
+            .submit_answer(&question, Answer::TrueFalse(true), 10)
+            .unwrap();
+
+        assert!(session.mistakes_for_review(&quiz).is_empty());
+    }
+
+    #[test]
+    fn test_attempt_history_records_every_attempt() {
+        let question = create_test_question();
+        let mut session = QuizSession::new(Uuid::new_v4(), None);
+        session.start().unwrap();
+
+        session
+            .submit_answer(&question, Answer::TrueFalse(false), 10)
+            .unwrap();
+        session
+            .submit_answer(&question, Answer::TrueFalse(false), 10)
+            .unwrap();
+        session
+            .submit_answer(&question, Answer::TrueFalse(true), 10)
+            .unwrap();
+
+        let history = session.attempt_history(question.id);
+        assert_eq!(history.len(), 3);
+        assert!(!history[0].is_correct);
+        assert!(!history[1].is_correct);
+        assert!(history[2].is_correct);
+        assert!(history[0].at <= history[2].at);
+
+        // Latest result and count are still tracked on the response
+        assert!(session.responses[0].is_correct);
+        assert_eq!(session.responses[0].attempts, 3);
+
+        assert!(session.attempt_history(Uuid::new_v4()).is_empty());
+    }
+
+    #[test]
+    fn test_submit_answer_with_confidence() {
+        let question = create_test_question();
+        let mut session = QuizSession::new(Uuid::new_v4(), None);
+        session.start().unwrap();
+
+        assert!(session
+            .submit_answer_with_confidence(&question, Answer::TrueFalse(true), 10, Some(1.5))
+            .is_err());
+        assert!(session.responses.is_empty());
+        assert!(session.calibration_score().is_none());
+
+        session
+            .submit_answer_with_confidence(&question, Answer::TrueFalse(true), 10, Some(0.9))
+            .unwrap();
+        assert_eq!(session.responses[0].confidence, Some(0.9));
+    }
+
+    #[test]
+    fn test_overconfident_wrong_answers_worsen_calibration() {
+        let questions: Vec<Question> = (0..4).map(|_| create_test_question()).collect();
+
+        // Confident and right, unsure and wrong: well calibrated
+        let mut calibrated = QuizSession::new(Uuid::new_v4(), None);
+        calibrated.start().unwrap();
+        calibrated
+            .submit_answer_with_confidence(&questions[0], Answer::TrueFalse(true), 10, Some(0.9))
+            .unwrap();
+        calibrated
+            .submit_answer_with_confidence(&questions[1], Answer::TrueFalse(true), 10, Some(0.8))
+            .unwrap();
+        calibrated
+            .submit_answer_with_confidence(&questions[2], Answer::TrueFalse(false), 10, Some(0.2))
+            .unwrap();
+        calibrated
+            .submit_answer_with_confidence(&questions[3], Answer::TrueFalse(false), 10, Some(0.1))
+            .unwrap();
+
+        // Same results, but confident on the wrong answers too
+        let mut overconfident = QuizSession::new(Uuid::new_v4(), None);
+        overconfident.start().unwrap();
+        overconfident
+            .submit_answer_with_confidence(&questions[0], Answer::TrueFalse(true), 10, Some(0.9))
+            .unwrap();
+        overconfident
+            .submit_answer_with_confidence(&questions[1], Answer::TrueFalse(true), 10, Some(0.8))
+            .unwrap();
+        overconfident
+            .submit_answer_with_confidence(&questions[2], Answer::TrueFalse(false), 10, Some(0.95))
+            .unwrap();
+        overconfident
+            .submit_answer_with_confidence(&questions[3], Answer::TrueFalse(false), 10, Some(1.0))
+            .unwrap();
+
+        let good = calibrated.calibration_score().unwrap();
+        let bad = overconfident.calibration_score().unwrap();
+        assert!(good < 0.05, "calibrated score was {}", good);
+        assert!(bad > good);
+        assert!(bad > 0.4);
+    }
+
+    #[test]
+    fn test_skip_question_checked_allowed() {
+        let quiz = QuizBuilder::new("Skippable".to_string())
+            .allow_skip(true)
+            .add_questions(vec![create_test_question(), create_test_question()])
+            .build();
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+
+        assert!(session.skip_question_checked(&quiz, 1).is_ok());
+        assert_eq!(session.skipped_questions, vec![1]);
+        assert!(matches!(
+            session.skip_question_checked(&quiz, 5),
+            Err(QuizlrError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_skip_question_checked_forbidden() {
+        let question = create_test_question();
+        let quiz = QuizBuilder::new("No skipping".to_string())
+            .allow_skip(false)
+            .add_questions(vec![question.clone(), create_test_question()])
+            .build();
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+
+        assert!(matches!(
+            session.skip_question_checked(&quiz, 1),
+            Err(QuizlrError::InvalidInput(_))
+        ));
+        assert!(session.skipped_questions.is_empty());
+
+        // An unchecked skip doesn't satisfy strict completion either
+        session
+            .submit_answer(&question, Answer::TrueFalse(true), 10)
+            .unwrap();
+        session.skip_question(1);
+        assert!(matches!(
+            session.complete_strict(&quiz),
+            Err(QuizlrError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_session_builder() {
+        let quiz_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let session = QuizSessionBuilder::new(quiz_id)
+            .user(user_id)
+            .time_limit(Duration::minutes(20))
+            .strategy(ScoringStrategy::Simple {
+                hint_penalty_per_use: 0.0,
+            })
+            .metadata("cohort".to_string(), serde_json::json!("spring-2024"))
+            .build();
+
+        assert_eq!(session.quiz_id, quiz_id);
+        assert_eq!(session.user_id, Some(user_id));
+        assert_eq!(session.time_limit, Some(Duration::minutes(20)));
+        assert!(matches!(
+            session.scoring_strategy,
+            Some(ScoringStrategy::Simple {
+                hint_penalty_per_use: 0.0,
+            })
+        ));
+        assert_eq!(
+            session.metadata.get("cohort"),
+            Some(&serde_json::json!("spring-2024"))
+        );
+        assert_eq!(session.state, SessionState::NotStarted);
+    }
+
+    #[test]
+    fn test_session_builder_defaults_match_new() {
+        let session = QuizSessionBuilder::new(Uuid::new_v4()).build();
+        assert!(session.user_id.is_none());
+        assert!(session.time_limit.is_none());
+        assert!(session.scoring_strategy.is_none());
+        assert!(session.metadata.is_empty());
+        assert_eq!(session.timing_mode, Some(TimingMode::Untimed));
+        assert!(!session.is_timed());
+
+        // A time limit alone makes the session timed against that limit
+        let limited = QuizSessionBuilder::new(Uuid::new_v4())
+            .time_limit(Duration::minutes(2))
+            .build();
+        assert_eq!(limited.timing_mode, Some(TimingMode::Total(120)));
+        assert_eq!(limited.effective_time_limit(), Some(Duration::minutes(2)));
+    }
+
+    #[test]
+    fn test_timing_mode_limits() {
+        let mut session = QuizSessionBuilder::new(Uuid::new_v4())
+            .timing_mode(TimingMode::Total(120))
+            .build();
+        session.start().unwrap();
+        let t0 = session.start_time.unwrap();
+        assert_eq!(
+            session.remaining(t0 + Duration::seconds(20)),
+            Some(Duration::seconds(100))
+        );
+        assert_eq!(session.question_time_limit(), None);
+
+        let per_question = QuizSessionBuilder::new(Uuid::new_v4())
+            .time_limit(Duration::minutes(10))
+            .timing_mode(TimingMode::PerQuestion(45))
+            .build();
+        assert_eq!(
+            per_question.question_time_limit(),
+            Some(Duration::seconds(45))
+        );
+        assert_eq!(
+            per_question.effective_time_limit(),
+            Some(Duration::minutes(10))
+        );
+
+        // Open book sessions have no limits at all
+        let open_book = QuizSessionBuilder::new(Uuid::new_v4())
+            .time_limit(Duration::minutes(10))
+            .timing_mode(TimingMode::Untimed)
+            .build();
+        assert!(!open_book.is_timed());
+        assert_eq!(open_book.effective_time_limit(), None);
+        assert_eq!(open_book.remaining(chrono::Utc::now()), None);
+    }
+
+    #[test]
+    fn test_elapsed_freezes_while_paused() {
+        let mut session = QuizSessionBuilder::new(Uuid::new_v4())
+            .time_limit(Duration::seconds(60))
+            .build();
+        assert_eq!(session.elapsed(chrono::Utc::now()), Duration::zero());
+
+        session.start().unwrap();
+        let t0 = session.start_time.unwrap();
+        assert_eq!(
+            session.elapsed(t0 + Duration::seconds(10)),
+            Duration::seconds(10)
+        );
+        assert_eq!(
+            session.remaining(t0 + Duration::seconds(10)),
+            Some(Duration::seconds(50))
+        );
+
+        // Pause 10 seconds in
+        session.pause().unwrap();
+        session.last_activity = t0 + Duration::seconds(10);
+        assert_eq!(
+            session.elapsed(t0 + Duration::seconds(40)),
+            Duration::seconds(10)
+        );
+        assert_eq!(
+            session.elapsed(t0 + Duration::seconds(90)),
+            Duration::seconds(10)
+        );
+
+        // Resume after a 50 second pause; the clock ticks again
+        session.state = SessionState::InProgress;
+        session.pause_duration = Duration::seconds(50);
+        assert_eq!(
+            session.elapsed(t0 + Duration::seconds(70)),
+            Duration::seconds(20)
+        );
+        assert_eq!(
+            session.remaining(t0 + Duration::seconds(70)),
+            Some(Duration::seconds(40))
+        );
+    }
+
+    #[test]
+    fn test_remaining_clamps_at_zero_and_requires_limit() {
+        let mut session = QuizSessionBuilder::new(Uuid::new_v4())
+            .time_limit(Duration::seconds(30))
+            .build();
+        session.start().unwrap();
+        let t0 = session.start_time.unwrap();
+
+        assert_eq!(
+            session.remaining(t0 + Duration::seconds(300)),
+            Some(Duration::zero())
+        );
+
+        let mut unlimited = QuizSession::new(Uuid::new_v4(), None);
+        unlimited.start().unwrap();
+        assert!(unlimited.remaining(t0).is_none());
+    }
+
+    struct FrenchLocalizer;
+
+    impl Localizer for FrenchLocalizer {
+        fn grade_label(&self, grade: &str) -> String {
+            format!("Note : {}", grade)
+        }
+
+        fn pass_message(&self, score: f32) -> String {
+            format!("Réussi avec {:.0} %", score * 100.0)
+        }
+
+        fn fail_message(&self, score: f32, pass_threshold: f32) -> String {
+            format!(
+                "Échoué avec {:.0} % ({:.0} % requis)",
+                score * 100.0,
+                pass_threshold * 100.0
+            )
+        }
+    }
+
+    #[test]
+    fn test_localized_feedback() {
+        let mut session = QuizSession::new(Uuid::new_v4(), None);
+        session.start().unwrap();
+        let mut summary = session.complete().unwrap();
+        summary.score = 0.85;
+
+        assert_eq!(summary.get_grade_localized(None), "B");
+        assert_eq!(summary.feedback_localized(0.7, None), "Passed with 85%");
+        assert_eq!(
+            summary.feedback_localized(0.9, None),
+            "Scored 85%, 90% needed to pass"
+        );
+
+        let french = FrenchLocalizer;
+        assert_eq!(summary.get_grade_localized(Some(&french)), "Note : B");
+        assert_eq!(
+            summary.feedback_localized(0.7, Some(&french)),
+            "Réussi avec 85 %"
+        );
+        assert_eq!(
+            summary.feedback_localized(0.9, Some(&french)),
+            "Échoué avec 85 % (90 % requis)"
+        );
+    }
+
+    fn paced_session(quiz: &crate::quiz::Quiz, seconds_per_question: u32) -> SessionSummary {
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+        for question in &quiz.questions {
+            session
+                .submit_answer(question, Answer::TrueFalse(true), seconds_per_question)
+                .unwrap();
+        }
+        session.complete().unwrap()
+    }
+
+    #[test]
+    fn test_pace_fast_and_slow() {
+        // Each question is estimated at 60 seconds
+        let quiz = QuizBuilder::new("Paced".to_string())
+            .add_questions(vec![create_test_question(), create_test_question()])
+            .build();
+
+        let fast = paced_session(&quiz, 20).pace(&quiz);
+        assert_eq!(fast.expected_seconds, 120);
+        assert_eq!(fast.actual_seconds, 40);
+        assert_eq!(fast.pace, Pace::Ahead);
+        assert_eq!(fast.label(), "ahead");
+
+        let slow = paced_session(&quiz, 90).pace(&quiz);
+        assert_eq!(slow.ratio, Some(1.5));
+        assert_eq!(slow.label(), "behind");
+
+        let steady = paced_session(&quiz, 65).pace(&quiz);
+        assert_eq!(steady.label(), "on pace");
+    }
+
+    #[test]
+    fn test_pace_with_zero_estimates() {
+        let mut question = create_test_question();
+        question.estimated_time_seconds = 0;
+        let quiz = QuizBuilder::new("Unestimated".to_string())
+            .add_question(question)
+            .build();
+
+        let report = paced_session(&quiz, 30).pace(&quiz);
+        assert_eq!(report.expected_seconds, 0);
+        assert!(report.ratio.is_none());
+        assert_eq!(report.pace, Pace::OnPace);
+    }
+
+    #[test]
+    fn test_first_attempt_tracked_separately() {
+        let q1 = create_test_question();
+        let q2 = create_test_question();
+        let mut session = QuizSession::new(Uuid::new_v4(), None);
+        session.start().unwrap();
+
+        // Wrong, then right
+        session
+            .submit_answer(&q1, Answer::TrueFalse(false), 10)
+            .unwrap();
+        session
+            .submit_answer(&q1, Answer::TrueFalse(true), 10)
+            .unwrap();
+        // Right first time
+        session
+            .submit_answer(&q2, Answer::TrueFalse(true), 10)
+            .unwrap();
+
+        assert!(session.responses[0].is_correct);
+        assert!(!session.responses[0].first_attempt_correct);
+        assert!(session.responses[1].first_attempt_correct);
+
+        assert_eq!(session.generate_summary().score, 1.0);
+        assert_eq!(session.first_attempt_score(), 0.5);
+    }
+
+    #[test]
+    fn test_legacy_responses_recover_first_attempt() {
+        let q1 = create_test_question();
+        let q2 = create_test_question();
+        let q3 = create_test_question();
+        let mut session = QuizSession::new(Uuid::new_v4(), None);
+        session.start().unwrap();
+        // Right first time, wrong then right, right first time
+        session
+            .submit_answer(&q1, Answer::TrueFalse(true), 10)
+            .unwrap();
+        session
+            .submit_answer(&q2, Answer::TrueFalse(false), 10)
+            .unwrap();
+        session
+            .submit_answer(&q2, Answer::TrueFalse(true), 10)
+            .unwrap();
+        session
+            .submit_answer(&q3, Answer::TrueFalse(true), 10)
+            .unwrap();
+
+        // Sessions saved before first_attempt_correct existed; the last one
+        // predates the attempt history too
+        let mut json = serde_json::to_value(&session).unwrap();
+        let responses = json["responses"].as_array_mut().unwrap();
+        for response in responses.iter_mut() {
+            response
+                .as_object_mut()
+                .unwrap()
+                .remove("first_attempt_correct");
+        }
+        responses[2]
+            .as_object_mut()
+            .unwrap()
+            .remove("attempt_history");
+
+        let restored: QuizSession = serde_json::from_value(json).unwrap();
+        assert!(restored.responses[0].first_attempt_correct);
+        assert!(!restored.responses[1].first_attempt_correct);
+        assert!(restored.responses[2].first_attempt_correct);
+        assert_eq!(
+            restored.first_attempt_score(),
+            session.first_attempt_score()
+        );
+    }
+
+    #[test]
+    fn test_first_incorrect_index() {
+        let questions: Vec<Question> = (0..4).map(|_| create_test_question()).collect();
+        let quiz = QuizBuilder::new("Review".to_string())
+            .add_questions(questions.clone())
+            .build();
+        let session_with = |answers: [bool; 4]| {
+            let mut session = QuizSession::new(quiz.id, None);
+            session.start().unwrap();
+            // Answer in reverse so submission order differs from quiz order
+            for (question, answer) in questions.iter().zip(answers).rev() {
+                session
+                    .submit_answer(question, Answer::TrueFalse(answer), 10)
+                    .unwrap();
+            }
+            session
+        };
+
+        assert_eq!(
+            session_with([false, true, true, true]).first_incorrect_index(&quiz),
+            Some(0)
+        );
+        assert_eq!(
+            session_with([true, true, false, false]).first_incorrect_index(&quiz),
+            Some(2)
+        );
+        assert_eq!(
+            session_with([true, true, true, false]).first_incorrect_index(&quiz),
+            Some(3)
+        );
+        assert_eq!(session_with([true; 4]).first_incorrect_index(&quiz), None);
+
+        // A corrected answer only counts when looking at first attempts
+        let mut session = session_with([true; 4]);
+        session.responses[2].first_attempt_correct = false;
+        let corrected_index = quiz
+            .questions
+            .iter()
+            .position(|q| q.id == session.responses[2].question_id);
+        assert_eq!(session.first_incorrect_index(&quiz), None);
+        assert_eq!(
+            session.first_incorrect_index_on_first_attempt(&quiz),
+            corrected_index
+        );
+    }
+
+    #[test]
+    fn test_question_unlocks_after_correct_prerequisite() {
+        let prerequisite = create_test_question();
+        let mut gated = create_test_question();
+        gated.unlock_after = Some(prerequisite.id);
+        gated.unlock_if_correct = true;
+        let mut after_any = create_test_question();
+        after_any.unlock_after = Some(prerequisite.id);
+        let quiz = QuizBuilder::new("Branching".to_string())
+            .add_questions(vec![prerequisite.clone(), gated, after_any])
+            .build();
+        assert!(quiz.validate().is_ok());
+
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+        assert_eq!(session.available_questions(&quiz), vec![0]);
+
+        session
+            .submit_answer(&prerequisite, Answer::TrueFalse(false), 10)
+            .unwrap();
+        assert_eq!(session.available_questions(&quiz), vec![0, 2]);
+
+        session
+            .submit_answer(&prerequisite, Answer::TrueFalse(true), 10)
+            .unwrap();
+        assert_eq!(session.available_questions(&quiz), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_unlock_cycles_and_unknown_prerequisites_fail_validation() {
+        let mut first = create_test_question();
+        let mut second = create_test_question();
+        first.unlock_after = Some(second.id);
+        second.unlock_after = Some(first.id);
+        let quiz = QuizBuilder::new("Loop".to_string())
+            .add_questions(vec![first, second])
+            .build();
+        let errors = quiz.validation_errors();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].message.contains("cycle"));
+        assert!(matches!(quiz.validate(), Err(QuizlrError::InvalidInput(_))));
+
+        let mut dangling = create_test_question();
+        dangling.unlock_after = Some(Uuid::new_v4());
+        let quiz = QuizBuilder::new("Dangling".to_string())
+            .add_question(dangling)
+            .build();
+        assert!(quiz.validation_errors()[0]
+            .message
+            .contains("unknown question"));
+    }
+
+    #[test]
+    fn test_dont_know_records_as_incorrect() {
+        let q1 = create_test_question();
+        let q2 = create_test_question();
+        let q3 = create_test_question();
+        let quiz = QuizBuilder::new("Don't know".to_string())
+            .add_questions(vec![q1.clone(), q2.clone(), q3.clone()])
+            .build();
+
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+        assert!(!session.submit_answer(&q1, Answer::DontKnow, 5).unwrap());
+        assert!(!session
+            .submit_answer(&q2, Answer::TrueFalse(false), 5)
+            .unwrap());
+        assert!(session
+            .submit_answer(&q3, Answer::TrueFalse(true), 5)
+            .unwrap());
+
+        assert!(session.responses[0].is_dont_know());
+        assert!(!session.responses[0].is_correct);
+        assert!(!session.responses[1].is_dont_know());
+        assert_eq!(session.dont_know_count(), 1);
+        assert_eq!(q1.partial_credit(&Answer::DontKnow).unwrap(), 0.0);
+
+        let score = ScoringStrategy::Simple {
+            hint_penalty_per_use: 0.0,
+        }
+        .calculate_score(&session, &quiz.questions);
+        assert!((score.raw_score - 1.0 / 3.0).abs() < 1e-6);
+
+        // Answering properly on a retry clears the flag
+        session
+            .submit_answer(&q1, Answer::TrueFalse(true), 5)
+            .unwrap();
+        assert_eq!(session.dont_know_count(), 0);
+    }
+
+    #[test]
+    fn test_regrade_after_answer_key_correction() {
+        let q1 = create_test_question();
+        let q2 = create_test_question();
+        let mut quiz = QuizBuilder::new("Regrade".to_string())
+            .add_questions(vec![q1.clone(), q2.clone()])
+            .build();
+
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+        session
+            .submit_answer(&q1, Answer::TrueFalse(false), 10)
+            .unwrap();
+        session
+            .submit_answer(&q2, Answer::TrueFalse(true), 10)
+            .unwrap();
+        assert!(!session.responses[0].is_correct);
+
+        // Nothing changes against the same keys
+        assert_eq!(session.regrade(&quiz).unwrap(), 0);
+
+        // The first key was wrong all along
+        if let QuestionType::TrueFalse { correct_answer, .. } = &mut quiz.questions[0].question_type
+        {
+            *correct_answer = false;
+        }
+        assert_eq!(session.regrade(&quiz).unwrap(), 1);
+        assert!(session.responses[0].is_correct);
+        assert!(session.responses[0].first_attempt_correct);
+        assert!(session.responses[0].attempt_history[0].is_correct);
+        assert!(session.responses[1].is_correct);
+
+        // A response to a question the quiz no longer has fails untouched
+        quiz.remove_question(q2.id);
+        assert!(matches!(
+            session.regrade(&quiz),
+            Err(QuizlrError::NotFound(_))
+        ));
+        assert!(session.responses[0].is_correct);
+    }
+
+    #[test]
+    fn test_first_attempt_score_empty() {
+        let session = QuizSession::new(Uuid::new_v4(), None);
+        assert_eq!(session.first_attempt_score(), 0.0);
+    }
+
+    #[test]
+    fn test_review_mixed_session() {
+        let mut explained = create_test_question();
+        if let QuestionType::TrueFalse { explanation, .. } = &mut explained.question_type {
+            *explanation = Some("Because it is".to_string());
+        }
+        let quiz = QuizBuilder::new("Review".to_string())
+            .add_questions(vec![
+                explained,
+                create_test_question(),
+                create_test_question(),
+            ])
+            .build();
+
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+        session
+            .submit_answer(&quiz.questions[0], Answer::TrueFalse(true), 10)
+            .unwrap();
+        session.skip_question(1);
+        session
+            .submit_answer(&quiz.questions[2], Answer::TrueFalse(false), 10)
+            .unwrap();
+        session.complete().unwrap();
+
+        let review = session.into_review(&quiz);
+        assert_eq!(review.len(), 3);
+        let items: Vec<_> = review.iter().collect();
+
+        assert_eq!(items[0].question.id, quiz.questions[0].id);
+        assert!(matches!(items[0].given, Some(Answer::TrueFalse(true))));
+        assert!(items[0].correct);
+        assert!(!items[0].skipped);
+        assert_eq!(items[0].explanation.as_deref(), Some("Because it is"));
+
+        assert!(items[1].given.is_none());
+        assert!(!items[1].correct);
+        assert!(items[1].skipped);
+        assert!(items[1].explanation.is_none());
+
+        assert!(matches!(items[2].given, Some(Answer::TrueFalse(false))));
+        assert!(!items[2].correct);
+        assert!(!items[2].skipped);
+
+        // The session itself is untouched and still usable
+        assert_eq!(session.state, SessionState::Completed);
+        assert_eq!(session.responses.len(), 2);
+    }
+
+    #[test]
+    fn test_response_gaps_and_suspicious_gaps() {
+        let questions: Vec<Question> = (0..4).map(|_| create_test_question()).collect();
+        let mut session = QuizSession::new(Uuid::new_v4(), None);
+        session.start().unwrap();
+        for question in &questions {
+            session
+                .submit_answer(question, Answer::TrueFalse(true), 5)
+                .unwrap();
+        }
+
+        let base = chrono::Utc::now();
+        let offsets = [0, 30, 630, 600];
+        for (response, offset) in session.responses.iter_mut().zip(offsets) {
+            response.submitted_at = base + Duration::seconds(offset);
+        }
+
+        let gaps = session.response_gaps();
+        assert_eq!(
+            gaps,
+            vec![
+                Duration::seconds(30),
+                Duration::seconds(600),
+                // Out-of-order timestamp clamps to zero
+                Duration::zero(),
+            ]
+        );
+        assert_eq!(session.suspicious_gaps(Duration::minutes(5)), vec![1]);
+        assert!(session.suspicious_gaps(Duration::minutes(15)).is_empty());
+
+        let empty = QuizSession::new(Uuid::new_v4(), None);
+        assert!(empty.response_gaps().is_empty());
+    }
+
+    #[test]
+    fn test_expire_if_idle_active_timeout() {
+        let policy = ExpiryPolicy {
+            max_idle_active: Duration::minutes(10),
+            max_paused: Duration::hours(12),
+        };
+        let mut session = QuizSession::new(Uuid::new_v4(), None);
+        assert_eq!(
+            session.expire_if_idle(&policy, session.last_activity + Duration::days(3)),
+            None
+        );
+
+        session.start().unwrap();
+        let last = session.last_activity;
+        assert_eq!(
+            session.expire_if_idle(&policy, last + Duration::minutes(9)),
+            None
+        );
+        assert_eq!(session.state, SessionState::InProgress);
+
+        assert_eq!(
+            session.expire_if_idle(&policy, last + Duration::minutes(11)),
+            Some(ExpiryReason::IdleTooLong)
+        );
+        assert_eq!(session.state, SessionState::Abandoned);
+        assert_eq!(session.abandon_reason, Some(AbandonReason::TimedOut));
+    }
+
+    #[test]
+    fn test_expire_if_idle_paused_timeout() {
+        let policy = ExpiryPolicy {
+            max_idle_active: Duration::minutes(10),
+            max_paused: Duration::hours(12),
+        };
+        let mut session = QuizSession::new(Uuid::new_v4(), None);
+        session.start().unwrap();
+        session.pause().unwrap();
+        let paused_at = session.last_activity;
+
+        // Far past the active limit, but paused sessions get longer
+        assert_eq!(
+            session.expire_if_idle(&policy, paused_at + Duration::hours(8)),
+            None
+        );
+        assert_eq!(session.state, SessionState::Paused);
+
+        assert_eq!(
+            session.expire_if_idle(&policy, paused_at + Duration::hours(13)),
+            Some(ExpiryReason::PausedTooLong)
+        );
+        assert_eq!(session.state, SessionState::Abandoned);
+    }
+
+    #[test]
+    fn test_pause_accounting_with_fixed_clock() {
+        use crate::clock::{Clock, FixedClock};
+        use std::sync::Arc;
+
+        let start = chrono::Utc::now();
+        let clock = Arc::new(FixedClock::new(start));
+        let mut session = QuizSession::with_clock(Uuid::new_v4(), None, clock.clone());
+
+        session.start().unwrap();
+        assert_eq!(session.start_time, Some(start));
+
+        clock.advance(Duration::minutes(5));
+        session.pause().unwrap();
+        clock.advance(Duration::minutes(10));
+        session.resume().unwrap();
+        assert_eq!(session.pause_duration, Duration::minutes(10));
+
+        clock.advance(Duration::minutes(2));
+        let summary = session.complete().unwrap();
+        assert_eq!(session.end_time, Some(start + Duration::minutes(17)));
+        assert_eq!(summary.duration, Duration::minutes(7));
+
+        let built = QuizSessionBuilder::new(Uuid::new_v4())
+            .clock(clock.clone())
+            .build();
+        assert_eq!(built.last_activity, clock.now());
+    }
+
+    #[test]
+    fn test_pause_with_reason_logs_complete_record() {
+        use crate::clock::FixedClock;
+        use crate::quiz::session::PauseRecord;
+        use std::sync::Arc;
+
+        let start = chrono::Utc::now();
+        let clock = Arc::new(FixedClock::new(start));
+        let mut session = QuizSession::with_clock(Uuid::new_v4(), None, clock.clone());
+        session.start().unwrap();
+
+        clock.advance(Duration::minutes(5));
+        session.pause_with_reason("Fire alarm".to_string()).unwrap();
+        assert!(session.pause_with_reason("Again".to_string()).is_err());
+        assert_eq!(session.pause_log.len(), 1);
+        assert_eq!(session.pause_log[0].resumed_at, None);
+
+        clock.advance(Duration::minutes(10));
+        session.resume().unwrap();
+        assert_eq!(
+            session.pause_log,
+            vec![PauseRecord {
+                reason: "Fire alarm".to_string(),
+                at: start + Duration::minutes(5),
+                resumed_at: Some(start + Duration::minutes(15)),
+            }]
+        );
+
+        // A plain pause is logged with an empty reason
+        session.pause().unwrap();
+        assert_eq!(session.pause_log.len(), 2);
+        assert_eq!(session.pause_log[1].reason, "");
+    }
+
+    #[test]
+    fn test_shown_options_persist_through_serialization() {
+        let question = Question::new(
+            QuestionType::MultipleChoice {
+                question: "Pick the prime".to_string(),
+                options: vec!["4".to_string(), "6".to_string(), "7".to_string()],
+                correct_index: 2,
+                alternate_correct: Vec::new(),
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.3,
+        );
+        let mut session = QuizSession::new(Uuid::new_v4(), None);
+        session.start().unwrap();
+
+        // Options were displayed shuffled, with "7" moved to the front
+        let shown = vec!["7".to_string(), "4".to_string(), "6".to_string()];
+        assert!(session
+            .submit_answer_with_shown_options(
+                &question,
+                Answer::MultipleChoice(0),
+                10,
+                shown.clone()
+            )
+            .unwrap());
+        assert!(matches!(
+            session.responses[0].answer,
+            Answer::MultipleChoice(2)
+        ));
+
+        // The displayed index 2 is "6", not the original option 2
+        assert!(!session
+            .submit_answer_with_shown_options(
+                &question,
+                Answer::MultipleChoice(2),
+                10,
+                shown.clone()
+            )
+            .unwrap());
+        session
+            .submit_answer_with_shown_options(
+                &question,
+                Answer::MultipleChoice(0),
+                10,
+                shown.clone(),
+            )
+            .unwrap();
+
+        let json = serde_json::to_string(&session).unwrap();
+        let restored: QuizSession = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.responses[0].shown_options, Some(shown));
+
+        // Mismatched lengths, options that aren't a reordering, displayed
+        // indices out of range and other question types are rejected
+        assert!(session
+            .submit_answer_with_shown_options(
+                &question,
+                Answer::MultipleChoice(0),
+                5,
+                vec!["4".to_string()]
+            )
+            .is_err());
+        assert!(session
+            .submit_answer_with_shown_options(
+                &question,
+                Answer::MultipleChoice(0),
+                5,
+                vec!["4".to_string(), "6".to_string(), "8".to_string()]
+            )
+            .is_err());
+        assert!(session
+            .submit_answer_with_shown_options(
+                &question,
+                Answer::MultipleChoice(3),
+                5,
+                vec!["7".to_string(), "4".to_string(), "6".to_string()]
+            )
+            .is_err());
+        assert!(session
+            .submit_answer_with_shown_options(
+                &create_test_question(),
+                Answer::TrueFalse(true),
+                5,
+                Vec::new()
+            )
+            .is_err());
+
+        // Plain submissions leave it unset
+        let mut plain = QuizSession::new(Uuid::new_v4(), None);
+        plain.start().unwrap();
+        plain
+            .submit_answer(&create_test_question(), Answer::TrueFalse(true), 5)
+            .unwrap();
+        assert!(plain.responses[0].shown_options.is_none());
+    }
+
+    #[test]
+    fn test_reconstruct_times_against_reported() {
+        use crate::clock::FixedClock;
+        use std::sync::Arc;
+
+        let questions: Vec<Question> = (0..3).map(|_| create_test_question()).collect();
+        let clock = Arc::new(FixedClock::new(chrono::Utc::now()));
+        let mut session = QuizSession::with_clock(Uuid::new_v4(), None, clock.clone());
+        session.start().unwrap();
+
+        clock.advance(Duration::seconds(40));
+        session
+            .submit_answer(&questions[0], Answer::TrueFalse(true), 40)
+            .unwrap();
+        // Skipped question leaves no entry
+        clock.advance(Duration::seconds(5));
+        session.skip_question(1);
+        clock.advance(Duration::seconds(25));
+        // Client under-reports this one
+        session
+            .submit_answer(&questions[2], Answer::TrueFalse(true), 10)
+            .unwrap();
+
+        let times = session.reconstruct_times();
+        assert_eq!(
+            times,
+            vec![
+                (questions[0].id, Duration::seconds(40)),
+                (questions[2].id, Duration::seconds(30)),
+            ]
+        );
+
+        let reported: Vec<u32> = session
+            .responses
+            .iter()
+            .map(|r| r.time_taken_seconds)
+            .collect();
+        assert_eq!(reported, vec![40, 10]);
+        assert!(times[1].1 > Duration::seconds(i64::from(reported[1])));
+
+        assert!(QuizSession::new(Uuid::new_v4(), None)
+            .reconstruct_times()
+            .is_empty());
+    }
 }
@@ -0,0 +1,232 @@
+//! Redacted, answer-free views of quizzes for sharing with students and
+//! rendering in the UI
+
+use super::question::{MediaAsset, Question, QuestionType, RubricCriterion};
+use super::quiz_impl::Quiz;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A question type with every correct answer, explanation and grading hint
+/// removed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "data")]
+pub enum StudentQuestionType {
+    TrueFalse {
+        statement: String,
+    },
+    MultipleChoice {
+        question: String,
+        options: Vec<String>,
+    },
+    MultiSelect {
+        question: String,
+        options: Vec<String>,
+    },
+    FillInTheBlank {
+        template: String,
+        blank_count: usize,
+    },
+    MatchPairs {
+        instruction: String,
+        left_items: Vec<String>,
+        right_items: Vec<String>,
+    },
+    InteractiveInterview {
+        topic: String,
+        initial_question: String,
+    },
+    TopicExplanation {
+        topic: String,
+        prompt: String,
+        min_word_count: usize,
+    },
+    Essay {
+        prompt: String,
+        rubric: Vec<RubricCriterion>,
+        max_words: Option<usize>,
+    },
+    /// Custom data may hold the answer, so only the kind is shared
+    Custom {
+        kind: String,
+    },
+}
+
+impl From<&QuestionType> for StudentQuestionType {
+    fn from(question_type: &QuestionType) -> Self {
+        match question_type.clone() {
+            QuestionType::TrueFalse { statement, .. } => Self::TrueFalse { statement },
+            QuestionType::MultipleChoice {
+                question, options, ..
+            } => Self::MultipleChoice { question, options },
+            QuestionType::MultiSelect {
+                question, options, ..
+            } => Self::MultiSelect { question, options },
+            QuestionType::FillInTheBlank {
+                template,
+                correct_answers,
+                ..
+            } => Self::FillInTheBlank {
+                template,
+                blank_count: correct_answers.len(),
+            },
+            QuestionType::MatchPairs {
+                instruction,
+                left_items,
+                right_items,
+                ..
+            } => Self::MatchPairs {
+                instruction,
+                left_items,
+                right_items,
+            },
+            QuestionType::InteractiveInterview {
+                topic,
+                initial_question,
+                ..
+            } => Self::InteractiveInterview {
+                topic,
+                initial_question,
+            },
+            QuestionType::TopicExplanation {
+                topic,
+                prompt,
+                min_word_count,
+                ..
+            } => Self::TopicExplanation {
+                topic,
+                prompt,
+                min_word_count,
+            },
+            QuestionType::Essay {
+                prompt,
+                rubric,
+                max_words,
+            } => Self::Essay {
+                prompt,
+                rubric,
+                max_words,
+            },
+            QuestionType::Custom { kind, .. } => Self::Custom { kind },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StudentQuestion {
+    pub id: Uuid,
+    pub question_type: StudentQuestionType,
+    pub topic_id: Uuid,
+    pub difficulty: f32,
+    pub estimated_time_seconds: u32,
+    pub tags: Vec<String>,
+    pub media: Vec<MediaAsset>,
+}
+
+impl From<&Question> for StudentQuestion {
+    fn from(question: &Question) -> Self {
+        Self {
+            id: question.id,
+            question_type: StudentQuestionType::from(&question.question_type),
+            topic_id: question.topic_id,
+            difficulty: question.difficulty,
+            estimated_time_seconds: question.estimated_time_seconds,
+            tags: question.tags.clone(),
+            media: question.media.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StudentQuiz {
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub questions: Vec<StudentQuestion>,
+    pub estimated_duration_minutes: u32,
+    pub pass_threshold: f32,
+    pub allow_skip: bool,
+    pub tags: Vec<String>,
+}
+
+impl From<&Quiz> for StudentQuiz {
+    fn from(quiz: &Quiz) -> Self {
+        Self {
+            id: quiz.id,
+            title: quiz.title.clone(),
+            description: quiz.description.clone(),
+            questions: quiz.questions.iter().map(StudentQuestion::from).collect(),
+            estimated_duration_minutes: quiz.estimated_duration_minutes,
+            pass_threshold: quiz.pass_threshold,
+            allow_skip: quiz.allow_skip,
+            tags: quiz.tags.clone(),
+        }
+    }
+}
+
+/// Read-only snapshot of a question for rendering, without answer data
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuestionView {
+    pub id: Uuid,
+    pub content: StudentQuestionType,
+    pub media: Vec<MediaAsset>,
+}
+
+impl QuestionView {
+    /// The main text shown to the learner
+    pub fn text(&self) -> &str {
+        match &self.content {
+            StudentQuestionType::TrueFalse { statement } => statement,
+            StudentQuestionType::MultipleChoice { question, .. }
+            | StudentQuestionType::MultiSelect { question, .. } => question,
+            StudentQuestionType::FillInTheBlank { template, .. } => template,
+            StudentQuestionType::MatchPairs { instruction, .. } => instruction,
+            StudentQuestionType::InteractiveInterview {
+                initial_question, ..
+            } => initial_question,
+            StudentQuestionType::TopicExplanation { prompt, .. }
+            | StudentQuestionType::Essay { prompt, .. } => prompt,
+            StudentQuestionType::Custom { .. } => "",
+        }
+    }
+
+    /// Selectable options, or the left-hand items for matching questions.
+    /// Empty for free-response types.
+    pub fn options(&self) -> &[String] {
+        match &self.content {
+            StudentQuestionType::MultipleChoice { options, .. }
+            | StudentQuestionType::MultiSelect { options, .. } => options,
+            StudentQuestionType::MatchPairs { left_items, .. } => left_items,
+            _ => &[],
+        }
+    }
+}
+
+impl From<&Question> for QuestionView {
+    fn from(question: &Question) -> Self {
+        Self {
+            id: question.id,
+            content: StudentQuestionType::from(&question.question_type),
+            media: question.media.clone(),
+        }
+    }
+}
+
+/// Read-only snapshot of a quiz for rendering, without answer data
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuizView {
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub questions: Vec<QuestionView>,
+}
+
+impl From<&Quiz> for QuizView {
+    fn from(quiz: &Quiz) -> Self {
+        Self {
+            id: quiz.id,
+            title: quiz.title.clone(),
+            description: quiz.description.clone(),
+            questions: quiz.questions.iter().map(QuestionView::from).collect(),
+        }
+    }
+}
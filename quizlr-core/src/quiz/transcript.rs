@@ -0,0 +1,91 @@
+//! Self-contained record of a single quiz attempt
+
+use super::quiz_impl::Quiz;
+use super::scoring::{Score, ScoringStrategy};
+use super::session::{QuizSession, SessionSummary};
+use serde::{Deserialize, Serialize};
+
+/// The quiz, the session taken against it and the resulting score, kept
+/// together so the attempt can be audited without any other data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transcript {
+    pub quiz: Quiz,
+    pub session: QuizSession,
+    pub score: Score,
+    pub summary: SessionSummary,
+}
+
+impl Transcript {
+    pub fn build(quiz: Quiz, session: QuizSession, strategy: &ScoringStrategy) -> Self {
+        let score = strategy.calculate_score(&session, &quiz.questions);
+        let summary = session.generate_summary();
+        Self {
+            quiz,
+            session,
+            score,
+            summary,
+        }
+    }
+
+    pub fn to_json(&self) -> crate::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> crate::Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quiz::{Answer, Question, QuestionType, QuizBuilder};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_transcript_round_trip() {
+        let questions: Vec<Question> = [true, false, true]
+            .into_iter()
+            .map(|answer| {
+                Question::new(
+                    QuestionType::TrueFalse {
+                        statement: "Statement".to_string(),
+                        correct_answer: answer,
+                        explanation: None,
+                    },
+                    Uuid::new_v4(),
+                    0.5,
+                )
+            })
+            .collect();
+        let quiz = QuizBuilder::new("Audit".to_string())
+            .add_questions(questions)
+            .build();
+
+        let mut session = QuizSession::new(quiz.id, None);
+        session.start().unwrap();
+        for question in &quiz.questions {
+            session
+                .submit_answer(question, Answer::TrueFalse(true), 20)
+                .unwrap();
+        }
+        session.complete().unwrap();
+
+        let strategy = ScoringStrategy::TimeWeighted {
+            base_time_seconds: 15,
+            penalty_per_second: 0.01,
+        };
+        let transcript = Transcript::build(quiz, session, &strategy);
+        let restored = Transcript::from_json(&transcript.to_json().unwrap()).unwrap();
+
+        assert_eq!(restored.quiz.id, transcript.quiz.id);
+        assert_eq!(restored.session.id, transcript.session.id);
+        assert_eq!(restored.summary.correct_answers, 2);
+        assert_eq!(restored.score.raw_score, transcript.score.raw_score);
+
+        let recomputed = strategy.calculate_score(&restored.session, &restored.quiz.questions);
+        assert_eq!(recomputed.raw_score, restored.score.raw_score);
+        assert_eq!(recomputed.weighted_score, restored.score.weighted_score);
+        assert_eq!(recomputed.time_bonus, restored.score.time_bonus);
+    }
+}
@@ -0,0 +1,75 @@
+//! Streaming JSON Lines export of quiz sessions
+//!
+//! Each session is written as one JSON object per line, so large exports
+//! never need to be held in memory at once and new sessions can be appended
+//! to an existing file.
+
+use crate::error::{QuizlrError, Result};
+use crate::quiz::QuizSession;
+use std::io::{BufRead, Write};
+
+fn io_error(error: std::io::Error) -> QuizlrError {
+    QuizlrError::Storage(error.to_string())
+}
+
+pub fn write_sessions_jsonl(
+    sessions: impl Iterator<Item = QuizSession>,
+    mut writer: impl Write,
+) -> Result<()> {
+    for session in sessions {
+        serde_json::to_writer(&mut writer, &session)?;
+        writer.write_all(b"\n").map_err(io_error)?;
+    }
+    writer.flush().map_err(io_error)
+}
+
+/// Read sessions back one line at a time. Blank lines are skipped.
+pub fn read_sessions_jsonl(reader: impl BufRead) -> impl Iterator<Item = Result<QuizSession>> {
+    reader.lines().filter_map(|line| match line {
+        Ok(line) if line.trim().is_empty() => None,
+        Ok(line) => Some(serde_json::from_str(&line).map_err(QuizlrError::from)),
+        Err(error) => Some(Err(io_error(error))),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_round_trip_three_sessions() {
+        let quiz_id = Uuid::new_v4();
+        let sessions: Vec<QuizSession> = (0..3)
+            .map(|_| QuizSession::new(quiz_id, Some(Uuid::new_v4())))
+            .collect();
+
+        let mut buffer = Vec::new();
+        write_sessions_jsonl(sessions.clone().into_iter(), &mut buffer).unwrap();
+
+        let text = String::from_utf8(buffer.clone()).unwrap();
+        assert_eq!(text.lines().count(), 3);
+
+        let restored: Vec<QuizSession> = read_sessions_jsonl(buffer.as_slice())
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(restored.len(), 3);
+        for (original, restored) in sessions.iter().zip(&restored) {
+            assert_eq!(original.id, restored.id);
+            assert_eq!(original.user_id, restored.user_id);
+        }
+    }
+
+    #[test]
+    fn test_appending_and_bad_lines() {
+        let mut buffer = Vec::new();
+        let first = QuizSession::new(Uuid::new_v4(), None);
+        write_sessions_jsonl(std::iter::once(first), &mut buffer).unwrap();
+        buffer.extend_from_slice(b"\nnot json\n");
+
+        let results: Vec<Result<QuizSession>> = read_sessions_jsonl(buffer.as_slice()).collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+    }
+}
@@ -0,0 +1,270 @@
+//! Filesystem storage backend
+//!
+//! Each key maps to a file beneath a root directory, with `/` in keys
+//! creating subdirectories.
+//!
+//! Saves are atomic: data is written and synced to a temporary file in the
+//! same directory, then renamed over the destination. On POSIX systems
+//! `rename` replaces the destination atomically, so readers see either the
+//! old contents or the new contents, never a partial file. On Windows the
+//! replace is not guaranteed atomic, but a failed rename leaves the original
+//! file untouched and the temporary file is removed.
+
+use super::Storage;
+use crate::error::QuizlrError;
+use async_trait::async_trait;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+const TEMP_MARKER: &str = ".tmp-";
+
+pub struct FileSystemStorage {
+    root: PathBuf,
+}
+
+impl FileSystemStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf, QuizlrError> {
+        let relative = Path::new(key);
+        let is_safe = !key.is_empty()
+            && relative
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)));
+        if !is_safe {
+            return Err(QuizlrError::InvalidInput(format!(
+                "Invalid storage key: {}",
+                key
+            )));
+        }
+        Ok(self.root.join(relative))
+    }
+
+    /// Write `data` to a fresh temporary file next to `path` and sync it to
+    /// disk. The destination is not touched until [`Self::commit`].
+    async fn write_temp(&self, path: &Path, data: &[u8]) -> Result<PathBuf, QuizlrError> {
+        let parent = path.parent().unwrap_or(&self.root);
+        fs::create_dir_all(parent).await.map_err(io_error)?;
+
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let temp_path = parent.join(format!(".{}{}{}", file_name, TEMP_MARKER, Uuid::new_v4()));
+
+        let result = async {
+            let mut file = fs::File::create(&temp_path).await?;
+            file.write_all(data).await?;
+            file.sync_all().await
+        }
+        .await;
+
+        if let Err(error) = result {
+            let _ = fs::remove_file(&temp_path).await;
+            return Err(io_error(error));
+        }
+        Ok(temp_path)
+    }
+
+    async fn commit(&self, temp_path: &Path, path: &Path) -> Result<(), QuizlrError> {
+        if let Err(error) = fs::rename(temp_path, path).await {
+            let _ = fs::remove_file(temp_path).await;
+            return Err(io_error(error));
+        }
+        Ok(())
+    }
+}
+
+fn io_error(error: std::io::Error) -> QuizlrError {
+    QuizlrError::Storage(error.to_string())
+}
+
+/// Whether `path` has the `.{name}.tmp-{uuid}` shape `write_temp` gives
+/// its files, so keys that merely contain the marker are still listed
+fn is_temp_file(path: &Path) -> bool {
+    let Some(file_name) = path.file_name() else {
+        return false;
+    };
+    let file_name = file_name.to_string_lossy();
+    let Some(rest) = file_name.strip_prefix('.') else {
+        return false;
+    };
+    rest.rsplit_once(TEMP_MARKER)
+        .is_some_and(|(name, suffix)| !name.is_empty() && Uuid::parse_str(suffix).is_ok())
+}
+
+#[async_trait]
+impl Storage for FileSystemStorage {
+    async fn save(&self, key: &str, data: &[u8]) -> Result<(), QuizlrError> {
+        let path = self.path_for(key)?;
+        let temp_path = self.write_temp(&path, data).await?;
+        self.commit(&temp_path, &path).await
+    }
+
+    async fn load(&self, key: &str) -> Result<Vec<u8>, QuizlrError> {
+        let path = self.path_for(key)?;
+        match fs::read(&path).await {
+            Ok(data) => Ok(data),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                Err(QuizlrError::NotFound(key.to_string()))
+            }
+            Err(error) => Err(io_error(error)),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), QuizlrError> {
+        let path = self.path_for(key)?;
+        match fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(io_error(error)),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, QuizlrError> {
+        let mut keys = Vec::new();
+        let mut pending = vec![self.root.clone()];
+
+        while let Some(dir) = pending.pop() {
+            let mut entries = match fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(error) => return Err(io_error(error)),
+            };
+
+            while let Some(entry) = entries.next_entry().await.map_err(io_error)? {
+                let path = entry.path();
+                let file_type = entry.file_type().await.map_err(io_error)?;
+                if file_type.is_dir() {
+                    pending.push(path);
+                } else if !is_temp_file(&path) {
+                    if let Ok(relative) = path.strip_prefix(&self.root) {
+                        let key = relative
+                            .components()
+                            .map(|c| c.as_os_str().to_string_lossy())
+                            .collect::<Vec<_>>()
+                            .join("/");
+                        if key.starts_with(prefix) {
+                            keys.push(key);
+                        }
+                    }
+                }
+            }
+        }
+
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root() -> PathBuf {
+        std::env::temp_dir().join(format!("quizlr-fs-test-{}", Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn test_save_load_list_delete() {
+        let root = temp_root();
+        let storage = FileSystemStorage::new(&root);
+
+        storage.save("quizzes/rust", b"rust").await.unwrap();
+        storage.save("quizzes/go", b"go").await.unwrap();
+        storage.save("sessions/1", b"s1").await.unwrap();
+
+        assert_eq!(
+            storage.load("quizzes/rust").await.unwrap(),
+            b"rust".to_vec()
+        );
+        assert_eq!(
+            storage.list("quizzes/").await.unwrap(),
+            vec!["quizzes/go".to_string(), "quizzes/rust".to_string()]
+        );
+
+        storage.delete("quizzes/rust").await.unwrap();
+        assert!(matches!(
+            storage.load("quizzes/rust").await,
+            Err(QuizlrError::NotFound(_))
+        ));
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[tokio::test]
+    async fn test_save_overwrites_existing() {
+        let root = temp_root();
+        let storage = FileSystemStorage::new(&root);
+
+        storage.save("quiz", b"first version").await.unwrap();
+        storage.save("quiz", b"second").await.unwrap();
+
+        assert_eq!(storage.load("quiz").await.unwrap(), b"second".to_vec());
+        assert_eq!(storage.list("").await.unwrap(), vec!["quiz".to_string()]);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_save_leaves_original_intact() {
+        let root = temp_root();
+        let storage = FileSystemStorage::new(&root);
+        storage.save("quiz", b"original contents").await.unwrap();
+
+        // Simulate a crash after the temp file is written but before rename
+        let path = storage.path_for("quiz").unwrap();
+        let temp_path = storage.write_temp(&path, b"new").await.unwrap();
+        assert!(temp_path.exists());
+
+        assert_eq!(
+            storage.load("quiz").await.unwrap(),
+            b"original contents".to_vec()
+        );
+        // Leftover temp files are never reported as keys
+        assert_eq!(storage.list("").await.unwrap(), vec!["quiz".to_string()]);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[tokio::test]
+    async fn test_keys_containing_temp_marker_are_listed() {
+        let root = temp_root();
+        let storage = FileSystemStorage::new(&root);
+
+        storage.save("notes.tmp-draft", b"a").await.unwrap();
+        storage.save(".hidden.tmp-1", b"b").await.unwrap();
+        assert_eq!(
+            storage.list("").await.unwrap(),
+            vec![".hidden.tmp-1".to_string(), "notes.tmp-draft".to_string()]
+        );
+
+        let path = storage.path_for("quiz").unwrap();
+        storage.write_temp(&path, b"pending").await.unwrap();
+        assert_eq!(storage.list("").await.unwrap().len(), 2);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_escaping_keys() {
+        let storage = FileSystemStorage::new(temp_root());
+
+        assert!(matches!(
+            storage.save("../outside", b"x").await,
+            Err(QuizlrError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            storage.load("/etc/passwd").await,
+            Err(QuizlrError::InvalidInput(_))
+        ));
+    }
+}
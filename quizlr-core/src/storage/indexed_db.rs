@@ -0,0 +1,175 @@
+//! IndexedDB-backed storage for the browser
+//!
+//! localStorage is capped at roughly 5MB, which is too small for large
+//! question banks. IndexedDB has no such practical limit, so this backend
+//! stores each payload as a byte array in a single object store keyed by
+//! the storage key.
+
+use super::Storage;
+use crate::error::QuizlrError;
+use async_trait::async_trait;
+use indexed_db_futures::prelude::*;
+use js_sys::Uint8Array;
+use std::ops::Deref;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::DomException;
+
+const DEFAULT_STORE: &str = "quizlr";
+
+/// Storage backend over the browser IndexedDB API.
+///
+/// The database is opened per operation rather than held open, which keeps
+/// the struct `Send + Sync` and lets the browser manage connection lifetime.
+/// Several backends can share one database by using different stores; a
+/// store missing from an existing database is added by upgrading it.
+pub struct IndexedDbStorage {
+    db_name: String,
+    store_name: String,
+}
+
+impl IndexedDbStorage {
+    pub fn new(db_name: impl Into<String>) -> Self {
+        Self {
+            db_name: db_name.into(),
+            store_name: DEFAULT_STORE.to_string(),
+        }
+    }
+
+    pub fn with_store(mut self, store_name: impl Into<String>) -> Self {
+        self.store_name = store_name.into();
+        self
+    }
+
+    async fn open(&self) -> Result<Connection, QuizlrError> {
+        // Opening at the current version never runs an upgrade, so a store
+        // this database doesn't have yet is created one version up
+        let db = self.open_at(None).await?;
+        if db.object_store_names().any(|name| name == self.store_name) {
+            return Ok(db);
+        }
+
+        let next_version = db.version() as u32 + 1;
+        drop(db);
+        self.open_at(Some(next_version)).await
+    }
+
+    async fn open_at(&self, version: Option<u32>) -> Result<Connection, QuizlrError> {
+        let mut request = match version {
+            Some(version) => IdbDatabase::open_u32(&self.db_name, version),
+            None => IdbDatabase::open(&self.db_name),
+        }
+        .map_err(dom_error)?;
+
+        let store_name = self.store_name.clone();
+        request.set_on_upgrade_needed(Some(
+            move |event: &IdbVersionChangeEvent| -> Result<(), JsValue> {
+                if !event
+                    .db()
+                    .object_store_names()
+                    .any(|name| name == store_name)
+                {
+                    event.db().create_object_store(&store_name)?;
+                }
+                Ok(())
+            },
+        ));
+
+        request.await.map(Connection).map_err(dom_error)
+    }
+}
+
+/// An open database, closed when dropped. A connection left open would
+/// block the upgrade that adds another store to the same database.
+struct Connection(IdbDatabase);
+
+impl Deref for Connection {
+    type Target = IdbDatabase;
+
+    fn deref(&self) -> &IdbDatabase {
+        &self.0
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+fn dom_error(error: DomException) -> QuizlrError {
+    QuizlrError::Storage(format!("IndexedDB error: {}", error.message()))
+}
+
+fn js_error(error: JsValue) -> QuizlrError {
+    QuizlrError::Storage(format!("IndexedDB error: {:?}", error))
+}
+
+#[async_trait(?Send)]
+impl Storage for IndexedDbStorage {
+    async fn save(&self, key: &str, data: &[u8]) -> Result<(), QuizlrError> {
+        let db = self.open().await?;
+        let tx = db
+            .transaction_on_one_with_mode(&self.store_name, IdbTransactionMode::Readwrite)
+            .map_err(dom_error)?;
+        let store = tx.object_store(&self.store_name).map_err(dom_error)?;
+
+        let value = Uint8Array::from(data);
+        store.put_key_val_owned(key, &value).map_err(dom_error)?;
+
+        tx.await.into_result().map_err(dom_error)?;
+        Ok(())
+    }
+
+    async fn load(&self, key: &str) -> Result<Vec<u8>, QuizlrError> {
+        let db = self.open().await?;
+        let tx = db.transaction_on_one(&self.store_name).map_err(dom_error)?;
+        let store = tx.object_store(&self.store_name).map_err(dom_error)?;
+
+        let value: Option<JsValue> = store
+            .get_owned(key)
+            .map_err(dom_error)?
+            .await
+            .map_err(dom_error)?;
+
+        match value {
+            Some(value) => {
+                let bytes = value.dyn_into::<Uint8Array>().map_err(js_error)?;
+                Ok(bytes.to_vec())
+            }
+            None => Err(QuizlrError::NotFound(key.to_string())),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), QuizlrError> {
+        let db = self.open().await?;
+        let tx = db
+            .transaction_on_one_with_mode(&self.store_name, IdbTransactionMode::Readwrite)
+            .map_err(dom_error)?;
+        let store = tx.object_store(&self.store_name).map_err(dom_error)?;
+
+        store.delete_owned(key).map_err(dom_error)?;
+
+        tx.await.into_result().map_err(dom_error)?;
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, QuizlrError> {
+        let db = self.open().await?;
+        let tx = db.transaction_on_one(&self.store_name).map_err(dom_error)?;
+        let store = tx.object_store(&self.store_name).map_err(dom_error)?;
+
+        let keys = store
+            .get_all_keys()
+            .map_err(dom_error)?
+            .await
+            .map_err(dom_error)?;
+
+        let mut keys: Vec<String> = keys
+            .iter()
+            .filter_map(|key| key.as_string())
+            .filter(|key| key.starts_with(prefix))
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+}
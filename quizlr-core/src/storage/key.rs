@@ -0,0 +1,249 @@
+//! Typed storage keys and the record helpers built on them
+//!
+//! Every record kind lives under its own `kind/` prefix, and names are
+//! escaped so they can never contain the separator. A listing by one kind's
+//! prefix therefore never picks up another kind's keys.
+
+use super::migrate::{from_versioned_json, to_versioned_json};
+use super::Storage;
+use crate::curriculum::Curriculum;
+use crate::quiz::{Quiz, QuizSession};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use uuid::Uuid;
+
+const QUIZ_KIND: &str = "quizzes";
+const SESSION_KIND: &str = "sessions";
+const CURRICULUM_KIND: &str = "curricula";
+const BLOB_KIND: &str = "blobs";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StorageKey(String);
+
+impl StorageKey {
+    /// Key for `name` under `kind`. `/` and `%` in the name are
+    /// percent-escaped so the name stays a single path segment.
+    pub fn new(kind: &str, name: &str) -> Self {
+        Self(format!("{}{}", Self::prefix(kind), escape(name)))
+    }
+
+    pub fn quiz(id: Uuid) -> Self {
+        Self::new(QUIZ_KIND, &id.to_string())
+    }
+
+    pub fn session(id: Uuid) -> Self {
+        Self::new(SESSION_KIND, &id.to_string())
+    }
+
+    pub fn curriculum(id: Uuid) -> Self {
+        Self::new(CURRICULUM_KIND, &id.to_string())
+    }
+
+    /// Key for a content-addressed blob with the given hex digest
+    pub fn blob(digest: &str) -> Self {
+        Self::new(BLOB_KIND, digest)
+    }
+
+    /// Listing prefix for every key of `kind`
+    pub fn prefix(kind: &str) -> String {
+        format!("{}/", escape(kind))
+    }
+
+    pub fn quiz_prefix() -> String {
+        Self::prefix(QUIZ_KIND)
+    }
+
+    pub fn session_prefix() -> String {
+        Self::prefix(SESSION_KIND)
+    }
+
+    pub fn curriculum_prefix() -> String {
+        Self::prefix(CURRICULUM_KIND)
+    }
+
+    pub fn blob_prefix() -> String {
+        Self::prefix(BLOB_KIND)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The unescaped name part of the key
+    pub fn name(&self) -> String {
+        let escaped = self.0.split_once('/').map_or("", |(_, name)| name);
+        unescape(escaped)
+    }
+}
+
+impl fmt::Display for StorageKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for StorageKey {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+fn escape(segment: &str) -> String {
+    segment.replace('%', "%25").replace('/', "%2F")
+}
+
+fn unescape(segment: &str) -> String {
+    segment.replace("%2F", "/").replace("%25", "%")
+}
+
+/// Save, load and list quizzes, sessions and curricula by id on any
+/// backend, using [`StorageKey`]s and JSON encoding. Records are stamped
+/// with the schema version on save and migrated on load.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait TypedStorage: Storage {
+    async fn save_quiz(&self, quiz: &Quiz) -> crate::Result<()> {
+        save_json(self, &StorageKey::quiz(quiz.id), quiz).await
+    }
+
+    async fn load_quiz(&self, id: Uuid) -> crate::Result<Quiz> {
+        load_json(self, &StorageKey::quiz(id)).await
+    }
+
+    async fn list_quiz_ids(&self) -> crate::Result<Vec<Uuid>> {
+        list_ids(self, &StorageKey::quiz_prefix()).await
+    }
+
+    async fn save_session(&self, session: &QuizSession) -> crate::Result<()> {
+        save_json(self, &StorageKey::session(session.id), session).await
+    }
+
+    async fn load_session(&self, id: Uuid) -> crate::Result<QuizSession> {
+        load_json(self, &StorageKey::session(id)).await
+    }
+
+    async fn list_session_ids(&self) -> crate::Result<Vec<Uuid>> {
+        list_ids(self, &StorageKey::session_prefix()).await
+    }
+
+    async fn save_curriculum(&self, curriculum: &Curriculum) -> crate::Result<()> {
+        save_json(self, &StorageKey::curriculum(curriculum.id), curriculum).await
+    }
+
+    async fn load_curriculum(&self, id: Uuid) -> crate::Result<Curriculum> {
+        load_json(self, &StorageKey::curriculum(id)).await
+    }
+}
+
+impl<S: Storage + ?Sized> TypedStorage for S {}
+
+async fn save_json<S: Storage + ?Sized, T: Serialize + Sync>(
+    storage: &S,
+    key: &StorageKey,
+    value: &T,
+) -> crate::Result<()> {
+    storage.save(key.as_str(), &to_versioned_json(value)?).await
+}
+
+async fn load_json<S: Storage + ?Sized, T: DeserializeOwned>(
+    storage: &S,
+    key: &StorageKey,
+) -> crate::Result<T> {
+    let bytes = storage.load(key.as_str()).await?;
+    from_versioned_json(&bytes)
+}
+
+/// Ids of every key under `prefix`, skipping names that aren't ids
+async fn list_ids<S: Storage + ?Sized>(storage: &S, prefix: &str) -> crate::Result<Vec<Uuid>> {
+    let mut ids: Vec<Uuid> = storage
+        .list(prefix)
+        .await?
+        .iter()
+        .filter_map(|key| key.strip_prefix(prefix))
+        .filter_map(|name| Uuid::parse_str(&unescape(name)).ok())
+        .collect();
+    ids.sort();
+    Ok(ids)
+}
+
+impl From<StorageKey> for String {
+    fn from(key: StorageKey) -> Self {
+        key.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    #[test]
+    fn test_session_key_never_matches_quiz_prefix() {
+        let id = Uuid::new_v4();
+        let session = StorageKey::session(id);
+        let quiz = StorageKey::quiz(id);
+
+        assert!(quiz.as_str().starts_with(&StorageKey::quiz_prefix()));
+        assert!(!session.as_str().starts_with(&StorageKey::quiz_prefix()));
+        assert!(!quiz.as_str().starts_with(&StorageKey::session_prefix()));
+        assert_eq!(quiz.name(), id.to_string());
+    }
+
+    #[test]
+    fn test_names_are_escaped() {
+        let key = StorageKey::new("quizzes", "a/b%c");
+        assert_eq!(key.as_str(), "quizzes/a%2Fb%25c");
+        assert_eq!(key.name(), "a/b%c");
+
+        // A name can't smuggle in another kind's prefix
+        let sneaky = StorageKey::new("quiz", "zes/1");
+        assert!(!sneaky.as_str().starts_with(&StorageKey::quiz_prefix()));
+    }
+
+    #[tokio::test]
+    async fn test_typed_helpers_round_trip() {
+        let storage = MemoryStorage::new();
+        let quiz = Quiz::new("Stored".to_string());
+        let session = QuizSession::new(quiz.id, None);
+
+        storage.save_quiz(&quiz).await.unwrap();
+        storage.save_session(&session).await.unwrap();
+
+        assert_eq!(storage.load_quiz(quiz.id).await.unwrap().title, "Stored");
+        assert_eq!(storage.list_quiz_ids().await.unwrap(), vec![quiz.id]);
+        assert_eq!(storage.list_session_ids().await.unwrap(), vec![session.id]);
+        assert!(storage.load_quiz(session.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_typed_helpers_version_records() {
+        use crate::storage::migrate::{MigrationRunner, CURRENT_SCHEMA_VERSION};
+
+        let storage = MemoryStorage::new();
+        let quiz = Quiz::new("Versioned".to_string());
+        storage.save_quiz(&quiz).await.unwrap();
+
+        let key = StorageKey::quiz(quiz.id);
+        let stored: serde_json::Value =
+            serde_json::from_slice(&storage.load(key.as_str()).await.unwrap()).unwrap();
+        assert_eq!(
+            MigrationRunner::version_of(&stored).unwrap(),
+            CURRENT_SCHEMA_VERSION
+        );
+
+        // A quiz saved before versioning, without tags or metadata
+        let mut old = serde_json::to_value(&quiz).unwrap();
+        let object = old.as_object_mut().unwrap();
+        object.remove("tags");
+        object.remove("metadata");
+        storage
+            .save(key.as_str(), &serde_json::to_vec(&old).unwrap())
+            .await
+            .unwrap();
+        let loaded = storage.load_quiz(quiz.id).await.unwrap();
+        assert_eq!(loaded.title, "Versioned");
+        assert!(loaded.tags.is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+//! localStorage-backed storage for the browser
+//!
+//! localStorage only holds strings, so payloads are stored base64-encoded.
+//! It is capped at roughly 5MB per origin; large question banks belong in
+//! [`IndexedDbStorage`](super::IndexedDbStorage) instead. Unlike IndexedDB
+//! it is synchronous, so the inherent methods can be called without an
+//! executor, which is what the `QuizlrCore` bindings rely on.
+
+use super::Storage;
+use crate::error::QuizlrError;
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use wasm_bindgen::JsValue;
+
+/// Storage backend over `window.localStorage`.
+///
+/// Every key is prefixed with the backend's namespace, so several apps or
+/// test runs on one origin don't see each other's data. The `Storage`
+/// handle is looked up per operation, which keeps the struct `Send + Sync`.
+pub struct LocalStorageBackend {
+    prefix: String,
+}
+
+impl LocalStorageBackend {
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Load the bytes stored under `key`
+    pub fn get(&self, key: &str) -> Result<Vec<u8>, QuizlrError> {
+        let encoded = local_storage()?
+            .get_item(&self.full_key(key))
+            .map_err(js_error)?
+            .ok_or_else(|| QuizlrError::NotFound(key.to_string()))?;
+        STANDARD
+            .decode(encoded)
+            .map_err(|e| QuizlrError::Storage(format!("Corrupt localStorage entry {}: {}", key, e)))
+    }
+
+    /// Store `data` under `key`, replacing any previous value
+    pub fn put(&self, key: &str, data: &[u8]) -> Result<(), QuizlrError> {
+        local_storage()?
+            .set_item(&self.full_key(key), &STANDARD.encode(data))
+            .map_err(js_error)
+    }
+
+    pub fn remove(&self, key: &str) -> Result<(), QuizlrError> {
+        local_storage()?
+            .remove_item(&self.full_key(key))
+            .map_err(js_error)
+    }
+
+    /// Sorted keys under `prefix`, without the backend's namespace
+    pub fn keys(&self, prefix: &str) -> Result<Vec<String>, QuizlrError> {
+        let storage = local_storage()?;
+        let len = storage.length().map_err(js_error)?;
+
+        let mut keys = Vec::new();
+        for index in 0..len {
+            let Some(key) = storage.key(index).map_err(js_error)? else {
+                continue;
+            };
+            if let Some(key) = key.strip_prefix(&self.prefix) {
+                if key.starts_with(prefix) {
+                    keys.push(key.to_string());
+                }
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+
+    fn full_key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+}
+
+fn local_storage() -> Result<web_sys::Storage, QuizlrError> {
+    web_sys::window()
+        .ok_or_else(|| QuizlrError::Storage("No window available".to_string()))?
+        .local_storage()
+        .map_err(js_error)?
+        .ok_or_else(|| QuizlrError::Storage("localStorage is unavailable".to_string()))
+}
+
+fn js_error(error: JsValue) -> QuizlrError {
+    QuizlrError::Storage(format!("localStorage error: {:?}", error))
+}
+
+#[async_trait(?Send)]
+impl Storage for LocalStorageBackend {
+    async fn save(&self, key: &str, data: &[u8]) -> Result<(), QuizlrError> {
+        self.put(key, data)
+    }
+
+    async fn load(&self, key: &str) -> Result<Vec<u8>, QuizlrError> {
+        self.get(key)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), QuizlrError> {
+        self.remove(key)
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, QuizlrError> {
+        self.keys(prefix)
+    }
+}
@@ -0,0 +1,145 @@
+//! In-memory storage backend
+//!
+//! Useful for tests and for ephemeral sessions where nothing needs to
+//! survive a reload.
+
+use super::Storage;
+use crate::error::QuizlrError;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    entries: RwLock<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.read().map(|e| e.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn poisoned<T>(_: T) -> QuizlrError {
+    QuizlrError::Storage("Memory storage lock poisoned".to_string())
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Storage for MemoryStorage {
+    async fn save(&self, key: &str, data: &[u8]) -> Result<(), QuizlrError> {
+        self.entries
+            .write()
+            .map_err(poisoned)?
+            .insert(key.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    async fn load(&self, key: &str) -> Result<Vec<u8>, QuizlrError> {
+        self.entries
+            .read()
+            .map_err(poisoned)?
+            .get(key)
+            .cloned()
+            .ok_or_else(|| QuizlrError::NotFound(key.to_string()))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), QuizlrError> {
+        self.entries.write().map_err(poisoned)?.remove(key);
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, QuizlrError> {
+        let mut keys: Vec<String> = self
+            .entries
+            .read()
+            .map_err(poisoned)?
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_save_load_delete() {
+        let storage = MemoryStorage::new();
+        storage.save("a", b"hello").await.unwrap();
+
+        assert_eq!(storage.load("a").await.unwrap(), b"hello".to_vec());
+        assert_eq!(storage.len(), 1);
+
+        storage.delete("a").await.unwrap();
+        assert!(matches!(
+            storage.load("a").await,
+            Err(QuizlrError::NotFound(_))
+        ));
+        assert!(storage.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_by_prefix() {
+        let storage = MemoryStorage::new();
+        storage.save("quiz/2", b"").await.unwrap();
+        storage.save("quiz/1", b"").await.unwrap();
+        storage.save("session/1", b"").await.unwrap();
+
+        let keys = storage.list("quiz/").await.unwrap();
+        assert_eq!(keys, vec!["quiz/1".to_string(), "quiz/2".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_list_paged_in_chunks_of_two() {
+        let storage = MemoryStorage::new();
+        for key in ["q/a", "q/b", "q/c", "q/d", "q/e", "other/x"] {
+            storage.save(key, b"").await.unwrap();
+        }
+
+        let mut pages = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (page, next) = storage.list_paged("q/", cursor, 2).await.unwrap();
+            pages.push(page);
+            match next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        assert_eq!(
+            pages,
+            vec![
+                vec!["q/a".to_string(), "q/b".to_string()],
+                vec!["q/c".to_string(), "q/d".to_string()],
+                vec!["q/e".to_string()],
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_paged_exact_fit_and_zero_limit() {
+        let storage = MemoryStorage::new();
+        storage.save("a", b"").await.unwrap();
+        storage.save("b", b"").await.unwrap();
+
+        let (page, next) = storage.list_paged("", None, 2).await.unwrap();
+        assert_eq!(page.len(), 2);
+        assert!(next.is_none());
+
+        assert!(storage.list_paged("", None, 0).await.is_err());
+    }
+}
@@ -0,0 +1,369 @@
+//! Schema migrations for stored data
+//!
+//! Stored blobs carry a `schema_version` field. Blobs written before
+//! versioning existed have no such field and are treated as version 0. On
+//! load, [`MigrationRunner`] applies each registered [`Migration`] in turn
+//! until the blob reaches [`CURRENT_SCHEMA_VERSION`]. Blobs written through
+//! [`to_versioned_json`] are stamped with the current version, so they only
+//! go through the migrations added after they were saved.
+
+use crate::error::{QuizlrError, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+pub const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+pub trait Migration: Send + Sync {
+    fn source_version(&self) -> u32;
+    /// Must be greater than `source_version`
+    fn to_version(&self) -> u32;
+    fn migrate(&self, value: Value) -> Result<Value>;
+}
+
+/// v0 blobs predate the `tags` and `metadata` fields on quizzes and
+/// questions; fill them in with empty defaults.
+pub struct V0ToV1;
+
+impl V0ToV1 {
+    fn fill_defaults(object: &mut Map<String, Value>) {
+        object
+            .entry("tags")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        object
+            .entry("metadata")
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+}
+
+impl Migration for V0ToV1 {
+    fn source_version(&self) -> u32 {
+        0
+    }
+
+    fn to_version(&self) -> u32 {
+        1
+    }
+
+    fn migrate(&self, mut value: Value) -> Result<Value> {
+        let object = value
+            .as_object_mut()
+            .ok_or_else(|| QuizlrError::Storage("Expected a JSON object to migrate".to_string()))?;
+
+        Self::fill_defaults(object);
+
+        if let Some(Value::Array(questions)) = object.get_mut("questions") {
+            for question in questions.iter_mut().filter_map(Value::as_object_mut) {
+                Self::fill_defaults(question);
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+/// v1 sessions keep the hint penalty on the session and store the simple
+/// scoring strategy as a bare `"Simple"`. v2 moved the penalty onto the
+/// simple and adaptive strategies, which made `Simple` a struct variant.
+pub struct V1ToV2;
+
+impl Migration for V1ToV2 {
+    fn source_version(&self) -> u32 {
+        1
+    }
+
+    fn to_version(&self) -> u32 {
+        2
+    }
+
+    fn migrate(&self, mut value: Value) -> Result<Value> {
+        let object = value
+            .as_object_mut()
+            .ok_or_else(|| QuizlrError::Storage("Expected a JSON object to migrate".to_string()))?;
+
+        let penalty = object.remove("hint_penalty_per_use");
+        if let Some(strategy) = object.get_mut("scoring_strategy") {
+            upgrade_scoring_strategy(strategy);
+            if let (Some(penalty), Some(variants)) = (penalty, strategy.as_object_mut()) {
+                for (_, fields) in variants
+                    .iter_mut()
+                    .filter(|(name, _)| *name == "Simple" || *name == "Adaptive")
+                {
+                    if let Some(fields) = fields.as_object_mut() {
+                        fields
+                            .entry("hint_penalty_per_use")
+                            .or_insert_with(|| penalty.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+/// Rewrite a serialized scoring strategy from before v2 into its current
+/// shape: a bare `"Simple"` becomes `{"Simple": {}}`
+pub(crate) fn upgrade_scoring_strategy(strategy: &mut Value) {
+    if strategy.as_str() == Some("Simple") {
+        *strategy = serde_json::json!({ "Simple": {} });
+    }
+}
+
+pub struct MigrationRunner {
+    migrations: Vec<Box<dyn Migration>>,
+    target_version: u32,
+}
+
+impl MigrationRunner {
+    /// A runner with no migrations registered
+    pub fn empty(target_version: u32) -> Self {
+        Self {
+            migrations: Vec::new(),
+            target_version,
+        }
+    }
+
+    pub fn with_migration(mut self, migration: impl Migration + 'static) -> Self {
+        self.migrations.push(Box::new(migration));
+        self
+    }
+
+    pub fn target_version(&self) -> u32 {
+        self.target_version
+    }
+
+    /// Read the schema version of a blob, treating a missing field as 0
+    pub fn version_of(value: &Value) -> Result<u32> {
+        match value.get(SCHEMA_VERSION_FIELD) {
+            None => Ok(0),
+            Some(version) => version
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| {
+                    QuizlrError::Storage(format!("Invalid schema version: {}", version))
+                }),
+        }
+    }
+
+    pub fn migrate(&self, mut value: Value) -> Result<Value> {
+        let mut version = Self::version_of(&value)?;
+
+        if version > self.target_version {
+            return Err(QuizlrError::Storage(format!(
+                "Stored schema version {} is newer than supported version {}",
+                version, self.target_version
+            )));
+        }
+
+        while version < self.target_version {
+            let migration = self
+                .migrations
+                .iter()
+                .find(|m| m.source_version() == version)
+                .ok_or_else(|| {
+                    QuizlrError::Storage(format!("No migration from schema version {}", version))
+                })?;
+
+            if migration.to_version() <= version {
+                return Err(QuizlrError::Storage(format!(
+                    "Migration from schema version {} does not move forward (to {})",
+                    version,
+                    migration.to_version()
+                )));
+            }
+
+            value = migration.migrate(value)?;
+            version = migration.to_version();
+
+            if let Some(object) = value.as_object_mut() {
+                object.insert(SCHEMA_VERSION_FIELD.to_string(), Value::from(version));
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+impl Default for MigrationRunner {
+    fn default() -> Self {
+        Self::empty(CURRENT_SCHEMA_VERSION)
+            .with_migration(V0ToV1)
+            .with_migration(V1ToV2)
+    }
+}
+
+/// Encode `value` as JSON stamped with [`CURRENT_SCHEMA_VERSION`]
+pub fn to_versioned_json<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    let mut value = serde_json::to_value(value)?;
+    if let Some(object) = value.as_object_mut() {
+        object.insert(
+            SCHEMA_VERSION_FIELD.to_string(),
+            Value::from(CURRENT_SCHEMA_VERSION),
+        );
+    }
+    Ok(serde_json::to_vec(&value)?)
+}
+
+/// Decode a stored JSON blob, migrating it to the current schema first
+pub fn from_versioned_json<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    let value = serde_json::from_slice(bytes)?;
+    let migrated = MigrationRunner::default().migrate(value)?;
+    Ok(serde_json::from_value(migrated)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quiz::Quiz;
+    use serde_json::json;
+
+    fn v0_quiz() -> Value {
+        json!({
+            "id": "6f1c1c1e-7b9e-4a8c-9d3e-2f3a4b5c6d7e",
+            "title": "Old Quiz",
+            "description": null,
+            "questions": [{
+                "id": "0b6f8f6e-1c2d-4e5f-8a9b-0c1d2e3f4a5b",
+                "question_type": {
+                    "type": "TrueFalse",
+                    "data": {
+                        "statement": "Rust is memory safe",
+                        "correct_answer": true,
+                        "explanation": null
+                    }
+                },
+                "topic_id": "a1b2c3d4-e5f6-4a7b-8c9d-0e1f2a3b4c5d",
+                "difficulty": 0.5,
+                "estimated_time_seconds": 60,
+                "citations": [],
+                "created_at": "2024-01-01T00:00:00Z",
+                "updated_at": "2024-01-01T00:00:00Z"
+            }],
+            "topic_ids": ["a1b2c3d4-e5f6-4a7b-8c9d-0e1f2a3b4c5d"],
+            "difficulty_range": [0.5, 0.5],
+            "estimated_duration_minutes": 1,
+            "pass_threshold": 0.7,
+            "allow_skip": true,
+            "show_explanations": true,
+            "randomize_questions": false,
+            "randomize_options": false,
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z"
+        })
+    }
+
+    #[test]
+    fn test_migrate_v0_quiz_to_current() {
+        let old = v0_quiz();
+        assert!(serde_json::from_value::<Quiz>(old.clone()).is_err());
+
+        let migrated = MigrationRunner::default().migrate(old).unwrap();
+        assert_eq!(
+            MigrationRunner::version_of(&migrated).unwrap(),
+            CURRENT_SCHEMA_VERSION
+        );
+
+        let quiz: Quiz = serde_json::from_value(migrated).unwrap();
+        assert_eq!(quiz.title, "Old Quiz");
+        assert!(quiz.tags.is_empty());
+        assert_eq!(quiz.questions.len(), 1);
+        assert!(quiz.questions[0].metadata.is_empty());
+    }
+
+    #[test]
+    fn test_versioned_json_round_trip() {
+        let quiz = Quiz::new("Stamped".to_string());
+        let bytes = to_versioned_json(&quiz).unwrap();
+        let stored: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(
+            MigrationRunner::version_of(&stored).unwrap(),
+            CURRENT_SCHEMA_VERSION
+        );
+        let loaded: Quiz = from_versioned_json(&bytes).unwrap();
+        assert_eq!(loaded.id, quiz.id);
+
+        // Unversioned blobs are migrated on the way in
+        let old = serde_json::to_vec(&v0_quiz()).unwrap();
+        let loaded: Quiz = from_versioned_json(&old).unwrap();
+        assert_eq!(loaded.title, "Old Quiz");
+        assert!(loaded.tags.is_empty());
+    }
+
+    #[test]
+    fn test_v1_session_moves_hint_penalty_onto_strategy() {
+        use crate::quiz::{QuizSessionBuilder, ScoringStrategy};
+
+        let session = QuizSessionBuilder::new(uuid::Uuid::new_v4())
+            .strategy(ScoringStrategy::Simple {
+                hint_penalty_per_use: 0.0,
+            })
+            .build();
+        let mut old = serde_json::to_value(&session).unwrap();
+        let object = old.as_object_mut().unwrap();
+        object.insert(SCHEMA_VERSION_FIELD.to_string(), json!(1));
+        object.insert("scoring_strategy".to_string(), json!("Simple"));
+        object.insert("hint_penalty_per_use".to_string(), json!(0.25));
+
+        let bytes = serde_json::to_vec(&old).unwrap();
+        let migrated: crate::quiz::QuizSession = from_versioned_json(&bytes).unwrap();
+        match migrated.scoring_strategy {
+            Some(ScoringStrategy::Simple {
+                hint_penalty_per_use,
+            }) => assert_eq!(hint_penalty_per_use, 0.25),
+            other => panic!("expected a simple strategy, got {:?}", other),
+        }
+
+        // Sessions without a strategy just lose the old field
+        let value = json!({ "schema_version": 1, "hint_penalty_per_use": 0.5 });
+        let migrated = MigrationRunner::default().migrate(value).unwrap();
+        assert_eq!(migrated, json!({ "schema_version": 2 }));
+    }
+
+    #[test]
+    fn test_current_version_is_untouched() {
+        let value = json!({ "schema_version": CURRENT_SCHEMA_VERSION, "tags": ["kept"] });
+        let migrated = MigrationRunner::default().migrate(value.clone()).unwrap();
+        assert_eq!(migrated, value);
+    }
+
+    #[test]
+    fn test_newer_version_is_rejected() {
+        let value = json!({ "schema_version": CURRENT_SCHEMA_VERSION + 1 });
+        assert!(MigrationRunner::default().migrate(value).is_err());
+    }
+
+    #[test]
+    fn test_missing_migration_step() {
+        let runner = MigrationRunner::empty(2).with_migration(V0ToV1);
+        assert!(runner.migrate(json!({})).is_err());
+    }
+
+    struct Stuck;
+
+    impl Migration for Stuck {
+        fn source_version(&self) -> u32 {
+            1
+        }
+
+        fn to_version(&self) -> u32 {
+            1
+        }
+
+        fn migrate(&self, value: Value) -> Result<Value> {
+            Ok(value)
+        }
+    }
+
+    #[test]
+    fn test_non_forward_migration_is_rejected() {
+        let runner = MigrationRunner::empty(2)
+            .with_migration(V0ToV1)
+            .with_migration(Stuck);
+        assert!(matches!(
+            runner.migrate(json!({})),
+            Err(QuizlrError::Storage(_))
+        ));
+    }
+}
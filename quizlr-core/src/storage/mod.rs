@@ -1,5 +1,32 @@
+use crate::cancel::{cancellable, CancellationToken};
+use crate::error::QuizlrError;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub mod export;
+#[cfg(feature = "native")]
+mod filesystem;
+#[cfg(target_arch = "wasm32")]
+mod indexed_db;
+mod key;
+#[cfg(target_arch = "wasm32")]
+mod local_storage;
+mod memory;
+pub mod migrate;
+mod namespaced;
+
+#[cfg(feature = "native")]
+pub use filesystem::FileSystemStorage;
+#[cfg(target_arch = "wasm32")]
+pub use indexed_db::IndexedDbStorage;
+pub use key::{StorageKey, TypedStorage};
+#[cfg(target_arch = "wasm32")]
+pub use local_storage::LocalStorageBackend;
+pub use memory::MemoryStorage;
+pub use namespaced::NamespacedStorage;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum StorageBackend {
@@ -8,21 +35,223 @@ pub enum StorageBackend {
     Custom(String),
 }
 
-#[async_trait]
+/// A key-value store for quiz data.
+///
+/// Implementations must be drop-safe: if a `save` future is dropped before
+/// it completes, the key is left holding either its previous value or the
+/// new one, never a partial write. Reads and listings have no side effects.
+//
+// Browser storage handles are not `Send`, so futures are only required to be
+// `Send` on native targets.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 pub trait Storage: Send + Sync {
     async fn save(&self, key: &str, data: &[u8]) -> Result<(), crate::error::QuizlrError>;
     async fn load(&self, key: &str) -> Result<Vec<u8>, crate::error::QuizlrError>;
     async fn delete(&self, key: &str) -> Result<(), crate::error::QuizlrError>;
     async fn list(&self, prefix: &str) -> Result<Vec<String>, crate::error::QuizlrError>;
+
+    /// List at most `limit` keys under `prefix`, resuming after `cursor`.
+    ///
+    /// Returns the page and a cursor for the next page, or `None` once the
+    /// listing is exhausted. Cursors are opaque; the default implementation
+    /// slices the full sorted `list` result, which backends with native
+    /// pagination should override.
+    async fn list_paged(
+        &self,
+        prefix: &str,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> Result<(Vec<String>, Option<String>), crate::error::QuizlrError> {
+        if limit == 0 {
+            return Err(crate::error::QuizlrError::InvalidInput(
+                "Page limit must be greater than zero".to_string(),
+            ));
+        }
+
+        let mut keys = self.list(prefix).await?;
+        keys.sort();
+
+        let start = match &cursor {
+            Some(after) => keys.partition_point(|key| key <= after),
+            None => 0,
+        };
+        let end = (start + limit).min(keys.len());
+        let page = keys[start..end].to_vec();
+
+        let next_cursor = if end < keys.len() {
+            page.last().cloned()
+        } else {
+            None
+        };
+        Ok((page, next_cursor))
+    }
+
+    /// [`Storage::save`], abandoned with `QuizlrError::Cancelled` if `token`
+    /// is cancelled first
+    async fn save_cancellable(
+        &self,
+        key: &str,
+        data: &[u8],
+        token: &CancellationToken,
+    ) -> Result<(), crate::error::QuizlrError> {
+        cancellable(token, self.save(key, data)).await
+    }
+
+    /// [`Storage::load`], abandoned with `QuizlrError::Cancelled` if `token`
+    /// is cancelled first
+    async fn load_cancellable(
+        &self,
+        key: &str,
+        token: &CancellationToken,
+    ) -> Result<Vec<u8>, crate::error::QuizlrError> {
+        cancellable(token, self.load(key)).await
+    }
+}
+
+// Lets one backend be shared, e.g. by several namespaces.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<S: Storage + ?Sized> Storage for Arc<S> {
+    async fn save(&self, key: &str, data: &[u8]) -> Result<(), crate::error::QuizlrError> {
+        (**self).save(key, data).await
+    }
+
+    async fn load(&self, key: &str) -> Result<Vec<u8>, crate::error::QuizlrError> {
+        (**self).load(key).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), crate::error::QuizlrError> {
+        (**self).delete(key).await
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, crate::error::QuizlrError> {
+        (**self).list(prefix).await
+    }
+
+    async fn list_paged(
+        &self,
+        prefix: &str,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> Result<(Vec<String>, Option<String>), crate::error::QuizlrError> {
+        (**self).list_paged(prefix, cursor, limit).await
+    }
+}
+
+/// Constructs a storage backend from a backend-specific config string
+pub type StorageConstructor = fn(config: &str) -> Result<Box<dyn Storage>, QuizlrError>;
+
+/// Registry resolving `StorageBackend::Custom` names to implementations
+#[derive(Clone, Default)]
+pub struct StorageFactory {
+    constructors: HashMap<String, StorageConstructor>,
+}
+
+impl StorageFactory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, name: impl Into<String>, constructor: StorageConstructor) {
+        self.constructors.insert(name.into(), constructor);
+    }
+
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.constructors.contains_key(name)
+    }
+
+    pub fn create(&self, name: &str, config: &str) -> Result<Box<dyn Storage>, QuizlrError> {
+        let constructor = self
+            .constructors
+            .get(name)
+            .ok_or_else(|| QuizlrError::Config(format!("Unknown storage backend: {}", name)))?;
+        constructor(config)
+    }
 }
 
 pub struct StorageManager {
-    // Placeholder for storage implementation
+    factory: StorageFactory,
+    config: String,
 }
 
 impl StorageManager {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            factory: StorageFactory::new(),
+            config: String::new(),
+        }
+    }
+
+    pub fn with_factory(mut self, factory: StorageFactory) -> Self {
+        self.factory = factory;
+        self
+    }
+
+    /// Config passed to the backend when opening it, e.g. the data
+    /// directory for local storage on native targets
+    pub fn with_config(mut self, config: impl Into<String>) -> Self {
+        self.config = config.into();
+        self
+    }
+
+    pub fn factory_mut(&mut self) -> &mut StorageFactory {
+        &mut self.factory
+    }
+
+    pub fn open(&self, backend: &StorageBackend) -> Result<Box<dyn Storage>, QuizlrError> {
+        match backend {
+            StorageBackend::Local => Ok(self.open_local()),
+            StorageBackend::GitHub => Err(QuizlrError::Config(
+                "GitHub storage backend is not available yet".to_string(),
+            )),
+            StorageBackend::Custom(name) => self.factory.create(name, &self.config),
+        }
+    }
+
+    /// Save `bytes` under a key derived from their SHA-256 digest and
+    /// return that key. Identical content always maps to the same key, so
+    /// retrying a save is harmless and duplicates are stored once.
+    pub async fn save_content_addressed(
+        &self,
+        storage: &dyn Storage,
+        bytes: &[u8],
+    ) -> Result<String, QuizlrError> {
+        let digest: String = Sha256::digest(bytes)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        let key = StorageKey::blob(&digest);
+
+        // Rewriting an existing blob stores the same bytes again, which is
+        // cheaper than listing to find out whether it's there
+        storage.save(key.as_str(), bytes).await?;
+        Ok(key.into())
+    }
+
+    #[cfg(feature = "native")]
+    fn open_local(&self) -> Box<dyn Storage> {
+        let root = if self.config.is_empty() {
+            "quizlr-data"
+        } else {
+            self.config.as_str()
+        };
+        Box::new(FileSystemStorage::new(root))
+    }
+
+    #[cfg(all(not(feature = "native"), target_arch = "wasm32"))]
+    fn open_local(&self) -> Box<dyn Storage> {
+        let db_name = if self.config.is_empty() {
+            "quizlr"
+        } else {
+            self.config.as_str()
+        };
+        Box::new(IndexedDbStorage::new(db_name))
+    }
+
+    #[cfg(all(not(feature = "native"), not(target_arch = "wasm32")))]
+    fn open_local(&self) -> Box<dyn Storage> {
+        Box::new(MemoryStorage::new())
     }
 }
 
@@ -31,3 +260,97 @@ impl Default for StorageManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_backend(_config: &str) -> Result<Box<dyn Storage>, QuizlrError> {
+        Ok(Box::new(MemoryStorage::new()))
+    }
+
+    fn failing_backend(config: &str) -> Result<Box<dyn Storage>, QuizlrError> {
+        Err(QuizlrError::Config(format!("bad config: {}", config)))
+    }
+
+    #[tokio::test]
+    async fn test_open_registered_custom_backend() {
+        let mut factory = StorageFactory::new();
+        factory.register("memory", memory_backend);
+        let manager = StorageManager::new().with_factory(factory);
+
+        let storage = manager
+            .open(&StorageBackend::Custom("memory".to_string()))
+            .unwrap();
+        storage.save("key", b"value").await.unwrap();
+        assert_eq!(storage.load("key").await.unwrap(), b"value".to_vec());
+    }
+
+    #[test]
+    fn test_unknown_and_failing_backends() {
+        let mut manager = StorageManager::new().with_config("cfg");
+        manager.factory_mut().register("broken", failing_backend);
+
+        assert!(matches!(
+            manager.open(&StorageBackend::Custom("missing".to_string())),
+            Err(QuizlrError::Config(_))
+        ));
+        match manager.open(&StorageBackend::Custom("broken".to_string())) {
+            Err(QuizlrError::Config(message)) => assert_eq!(message, "bad config: cfg"),
+            _ => panic!("expected the constructor's error"),
+        }
+        assert!(manager.open(&StorageBackend::GitHub).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_content_addressed_save_is_idempotent() {
+        let manager = StorageManager::new();
+        let storage = MemoryStorage::new();
+
+        let first = manager
+            .save_content_addressed(&storage, b"same bytes")
+            .await
+            .unwrap();
+        let retry = manager
+            .save_content_addressed(&storage, b"same bytes")
+            .await
+            .unwrap();
+        assert_eq!(first, retry);
+        assert_eq!(
+            first,
+            "blobs/58100dc8fc06562ce3e578231dc948e083520ee49c4b4ee5a5a28bb4b4003feb"
+        );
+
+        let stored = storage.list(&StorageKey::blob_prefix()).await.unwrap();
+        assert_eq!(stored, vec![first.clone()]);
+        assert_eq!(storage.load(&first).await.unwrap(), b"same bytes".to_vec());
+
+        let other = manager
+            .save_content_addressed(&storage, b"other bytes")
+            .await
+            .unwrap();
+        assert_ne!(other, first);
+        assert_eq!(storage.list("blobs/").await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_save_leaves_key_unwritten() {
+        let storage = MemoryStorage::new();
+        let token = CancellationToken::new();
+        storage
+            .save_cancellable("key", b"first", &token)
+            .await
+            .unwrap();
+
+        token.cancel();
+        assert!(matches!(
+            storage.save_cancellable("key", b"second", &token).await,
+            Err(QuizlrError::Cancelled)
+        ));
+        assert!(matches!(
+            storage.load_cancellable("key", &token).await,
+            Err(QuizlrError::Cancelled)
+        ));
+        assert_eq!(storage.load("key").await.unwrap(), b"first".to_vec());
+    }
+}
@@ -0,0 +1,123 @@
+//! Key namespacing over another storage backend
+//!
+//! Lets several users or quiz collections share one backend without seeing
+//! each other's keys. Wrappers compose, so a namespace can itself be split
+//! into sub-namespaces.
+
+use super::Storage;
+use crate::error::QuizlrError;
+use async_trait::async_trait;
+
+pub struct NamespacedStorage<S: Storage> {
+    inner: S,
+    prefix: String,
+}
+
+impl<S: Storage> NamespacedStorage<S> {
+    pub fn new(inner: S, prefix: impl Into<String>) -> Self {
+        Self {
+            inner,
+            prefix: prefix.into(),
+        }
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn full_key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<S: Storage> Storage for NamespacedStorage<S> {
+    async fn save(&self, key: &str, data: &[u8]) -> Result<(), QuizlrError> {
+        self.inner.save(&self.full_key(key), data).await
+    }
+
+    async fn load(&self, key: &str) -> Result<Vec<u8>, QuizlrError> {
+        self.inner.load(&self.full_key(key)).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), QuizlrError> {
+        self.inner.delete(&self.full_key(key)).await
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, QuizlrError> {
+        let keys = self.inner.list(&self.full_key(prefix)).await?;
+        Ok(keys
+            .into_iter()
+            .filter_map(|key| key.strip_prefix(&self.prefix).map(str::to_string))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_namespaces_are_isolated() {
+        let backend = Arc::new(MemoryStorage::new());
+        let alice = NamespacedStorage::new(backend.clone(), "alice/");
+        let bob = NamespacedStorage::new(backend.clone(), "bob/");
+
+        alice.save("quiz", b"alice's quiz").await.unwrap();
+        bob.save("quiz", b"bob's quiz").await.unwrap();
+        bob.save("notes", b"bob's notes").await.unwrap();
+
+        assert_eq!(alice.load("quiz").await.unwrap(), b"alice's quiz".to_vec());
+        assert_eq!(bob.load("quiz").await.unwrap(), b"bob's quiz".to_vec());
+        assert!(alice.load("notes").await.is_err());
+
+        assert_eq!(alice.list("").await.unwrap(), vec!["quiz".to_string()]);
+        assert_eq!(
+            bob.list("").await.unwrap(),
+            vec!["notes".to_string(), "quiz".to_string()]
+        );
+        assert_eq!(backend.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_nested_namespaces_compose() {
+        let storage = NamespacedStorage::new(
+            NamespacedStorage::new(MemoryStorage::new(), "user/"),
+            "quizzes/",
+        );
+
+        storage.save("rust", b"data").await.unwrap();
+        assert_eq!(storage.list("").await.unwrap(), vec!["rust".to_string()]);
+
+        let backend = storage.into_inner().into_inner();
+        assert_eq!(
+            backend.list("").await.unwrap(),
+            vec!["user/quizzes/rust".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_only_affects_namespace() {
+        let backend = Arc::new(MemoryStorage::new());
+        let a = NamespacedStorage::new(backend.clone(), "a:");
+        let b = NamespacedStorage::new(backend.clone(), "b:");
+
+        a.save("key", b"1").await.unwrap();
+        b.save("key", b"2").await.unwrap();
+        a.delete("key").await.unwrap();
+
+        assert!(a.load("key").await.is_err());
+        assert_eq!(b.load("key").await.unwrap(), b"2".to_vec());
+    }
+}
@@ -6,6 +6,7 @@
 
 #[cfg(test)]
 pub mod builders {
+    use crate::graph::{KnowledgeGraph, TopicNode};
     use crate::quiz::{Answer, Question, QuestionType, Quiz, QuizBuilder};
     use uuid::Uuid;
 
@@ -59,6 +60,7 @@ pub mod builders {
                     question: question.to_string(),
                     options: options.into_iter().map(|s| s.to_string()).collect(),
                     correct_index,
+                    alternate_correct: Vec::new(),
                     explanation: None,
                 },
                 ..Self::new()
@@ -226,27 +228,21 @@ pub mod builders {
                 .build()
         }
 
+        /// Add a topic named `name` to `graph`, returning its id
+        pub fn add_topic(graph: &mut KnowledgeGraph, name: &str) -> Uuid {
+            let topic = TopicNode::new(name.to_string());
+            let id = topic.id;
+            graph.add_topic(topic);
+            id
+        }
+
         /// Create a set of correct answers for a quiz
         pub fn correct_answers_for(quiz: &Quiz) -> Vec<Answer> {
             quiz.questions
                 .iter()
-                .map(|q| match &q.question_type {
-                    QuestionType::TrueFalse { correct_answer, .. } => {
-                        Answer::TrueFalse(*correct_answer)
-                    }
-                    QuestionType::MultipleChoice { correct_index, .. } => {
-                        Answer::MultipleChoice(*correct_index)
-                    }
-                    QuestionType::MultiSelect {
-                        correct_indices, ..
-                    } => Answer::MultiSelect(correct_indices.clone()),
-                    QuestionType::FillInTheBlank {
-                        correct_answers, ..
-                    } => Answer::FillInTheBlank(correct_answers.clone()),
-                    QuestionType::MatchPairs { correct_pairs, .. } => {
-                        Answer::MatchPairs(correct_pairs.clone())
-                    }
-                    _ => panic!("Unsupported question type in test"),
+                .map(|q| {
+                    q.correct_answer()
+                        .expect("Unsupported question type in test")
                 })
                 .collect()
         }
@@ -0,0 +1,51 @@
+//! Browser tests for the IndexedDB storage backend
+//!
+//! Run with `wasm-pack test --headless --chrome quizlr-core`.
+
+#![cfg(target_arch = "wasm32")]
+
+use quizlr_core::storage::{IndexedDbStorage, Storage};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+async fn test_save_and_load_large_blob() {
+    let storage = IndexedDbStorage::new("quizlr-test-blob");
+
+    // Several kilobytes of non-trivial bytes
+    let data: Vec<u8> = (0..64 * 1024).map(|i| (i % 251) as u8).collect();
+
+    storage.save("bank/large", &data).await.unwrap();
+    let loaded = storage.load("bank/large").await.unwrap();
+
+    assert_eq!(loaded, data);
+}
+
+#[wasm_bindgen_test]
+async fn test_list_delete_and_missing_key() {
+    let storage = IndexedDbStorage::new("quizlr-test-list");
+
+    storage.save("quiz/a", b"a").await.unwrap();
+    storage.save("quiz/b", b"b").await.unwrap();
+    storage.save("other/c", b"c").await.unwrap();
+
+    let keys = storage.list("quiz/").await.unwrap();
+    assert_eq!(keys, vec!["quiz/a".to_string(), "quiz/b".to_string()]);
+
+    storage.delete("quiz/a").await.unwrap();
+    assert!(storage.load("quiz/a").await.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn test_two_stores_in_one_database() {
+    let quizzes = IndexedDbStorage::new("quizlr-test-stores").with_store("quizzes");
+    let sessions = IndexedDbStorage::new("quizlr-test-stores").with_store("sessions");
+
+    // The second store is added to a database that already exists
+    quizzes.save("key", b"quiz").await.unwrap();
+    sessions.save("key", b"session").await.unwrap();
+
+    assert_eq!(quizzes.load("key").await.unwrap(), b"quiz".to_vec());
+    assert_eq!(sessions.load("key").await.unwrap(), b"session".to_vec());
+}
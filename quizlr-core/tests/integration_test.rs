@@ -1,5 +1,5 @@
 use quizlr_core::quiz::{Answer, Question, QuestionType, QuizBuilder, QuizSession};
-use quizlr_core::quiz::{ScoringStrategy, SessionState};
+use quizlr_core::quiz::{DifficultyBuckets, ScoringStrategy, SessionState};
 use uuid::Uuid;
 
 #[test]
@@ -27,6 +27,7 @@ fn test_complete_quiz_workflow() {
                     "gem".to_string(),
                 ],
                 correct_index: 2,
+                alternate_correct: Vec::new(),
                 explanation: Some("Cargo is Rust's build system and package manager".to_string()),
             },
             topic_id,
@@ -89,7 +90,10 @@ fn test_complete_quiz_workflow() {
     assert!(summary.passed(0.7));
 
     // Test different scoring strategies
-    let simple_score = ScoringStrategy::Simple.calculate_score(&session, &questions);
+    let simple_score = ScoringStrategy::Simple {
+        hint_penalty_per_use: 0.0,
+    }
+    .calculate_score(&session, &questions);
     assert_eq!(simple_score.raw_score, 1.0);
 
     let time_weighted = ScoringStrategy::TimeWeighted {
@@ -103,6 +107,7 @@ fn test_complete_quiz_workflow() {
         easy_multiplier: 1.0,
         medium_multiplier: 1.5,
         hard_multiplier: 2.0,
+        buckets: DifficultyBuckets::default(),
     };
     let diff_score = difficulty_weighted.calculate_score(&session, &questions);
     assert!(diff_score.weighted_score > 0.0);
@@ -199,6 +204,7 @@ fn test_answer_validation_errors() {
             question: "Test".to_string(),
             options: vec!["A".to_string(), "B".to_string()],
             correct_index: 0,
+            alternate_correct: Vec::new(),
             explanation: None,
         },
         Uuid::new_v4(),
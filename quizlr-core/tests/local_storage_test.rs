@@ -0,0 +1,66 @@
+//! Browser tests for listing and opening quizzes persisted in localStorage
+//!
+//! Run with `wasm-pack test --headless --chrome quizlr-core`.
+
+#![cfg(target_arch = "wasm32")]
+
+use quizlr_core::quiz::{Question, QuestionType, Quiz, QuizBuilder};
+use quizlr_core::storage::{LocalStorageBackend, StorageKey, TypedStorage};
+use quizlr_core::QuizlrCore;
+use serde::Deserialize;
+use uuid::Uuid;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[derive(Debug, Deserialize)]
+struct Listing {
+    id: Uuid,
+    title: String,
+}
+
+fn quiz(title: &str) -> Quiz {
+    QuizBuilder::new(title.to_string())
+        .add_question(Question::new(
+            QuestionType::TrueFalse {
+                statement: "Rust is memory safe".to_string(),
+                correct_answer: true,
+                explanation: None,
+            },
+            Uuid::new_v4(),
+            0.5,
+        ))
+        .build()
+}
+
+#[wasm_bindgen_test]
+async fn test_list_and_open_saved_quizzes() {
+    let prefix = format!("quizlr-test-{}/", Uuid::new_v4());
+    let backend = LocalStorageBackend::new(prefix.as_str());
+    let first = quiz("Ownership");
+    let second = quiz("Lifetimes");
+    backend.save_quiz(&first).await.unwrap();
+    backend.save_quiz(&second).await.unwrap();
+    // Unreadable entries are skipped rather than failing the listing
+    backend
+        .put(StorageKey::quiz(Uuid::new_v4()).as_str(), b"not json")
+        .unwrap();
+
+    let mut core = QuizlrCore::new();
+    assert!(core.list_quizzes().is_err());
+    core.use_local_storage(&prefix);
+
+    let mut listings: Vec<Listing> =
+        serde_wasm_bindgen::from_value(core.list_quizzes().unwrap()).unwrap();
+    listings.sort_by(|a, b| a.title.cmp(&b.title));
+    assert_eq!(listings.len(), 2);
+    assert_eq!(listings[0].id, second.id);
+    assert_eq!(listings[0].title, "Lifetimes");
+    assert_eq!(listings[1].id, first.id);
+    assert_eq!(listings[1].title, "Ownership");
+
+    core.open_quiz(&first.id.to_string()).unwrap();
+    assert_eq!(core.quiz().unwrap().title, "Ownership");
+    assert!(core.open_quiz(&Uuid::new_v4().to_string()).is_err());
+    assert!(core.open_quiz("not an id").is_err());
+}
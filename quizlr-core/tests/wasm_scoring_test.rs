@@ -0,0 +1,73 @@
+//! Browser tests for the scoring bindings exposed to the web UI
+//!
+//! Run with `wasm-pack test --headless --chrome quizlr-core`.
+
+#![cfg(target_arch = "wasm32")]
+
+use quizlr_core::quiz::{Answer, Question, QuestionType, QuizBuilder, QuizSession, Score};
+use quizlr_core::QuizlrCore;
+use uuid::Uuid;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn loaded_core() -> QuizlrCore {
+    let topic_id = Uuid::new_v4();
+    let q1 = Question::new(
+        QuestionType::TrueFalse {
+            statement: "Rust is memory safe".to_string(),
+            correct_answer: true,
+            explanation: None,
+        },
+        topic_id,
+        0.3,
+    );
+    let q2 = Question::new(
+        QuestionType::MultipleChoice {
+            question: "What is 2+2?".to_string(),
+            options: vec!["3".to_string(), "4".to_string()],
+            correct_index: 1,
+            alternate_correct: Vec::new(),
+            explanation: None,
+        },
+        topic_id,
+        0.3,
+    );
+    let quiz = QuizBuilder::new("Wasm".to_string())
+        .add_question(q1.clone())
+        .add_question(q2.clone())
+        .build();
+
+    let mut session = QuizSession::new(quiz.id, None);
+    session.start().unwrap();
+    session
+        .submit_answer(&q1, Answer::TrueFalse(true), 10)
+        .unwrap();
+    session
+        .submit_answer(&q2, Answer::MultipleChoice(0), 10)
+        .unwrap();
+
+    let mut core = QuizlrCore::new();
+    core.load_quiz(&serde_json::to_string(&quiz).unwrap())
+        .unwrap();
+    core.load_session(&serde_json::to_string(&session).unwrap())
+        .unwrap();
+    core
+}
+
+#[wasm_bindgen_test]
+fn test_score_session_simple() {
+    let core = loaded_core();
+
+    let value = core.score_session(r#""Simple""#).unwrap();
+    let score: Score = serde_wasm_bindgen::from_value(value).unwrap();
+
+    assert_eq!(score.raw_score, 0.5);
+    assert_eq!(score.weighted_score, 0.5);
+}
+
+#[wasm_bindgen_test]
+fn test_score_session_invalid_strategy() {
+    let core = loaded_core();
+    assert!(core.score_session("not a strategy").is_err());
+}